@@ -0,0 +1,129 @@
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    MoveUpLeft,
+    MoveUpRight,
+    MoveDownLeft,
+    MoveDownRight,
+    Wait,
+    PickUp,
+    Use,
+    Inventory,
+    Equip,
+    Unequip,
+    Search,
+    Drink,
+    Read,
+    Eat,
+    Console,
+    Log,
+    Overview,
+    CycleMinimap,
+    CyclePalette,
+    DebugOverlay,
+    KeyBindings,
+    Quit,
+}
+
+impl Action {
+    // also the order of the bindings screen and of a saved config
+    pub const ALL: [Action; 26] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::MoveUpLeft,
+        Action::MoveUpRight,
+        Action::MoveDownLeft,
+        Action::MoveDownRight,
+        Action::Wait,
+        Action::PickUp,
+        Action::Use,
+        Action::Inventory,
+        Action::Equip,
+        Action::Unequip,
+        Action::Search,
+        Action::Drink,
+        Action::Read,
+        Action::Eat,
+        Action::Console,
+        Action::Log,
+        Action::Overview,
+        Action::CycleMinimap,
+        Action::CyclePalette,
+        Action::DebugOverlay,
+        Action::KeyBindings,
+        Action::Quit,
+    ];
+
+    // the name used in the config file
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::MoveUp => "move_up",
+            Action::MoveDown => "move_down",
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::MoveUpLeft => "move_up_left",
+            Action::MoveUpRight => "move_up_right",
+            Action::MoveDownLeft => "move_down_left",
+            Action::MoveDownRight => "move_down_right",
+            Action::Wait => "wait",
+            Action::PickUp => "pick_up",
+            Action::Use => "use",
+            Action::Inventory => "inventory",
+            Action::Equip => "equip",
+            Action::Unequip => "unequip",
+            Action::Search => "search",
+            Action::Drink => "drink",
+            Action::Read => "read",
+            Action::Eat => "eat",
+            Action::Console => "console",
+            Action::Log => "log",
+            Action::Overview => "overview",
+            Action::CycleMinimap => "minimap",
+            Action::CyclePalette => "palette",
+            Action::DebugOverlay => "debug_overlay",
+            Action::KeyBindings => "key_bindings",
+            Action::Quit => "quit",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    // whether the action uses up the player's turn, the inventory menu turns movement into
+    // selection and use into actually using something
+    pub fn takes_turn(&self, is_menu_open: bool) -> bool {
+        match self {
+            Action::Use => is_menu_open,
+            Action::Wait
+            | Action::PickUp
+            | Action::Equip
+            | Action::Unequip
+            | Action::Search
+            | Action::Drink
+            | Action::Read
+            | Action::Eat => !is_menu_open,
+            action => action.get_direction().is_some() && !is_menu_open,
+        }
+    }
+
+    // the step a movement action takes, x grows to the right and y downwards
+    pub fn get_direction(&self) -> Option<(isize, isize)> {
+        match self {
+            Action::MoveUp => Some((0, -1)),
+            Action::MoveDown => Some((0, 1)),
+            Action::MoveLeft => Some((-1, 0)),
+            Action::MoveRight => Some((1, 0)),
+            Action::MoveUpLeft => Some((-1, -1)),
+            Action::MoveUpRight => Some((1, -1)),
+            Action::MoveDownLeft => Some((-1, 1)),
+            Action::MoveDownRight => Some((1, 1)),
+            _ => None,
+        }
+    }
+}
@@ -1,6 +1,5 @@
-use std::sync::Arc;
-use futures::lock::{Mutex, MutexGuard};
-use crate::chat::Chat;
+use crate::chat::{Chat, MessageCategory};
+use crate::effect::{Effect, EffectSystem};
 use crate::monster::Monster;
 use crate::player::Player;
 
@@ -10,20 +9,36 @@ pub(crate) struct BattleSystem {
 
 impl BattleSystem {
 
-    pub(crate) async fn start_battle<'a>(
+    pub(crate) fn start_battle(
         // map: &MapData,
-        player: &mut MutexGuard<'a, Player>,
+        player: &mut Player,
         monster: &mut Monster,
-        chat: &Arc<Mutex<Chat>>
+        chat: &mut Chat
     ) {
         //monster.in_battle = true;
-        monster.status.health -= player.status.str;
+        let player_status = player.get_effective_status();
+        let damage = BattleSystem::calculate_damage(player_status.str, monster.status.def);
+        EffectSystem::apply_to_status(Effect::Damage(damage), &mut monster.status);
 
-        chat.lock().await.process_debug_message(&format!("monster has: {} hp left", monster.status.health), 1);
-        
-        if monster.status.health.is_negative() {
-            chat.lock().await.process_debug_message("monster has no hp left", 1);
+        chat.process_categorised_message(&format!("You hit the {} for {} damage.", monster.get_name(), damage), MessageCategory::Combat);
+        chat.process_debug_message(&format!("monster has: {} hp left", monster.status.health), 1);
+
+        if monster.status.health <= 0 {
+            chat.process_debug_message("monster has no hp left", 1);
             monster.is_alive = false;
+            return;
         }
+
+        // monster strikes back against the player's effective defence
+        let damage = BattleSystem::calculate_damage(monster.status.str, player_status.def);
+        let damage = EffectSystem::apply_to_player(Effect::Damage(damage), player);
+        chat.process_categorised_message(&format!("The {} hits you for {} damage.", monster.get_name(), damage), MessageCategory::Combat);
+
+        EffectSystem::check_player_death(player, chat);
+    }
+
+    fn calculate_damage(attack: i32, defence: i32) -> i32 {
+        // a hit always does at least one point of damage
+        (attack - defence).max(1)
     }
 }
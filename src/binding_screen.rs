@@ -0,0 +1,92 @@
+use crate::input::{KeyBinding, KeyBindings};
+use crate::action::Action;
+use crossterm::event::{KeyCode, KeyEvent};
+
+// the full-screen list of actions, the selected one can be given a new key
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct BindingScreen {
+    pub selected_index: usize,
+    pub is_capturing: bool,
+}
+
+pub enum BindingScreenAction {
+    None,
+    Message(String),
+    Close,
+}
+
+impl BindingScreen {
+    pub(crate) fn new() -> Self {
+        BindingScreen::default()
+    }
+
+    // the screen has fixed keys of its own so a broken config can always be repaired from here
+    pub(crate) fn handle_key(
+        &mut self,
+        event: &KeyEvent,
+        key_bindings: &mut KeyBindings,
+    ) -> BindingScreenAction {
+        let selected_action = Action::ALL[self.selected_index];
+
+        if self.is_capturing {
+            self.is_capturing = false;
+            if event.code == KeyCode::Esc {
+                return BindingScreenAction::None;
+            }
+            let binding = KeyBinding::from_event(event);
+            key_bindings.rebind(selected_action, binding);
+            return BindingScreenAction::Message(format!(
+                "Bound {} to {}.",
+                selected_action.name(),
+                binding
+            ));
+        }
+
+        match event.code {
+            KeyCode::Up => self.selected_index = self.selected_index.saturating_sub(1),
+            KeyCode::Down => {
+                self.selected_index = (self.selected_index + 1).min(Action::ALL.len() - 1)
+            }
+            KeyCode::Enter => self.is_capturing = true,
+            KeyCode::Tab => {
+                key_bindings.reset_to(key_bindings.preset.next());
+                return BindingScreenAction::Message(format!(
+                    "Switched to the {} preset.",
+                    key_bindings.preset.name()
+                ));
+            }
+            KeyCode::Char('w') => {
+                return BindingScreenAction::Message(match key_bindings.save() {
+                    Ok(()) => format!(
+                        "Saved the key bindings to {}.",
+                        key_bindings.path.display()
+                    ),
+                    Err(error) => format!("Could not save the key bindings: {}", error),
+                });
+            }
+            KeyCode::Esc => return BindingScreenAction::Close,
+            _ => {}
+        }
+        BindingScreenAction::None
+    }
+
+    pub(crate) fn get_lines(&self, key_bindings: &KeyBindings) -> Vec<String> {
+        Action::ALL
+            .iter()
+            .enumerate()
+            .map(|(index, action)| {
+                let cursor = if index == self.selected_index {
+                    ">"
+                } else {
+                    " "
+                };
+                let keys = if index == self.selected_index && self.is_capturing {
+                    "press a key, esc cancels".to_string()
+                } else {
+                    key_bindings.get_label(*action)
+                };
+                format!("{} {:<16}{}", cursor, action.name(), keys)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,137 @@
+use crate::vec2::Vec2;
+
+// the camera only scrolls once the target leaves the dead-zone around the viewport center
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Camera {
+    pub offset: Vec2,
+    pub dead_zone: Vec2,
+}
+
+impl Camera {
+    pub(crate) fn new(dead_zone: Vec2) -> Self {
+        Camera {
+            offset: Vec2::ZERO,
+            dead_zone,
+        }
+    }
+
+    pub(crate) fn with_offset(mut self, offset: Vec2) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub(crate) fn follow(&mut self, target: Vec2, map_size: Vec2, view_size: Vec2) {
+        self.offset = Vec2::new(
+            Camera::follow_axis(
+                self.offset.x,
+                target.x,
+                self.dead_zone.x,
+                map_size.x,
+                view_size.x,
+            ),
+            Camera::follow_axis(
+                self.offset.y,
+                target.y,
+                self.dead_zone.y,
+                map_size.y,
+                view_size.y,
+            ),
+        );
+    }
+
+    fn follow_axis(
+        offset: usize,
+        target: usize,
+        dead_zone: usize,
+        map_length: usize,
+        view_length: usize,
+    ) -> usize {
+        // maps that fit on screen never scroll
+        if map_length <= view_length {
+            return 0;
+        }
+
+        // a dead-zone wider than half the view would let the target walk off screen
+        let dead_zone = dead_zone.min(view_length.saturating_sub(1) / 2);
+        let center = offset + view_length / 2;
+        let offset = if target + dead_zone < center {
+            (target + dead_zone).saturating_sub(view_length / 2)
+        } else if target > center + dead_zone {
+            target - dead_zone - view_length / 2
+        } else {
+            offset
+        };
+
+        offset.min(map_length - view_length)
+    }
+
+    // moves freely, clamp keeps it inside the map before the next draw
+    pub(crate) fn scroll(&mut self, delta_x: isize, delta_y: isize) {
+        self.offset = Vec2::new(
+            self.offset.x.saturating_add_signed(delta_x),
+            self.offset.y.saturating_add_signed(delta_y),
+        );
+    }
+
+    pub(crate) fn clamp(&mut self, map_size: Vec2, view_size: Vec2) {
+        self.offset = Vec2::new(
+            self.offset.x.min(map_size.x.saturating_sub(view_size.x)),
+            self.offset.y.min(map_size.y.saturating_sub(view_size.y)),
+        );
+    }
+
+    // map position of a viewport cell
+    pub(crate) fn to_map_position(self, view_x: usize, view_y: usize) -> Vec2 {
+        Vec2::new(self.offset.x + view_x, self.offset.y + view_y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a 20 wide view over a 100 wide map, the center column is at offset + 10
+    fn follow_x(offset: usize, target: usize, dead_zone: usize) -> usize {
+        Camera::follow_axis(offset, target, dead_zone, 100, 20)
+    }
+
+    #[test]
+    fn the_camera_holds_still_inside_the_dead_zone() {
+        for target in 7..=13 {
+            assert_eq!(follow_x(0, target, 3), 0);
+        }
+        assert_eq!(follow_x(0, 14, 3), 1);
+        assert_eq!(follow_x(40, 46, 3), 39);
+        assert_eq!(follow_x(40, 47, 3), 40);
+    }
+
+    #[test]
+    fn the_camera_stops_at_the_edges_of_the_map() {
+        assert_eq!(follow_x(40, 99, 3), 80);
+        assert_eq!(follow_x(40, 0, 3), 0);
+        // a map that fits on screen never scrolls
+        assert_eq!(Camera::follow_axis(5, 14, 3, 20, 20), 0);
+    }
+
+    #[test]
+    fn an_oversized_dead_zone_still_keeps_the_target_on_screen() {
+        let offset = follow_x(0, 20, 50);
+        assert_eq!(offset, 1);
+        assert!((offset..offset + 20).contains(&20));
+
+        let mut camera = Camera::new(Vec2::new(50, 50));
+        camera.follow(Vec2::new(60, 3), Vec2::new(100, 8), Vec2::new(20, 10));
+        // the target ends up in the last column, the map is too short to scroll down at all
+        assert_eq!(camera.offset, Vec2::new(41, 0));
+    }
+
+    #[test]
+    fn scrolling_is_clamped_back_inside_the_map() {
+        let mut camera = Camera::new(Vec2::new(3, 3)).with_offset(Vec2::new(75, 2));
+        camera.scroll(10, -5);
+        assert_eq!(camera.offset, Vec2::new(85, 0));
+        camera.clamp(Vec2::new(100, 40), Vec2::new(20, 10));
+        assert_eq!(camera.offset, Vec2::new(80, 0));
+        assert_eq!(camera.to_map_position(4, 6), Vec2::new(84, 6));
+    }
+}
@@ -1,94 +1,162 @@
-use crossterm::{terminal, QueueableCommand};
-use std::io::{stdout, Write};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
 
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum MessageCategory {
+    General,
+    Combat,
+    Loot,
+    Warning,
+    Debug,
+}
+
+impl MessageCategory {
+    pub const ALL: [MessageCategory; 5] = [
+        MessageCategory::General,
+        MessageCategory::Combat,
+        MessageCategory::Loot,
+        MessageCategory::Warning,
+        MessageCategory::Debug,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MessageCategory::General => "general",
+            MessageCategory::Combat => "combat",
+            MessageCategory::Loot => "loot",
+            MessageCategory::Warning => "warning",
+            MessageCategory::Debug => "debug",
+        }
+    }
+
+    fn default_severity(&self) -> Severity {
+        match self {
+            MessageCategory::Warning => Severity::Notice,
+            _ => Severity::Info,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Severity {
+    Info,
+    Notice,
+    Danger,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Message {
+    pub text: String,
+    pub category: MessageCategory,
+    pub severity: Severity,
+    // time since the session started, updated when a repeat gets collapsed into it
+    pub timestamp: Duration,
+    pub repeat_count: u32,
+}
+
+impl Message {
+    pub fn get_text(&self) -> String {
+        if self.repeat_count > 1 {
+            format!("{} x{}", self.text, self.repeat_count)
+        } else {
+            self.text.clone()
+        }
+    }
+
+    pub fn get_log_line(&self) -> String {
+        let seconds = self.timestamp.as_secs();
+        format!(
+            "[{:02}:{:02}] {}",
+            seconds / 60,
+            seconds % 60,
+            self.get_text()
+        )
+    }
+}
+
+// the message history for the log viewer, debug output also lives in its own overlay slots
 #[derive(Clone)]
 pub struct Chat {
-    pub chat: [String; 10],
-    pub input_counter: usize,
-    pub is_repeat_message: bool,
-    pub repeat_message_counter: i32,
-    pub previous_message: String,
+    pub messages: Vec<Message>,
+    pub debug_lines: BTreeMap<usize, String>,
+    started_at: Instant,
 }
 
 impl Chat {
     pub(crate) fn new() -> Self {
         Chat {
-            chat: [
-                "".to_string(),
-                "".to_string(),
-                "".to_string(),
-                "".to_string(),
-                "".to_string(),
-                "".to_string(),
-                "".to_string(),
-                "".to_string(),
-                "".to_string(),
-                "".to_string(),
-            ],
-            input_counter: 0,
-            is_repeat_message: false,
-            repeat_message_counter: 1,
-            previous_message: "".parse().unwrap(),
+            messages: Vec::new(),
+            debug_lines: BTreeMap::new(),
+            started_at: Instant::now(),
         }
     }
 
-    pub(crate) fn print_chat(&mut self) {
-        let mut stdout = stdout();
-        stdout
-            .queue(terminal::Clear(terminal::ClearType::FromCursorDown))
-            .unwrap();
-        for i in 0..self.chat.len() {
-            println!("{}", self.chat[i]);
-        }
+    // the newest gameplay messages, oldest first
+    pub fn get_chat_lines(&self, count: usize) -> Vec<&Message> {
+        let mut lines: Vec<&Message> = self
+            .messages
+            .iter()
+            .rev()
+            .filter(|message| message.category != MessageCategory::Debug)
+            .take(count)
+            .collect();
+        lines.reverse();
+        lines
     }
 
     pub(crate) fn clear_chat(&mut self) {
-        for i in 0..self.chat.len() {
-            self.chat[i] = "".parse().unwrap();
-        }
+        self.messages.clear();
     }
 
     pub(crate) fn process_chat_message(&mut self, message: &str) {
-        if self.input_counter == self.chat.len() {
-            self.input_counter = 0;
-        }
+        self.process_categorised_message(message, MessageCategory::General);
+    }
 
-        // check if message is the same as previous
-        if self.previous_message == message {
-            self.repeat_message_counter += 1;
-            let repeat_suffix = format!("x{}", self.repeat_message_counter);
-            let repeated_message = format!("{} {}", message, repeat_suffix);
-            let mut tmp = 0;
-            if self.input_counter > 0 {
-                tmp = 1;
-            }
-            self.chat[self.input_counter - tmp] = repeated_message;
-            self.is_repeat_message = true;
-        } else {
-            self.chat[self.input_counter] = message.parse().unwrap();
+    pub(crate) fn process_categorised_message(&mut self, message: &str, category: MessageCategory) {
+        self.process_message(message, category, category.default_severity());
+    }
 
-            self.input_counter += 1;
-            self.repeat_message_counter = 1;
+    pub(crate) fn process_message(
+        &mut self,
+        message: &str,
+        category: MessageCategory,
+        severity: Severity,
+    ) {
+        let timestamp = self.started_at.elapsed();
+        // monsters report what they are doing every step, so debug lines and gameplay messages
+        // are each compared among their own kind
+        let is_debug = category == MessageCategory::Debug;
+        let is_same_kind =
+            |message: &Message| (message.category == MessageCategory::Debug) == is_debug;
 
-            self.is_repeat_message = false;
+        // the same message twice in a row is collapsed into one line with a counter
+        let previous = self
+            .messages
+            .iter_mut()
+            .rev()
+            .find(|message| is_same_kind(message));
+        if let Some(previous) = previous {
+            if previous.text == message && previous.category == category {
+                previous.repeat_count += 1;
+                previous.timestamp = timestamp;
+                previous.severity = previous.severity.max(severity);
+                return;
+            }
         }
 
-        // store previous message
-        self.previous_message = message.parse().unwrap();
+        self.messages.push(Message {
+            text: message.to_string(),
+            category,
+            severity,
+            timestamp,
+            repeat_count: 1,
+        });
     }
 
-    pub(crate) fn process_debug_message(&mut self, message: &str, index: usize) {
-        self.chat[index] = message.parse().unwrap();
-    }
-
-    fn print_processed_input(&mut self) {
-        if self.is_repeat_message {
-            println!(
-                "{} x{}",
-                self.chat[self.input_counter], self.repeat_message_counter
-            );
-        } else {
-            println!("{}", self.chat[self.input_counter]);
-        }
+    // each slot holds the latest value for one debug readout, the history keeps them under the
+    // debug category which the message panel leaves out
+    pub(crate) fn process_debug_message(&mut self, message: &str, slot: usize) {
+        self.debug_lines.insert(slot, message.to_string());
+        self.process_categorised_message(message, MessageCategory::Debug);
     }
 }
@@ -0,0 +1,209 @@
+// the command line, everything it can set up before the first frame is drawn
+use project_aether::game::{GameConfig, MAP_NAMES};
+use project_aether::game_client::DEFAULT_DEAD_ZONE;
+use project_aether::input::KEY_BINDINGS_PATH;
+use project_aether::map_loader::MAPS_DIRECTORY;
+use project_aether::scheduler::TimingMode;
+use project_aether::vec2::Vec2;
+use std::path::PathBuf;
+
+// the map load_map builds from the seed instead of reading it from src/maps
+const GENERATED_MAP_NAME: &str = "test";
+
+pub(crate) const USAGE: &str = "usage: ProjectAether [options], see --help";
+
+pub(crate) const HELP: &str = "\
+usage: ProjectAether [options]
+       ProjectAether validate-maps [directory]
+
+starting a new game:
+  --map <name>        start on one of the maps below instead of map2
+  --generate          start on the map generated from the seed
+  --seed <phrase>     seed for every random roll and the generated map
+  --spawn <x,y>       start at this tile instead of the map's starting spot
+  --timing <mode>     turn (the default) or realtime
+  --fog               hide what the player can't see
+  --no-fog            show the whole map, the default
+
+continuing a game:
+  --load <slot>       load a save slot and skip the load screen
+
+replays:
+  --record <file>     write every event of a new game to a replay file
+  --replay <file>     play a replay back, then hand the game over
+  --step              with --replay, wait for a key before every event
+  --verify <file>     check a replay still plays out the same, without a terminal
+
+checking maps:
+  validate-maps       check every map in src/maps, or the given directory, and quit
+
+other:
+  --config <file>     key bindings file, keybindings.cfg by default
+  --dead-zone <x,y>   tiles off center the player can walk before the view scrolls, 4,2 by default
+  --help              show this and quit
+";
+
+// what to do with a replay file this session, if anything
+pub(crate) enum ReplayOption {
+    None,
+    Record(PathBuf),
+    Play {
+        path: PathBuf,
+        is_step_by_step: bool,
+    },
+    Verify(PathBuf),
+}
+
+pub(crate) struct Options {
+    // used for a new game, a loaded save or a replay brings its own
+    pub(crate) config: GameConfig,
+    pub(crate) load_slot: Option<String>,
+    // a new game was asked for on the command line, so the load screen is skipped
+    pub(crate) has_new_game_options: bool,
+    pub(crate) key_bindings_path: PathBuf,
+    pub(crate) dead_zone: Vec2,
+    pub(crate) replay_option: ReplayOption,
+}
+
+pub(crate) enum Command {
+    Help,
+    ValidateMaps(PathBuf),
+    Play(Options),
+}
+
+pub(crate) fn parse_arguments(arguments: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut arguments = arguments.peekable();
+    if arguments.next_if_eq("validate-maps").is_some() {
+        let directory = arguments.next().unwrap_or(MAPS_DIRECTORY.to_string());
+        if let Some(argument) = arguments.next() {
+            return Err(format!(
+                "validate-maps takes one directory, not '{}'",
+                argument
+            ));
+        }
+        return Ok(Command::ValidateMaps(PathBuf::from(directory)));
+    }
+
+    let mut options = Options {
+        config: GameConfig {
+            autosave: true,
+            ..GameConfig::default()
+        },
+        load_slot: None,
+        has_new_game_options: false,
+        key_bindings_path: PathBuf::from(KEY_BINDINGS_PATH),
+        dead_zone: DEFAULT_DEAD_ZONE,
+        replay_option: ReplayOption::None,
+    };
+    let mut is_step_by_step = false;
+    // the options that only mean something for a new game, kept to name them in errors
+    let mut new_game_options = Vec::new();
+
+    while let Some(argument) = arguments.next() {
+        let mut value = || {
+            arguments
+                .next()
+                .ok_or(format!("{} needs a value", argument))
+        };
+        let replay_option = match argument.as_str() {
+            "--help" | "-h" => return Ok(Command::Help),
+            "--map" => {
+                let name = value()?;
+                if !MAP_NAMES.contains(&name.as_str()) {
+                    return Err(format!(
+                        "there is no map called {}, pick one of {}",
+                        name,
+                        MAP_NAMES.join(", ")
+                    ));
+                }
+                options.config.start_map = name;
+                new_game_options.push(argument);
+                continue;
+            }
+            "--generate" => {
+                options.config.start_map = GENERATED_MAP_NAME.to_string();
+                new_game_options.push(argument);
+                continue;
+            }
+            "--seed" => {
+                options.config.seed_phrase = value()?;
+                new_game_options.push(argument);
+                continue;
+            }
+            "--spawn" => {
+                let text = value()?;
+                options.config.spawn = Some(
+                    Vec2::from_text(&text)
+                        .ok_or(format!("'{}' is not a position, write it as x,y", text))?,
+                );
+                new_game_options.push(argument);
+                continue;
+            }
+            "--timing" => {
+                let name = value()?;
+                options.config.timing = TimingMode::from_name(&name)
+                    .ok_or(format!("timing is turn or realtime, not '{}'", name))?;
+                new_game_options.push(argument);
+                continue;
+            }
+            "--fog" | "--no-fog" => {
+                options.config.fog_of_war = argument == "--fog";
+                new_game_options.push(argument);
+                continue;
+            }
+            "--load" => {
+                options.load_slot = Some(value()?);
+                continue;
+            }
+            "--config" => {
+                options.key_bindings_path = PathBuf::from(value()?);
+                continue;
+            }
+            "--dead-zone" => {
+                let text = value()?;
+                options.dead_zone = Vec2::from_text(&text)
+                    .ok_or(format!("'{}' is not a size, write it as x,y", text))?;
+                continue;
+            }
+            "--step" => {
+                is_step_by_step = true;
+                continue;
+            }
+            "--record" => ReplayOption::Record(PathBuf::from(value()?)),
+            "--replay" => ReplayOption::Play {
+                path: PathBuf::from(value()?),
+                is_step_by_step: false,
+            },
+            "--verify" => ReplayOption::Verify(PathBuf::from(value()?)),
+            _ => return Err(format!("unknown argument '{}'", argument)),
+        };
+        if !matches!(options.replay_option, ReplayOption::None) {
+            return Err("only one of --record, --replay and --verify can be given".to_string());
+        }
+        options.replay_option = replay_option;
+    }
+
+    // a save and a replay each carry the game they start from, so there is nothing left to set up
+    let starts_elsewhere = match &options.replay_option {
+        ReplayOption::Play { .. } | ReplayOption::Verify(_) => Some("a replay"),
+        _ if options.load_slot.is_some() => Some("--load"),
+        _ => None,
+    };
+    if let (Some(source), Some(option)) = (starts_elsewhere, new_game_options.first()) {
+        return Err(format!("{} can't be combined with {}", option, source));
+    }
+    if options.load_slot.is_some() && !matches!(options.replay_option, ReplayOption::None) {
+        return Err("a replay always starts from a new game, --load can't go with it".to_string());
+    }
+
+    options.replay_option = match options.replay_option {
+        ReplayOption::Play { path, .. } => ReplayOption::Play {
+            path,
+            is_step_by_step,
+        },
+        _ if is_step_by_step => return Err("--step only goes with --replay".to_string()),
+        replay_option => replay_option,
+    };
+    options.has_new_game_options = !new_game_options.is_empty();
+    Ok(Command::Play(options))
+}
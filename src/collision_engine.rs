@@ -1,18 +1,18 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashSet};
 
-use crate::chat::Chat;
+use crate::chat::{Chat, MessageCategory};
 use crate::map_data::MapData;
 
 use crate::player::Player;
 use crate::tile_set::{DEFAULT_TILE_SET, LADDER_TILE_SET, MONSTER_TILE_SET};
 use crate::MovementType;
-use crossterm::event::KeyCode;
 
 use crate::map_manager::MapManager;
-use futures::lock::{Mutex, MutexGuard};
-use std::io;
-use std::sync::Arc;
+use crate::action::Action;
+use crate::interaction_system::InteractionSystem;
+use crate::item::ItemKind;
 use crate::monster::Monster;
+use rand::Rng;
 
 use crate::monster_manager::MonsterManager;
 use crate::pathfinding::Pathfinding;
@@ -41,55 +41,43 @@ impl CollisionEngine {
         CollisionEngine {}
     }
 
-    pub(crate) async fn try_move_player(
+    pub(crate) fn try_move_player(
         &mut self,
         player: &mut Player,
-        chat: &mut Arc<Mutex<Chat>>,
+        chat: &mut Chat,
     ) -> Vec2 {
         let mut current_position = Vec2::ZERO;
-        let mut chat_guard = chat.lock().await;
-        match player.key_event {
-            KeyCode::Up => {
-                // Move up
-                chat_guard.process_chat_message("You walk up.");
-                return Vec2::new(player.position.x, player.position.y - 1);
-            }
-            KeyCode::Down => {
-                // Move down
-                chat_guard.process_chat_message("You walk down.");
-                return Vec2::new(player.position.x, player.position.y + 1);
-            }
-            KeyCode::Left => {
-                // Move left
-                chat_guard.process_chat_message("You walk left.");
-                return Vec2::new(player.position.x - 1, player.position.y);
-            }
-            KeyCode::Right => {
-                // Move right
-                chat_guard.process_chat_message("You walk right.");
-                return Vec2::new(player.position.x + 1, player.position.y);
-            }
-            KeyCode::Tab => {
-                println!("Please enter a command: ");
 
-                let mut input = String::new();
-                if let Err(_) = io::stdin().read_line(&mut input) {
-                    chat_guard.process_chat_message("Error reading command.");
+        if let Some((delta_x, delta_y)) = player.action.and_then(|action| action.get_direction()) {
+            chat.process_chat_message(&format!("You walk {}.", self.get_direction_name(delta_x, delta_y)));
+            return Vec2::new(
+                player.position.x.wrapping_add_signed(delta_x),
+                player.position.y.wrapping_add_signed(delta_y),
+            );
+        }
+
+        match player.action {
+            Some(Action::Wait) => {
+                chat.process_chat_message("You wait.");
+            }
+            Some(Action::Equip) => {
+                match player.inventory.first_equippable_index() {
+                    Some(index) => match player.equip_item(index) {
+                        Ok(message) => chat.process_chat_message(&message),
+                        Err(error) => chat.process_chat_message(&error.to_string()),
+                    },
+                    None => chat.process_chat_message("You have nothing to equip."),
                 }
-                // @TODO turn into actual command system
-                if input.trim() == "nofog" {
-                    if player.fog_of_war {
-                        chat_guard.process_chat_message("Removed fog of war.");
-                        player.fog_of_war = false;
-                    } else {
-                        chat_guard.process_chat_message("Added back fog of war.");
-                        player.fog_of_war = true;
-                    }
-                } else {
-                    chat_guard.process_chat_message("Invalid command.");
+            }
+            Some(Action::Unequip) => {
+                match player.equipment.first_occupied_slot() {
+                    Some(slot) => match player.unequip_item(slot) {
+                        Ok(message) => chat.process_chat_message(&message),
+                        Err(error) => chat.process_chat_message(&error.to_string()),
+                    },
+                    None => chat.process_chat_message("You have nothing equipped."),
                 }
             }
-
             _ => {}
         }
         current_position = player.position;
@@ -97,22 +85,48 @@ impl CollisionEngine {
         return current_position;
     }
 
-    pub(crate) async fn try_process_move<'a>(
+    fn get_direction_name(&self, delta_x: isize, delta_y: isize) -> &'static str {
+        match (delta_x.signum(), delta_y.signum()) {
+            (0, -1) => "up",
+            (0, 1) => "down",
+            (-1, 0) => "left",
+            (1, 0) => "right",
+            (-1, -1) => "up and to the left",
+            (1, -1) => "up and to the right",
+            (-1, 1) => "down and to the left",
+            _ => "down and to the right",
+        }
+    }
+
+    pub(crate) fn try_process_move(
         &mut self,
-        map_manager_clone: &mut MutexGuard<'a, MapManager>,
+        map_manager_clone: &mut MapManager,
         player: &mut Player,
-        chat: &mut Arc<Mutex<Chat>>,
+        monster_manager: &mut MonsterManager,
+        chat: &mut Chat,
         new_player_pos: Vec2,
+        rng: &mut impl Rng,
     ) -> MovementType {
+        let movement = map_manager_clone.get_movement();
         let map_index = map_manager_clone.current_map_index;
         let map = map_manager_clone.get_map_mut(map_index).expect("map data");
 
-        let space = map.map[new_player_pos.y][new_player_pos.x];
-        let tmp_tile = map.map[new_player_pos.y][new_player_pos.x].tile;
-        let is_tile_solid = map.map[new_player_pos.y][new_player_pos.x].is_solid;
-        let is_tile_traversable = map.map[new_player_pos.y][new_player_pos.x].is_traversable;
+        let delta_x = new_player_pos.x as i32 - player.position.x as i32;
+        let delta_y = new_player_pos.y as i32 - player.position.y as i32;
+        if !movement.can_step(&map.map, player.position, delta_x, delta_y) {
+            chat.process_chat_message("You can't squeeze between the walls.");
+            return MovementType::Unable;
+        }
+
+        // a step off the edge of a map without walls around it goes nowhere
+        let space = match map.get_space(new_player_pos) {
+            Some(space) => *space,
+            None => return MovementType::Unable,
+        };
+        let tmp_tile = space.tile;
+        let is_tile_solid = space.is_solid;
+        let is_tile_traversable = space.is_traversable;
         let tile_set = map.tile_set.clone();
-        let mut chat_guard = chat.lock().await;
         let res = self.check_for_multi_tile(map, tmp_tile, new_player_pos);
 
         if space.is_monster {
@@ -120,38 +134,39 @@ impl CollisionEngine {
         }
 
         if res == tile_set.ladder && tile_set.name == DEFAULT_TILE_SET.name {
-            if player.key_event == KeyCode::Up {
+            if player.action == Some(Action::MoveUp) {
                 return MovementType::LadderUp;
-            } else if player.key_event == KeyCode::Down {
+            } else if player.action == Some(Action::MoveDown) {
                 return MovementType::LadderDown;
             }
         } else if res == tile_set.ladder && tile_set.name == LADDER_TILE_SET.name {
-            if player.key_event == KeyCode::Up && player.position.y == 1 {
+            if player.action == Some(Action::MoveUp) && player.position.y == 1 {
                 return MovementType::LadderEnter;
-            } else if player.key_event == KeyCode::Down && player.position.y == 2 {
+            } else if player.action == Some(Action::MoveDown) && player.position.y == 2 {
                 return MovementType::LadderExit;
             }
         }
 
+        if is_tile_solid
+            && InteractionSystem::interact(map, player, monster_manager, chat, new_player_pos, rng)
+        {
+            return MovementType::Unable;
+        }
+
         if tmp_tile == tile_set.key {
-            chat_guard.process_chat_message("You pick up a rusty key.");
-            player.inventory.add_key(1);
+            let key_id = map.take_key_at(new_player_pos);
+            chat.process_categorised_message(&format!("You pick up a {} key.", key_id), MessageCategory::Loot);
+            player.inventory.add_key(&key_id);
             map.map[new_player_pos.y][new_player_pos.x] = Space::new(DEFAULT_TILE_SET.floor);
         } else if tmp_tile == tile_set.closed_door_side || tmp_tile == tile_set.closed_door_top {
-            if player.inventory.keys >= 1 {
-                player.inventory.remove_key(1);
-                chat_guard.process_chat_message("You unlock the door using a rusty key.");
-                map.map[new_player_pos.y][new_player_pos.x] =
-                    Space::new(DEFAULT_TILE_SET.open_door);
-            } else {
-                chat_guard.process_chat_message("You need a rusty key to open this door.");
-            };
+            self.try_unlock_door(map, player, chat, new_player_pos, rng);
         }
         if tile_set.name == DEFAULT_TILE_SET.name {
             if !is_tile_traversable {
                 return MovementType::Unable;
             }
             if !is_tile_solid {
+                InteractionSystem::step_on(map, player, monster_manager, chat, new_player_pos);
                 return MovementType::Normal;
             }
         } else if tile_set.name == LADDER_TILE_SET.name {
@@ -159,10 +174,58 @@ impl CollisionEngine {
                 return MovementType::Normal;
             }
         }
-        drop(chat_guard);
         return MovementType::Unable;
     }
 
+    fn try_unlock_door(
+        &mut self,
+        map: &mut MapData,
+        player: &mut Player,
+        chat: &mut Chat,
+        door_position: Vec2,
+        rng: &mut impl Rng,
+    ) {
+        let lock = map.get_lock_mut(door_position);
+
+        if let Some(key_index) = player.inventory.find_key_for(lock) {
+            let key_id = player.inventory.use_key(key_index);
+            chat.process_chat_message(&format!("You unlock the door using the {} key.", key_id));
+        } else if let Some(lockpick_index) = player
+            .inventory
+            .first_index_of(&ItemKind::Lockpick)
+            .filter(|_| lock.can_be_picked())
+        {
+            if rng.gen::<f64>() >= lock.pick_chance {
+                player.inventory.take_item(lockpick_index);
+                chat.process_categorised_message("You fail to pick the lock and your lockpick snaps.", MessageCategory::Warning);
+                return;
+            }
+            chat.process_chat_message("You pick the lock.");
+        } else {
+            chat.process_categorised_message(&format!("You need a {} key to open this door.", lock.id), MessageCategory::Warning);
+            return;
+        }
+
+        lock.is_locked = false;
+        map.map[door_position.y][door_position.x] = Space::new(DEFAULT_TILE_SET.open_door);
+    }
+
+    // swings a re-locking door shut once the player has stepped off it
+    fn relock_door(&mut self, map: &mut MapData, position: Vec2, chat: &mut Chat) {
+        if let Some(lock) = map.locks.get_mut(&position).filter(|lock| lock.relocks && !lock.is_locked) {
+            lock.is_locked = true;
+            map.map[position.y][position.x] = Space::new(lock.closed_tile);
+            chat.process_categorised_message("The door swings shut and locks behind you.", MessageCategory::Warning);
+        }
+    }
+
+    fn report_item_pile(&mut self, map: &MapData, chat: &mut Chat, position: Vec2) {
+        if let Some(items) = map.item_piles.get(&position) {
+            let names: Vec<&str> = items.iter().map(|item| item.name.as_str()).collect();
+            chat.process_categorised_message(&format!("You see here: {}.", names.join(", ")), MessageCategory::Loot);
+        }
+    }
+
     fn is_tile_monster(&self, monster: char) -> bool {
         let monster_variants = [MONSTER_TILE_SET.snake, MONSTER_TILE_SET.goblin];
 
@@ -174,10 +237,11 @@ impl CollisionEngine {
         false
     }
 
-    pub(crate) async fn update_player_position<'a>(
+    pub(crate) fn update_player_position(
         &mut self,
-        map_manager_clone: &mut MutexGuard<'a, MapManager>,
+        map_manager_clone: &mut MapManager,
         player: &mut Player,
+        chat: &mut Chat,
         new_player_position: Vec2,
     ) {
         let map_index = map_manager_clone.current_map_index;
@@ -185,19 +249,22 @@ impl CollisionEngine {
         let tmp_tile = map.map[new_player_position.y][new_player_position.x].tile;
         let pos = player.position.clone();
         map.map[pos.y][pos.x] = Space::new(self.update_player_previous_tile(player, tmp_tile));
+        map.restore_object_tile(pos);
+        self.relock_door(map, pos, chat);
+        self.report_item_pile(map, chat, new_player_position);
         player.position = new_player_position;
         player.tile_below_player = tmp_tile;
         map.set_player_position(new_player_position);
         player.update_tile_below_player(tmp_tile);
     }
 
-    pub(crate) async fn update_player_vision<'a>(
+    pub(crate) fn update_player_vision(
         &mut self,
-        map_manager_clone: &mut MutexGuard<'a, MapManager>,
+        map_manager_clone: &mut MapManager,
         player: &Player,
         _new_player_position: Vec2,
     ) {
-        //let mut map_manager_guard = map_manager_clone.lock().await;
+        //let mut map_manager_guard = map_manager_clone.lock();
         let map_index = map_manager_clone.current_map_index;
         let map_data = map_manager_clone.get_map_mut(map_index).expect("map data");
         map_data.set_player_vision(player, _new_player_position);
@@ -236,17 +303,23 @@ impl CollisionEngine {
         return "".to_string();
     }
 
-    pub(crate) async fn try_move_monsters<'a>(
+    pub(crate) fn try_move_monsters(
         &mut self,
-        player: &MutexGuard<'a, Player>,
+        player: &Player,
         monster_manager: &mut MonsterManager,
-        map_guard: &mut MutexGuard<'a, MapManager>,
-        chat: &mut Arc<Mutex<Chat>>,
-    ) -> HashMap<i32, Vec2> {
-        let mut new_monsters_position = HashMap::<i32, Vec2>::new();
+        map_guard: &mut MapManager,
+        chat: &mut Chat,
+        rng: &mut impl Rng,
+    ) -> BTreeMap<i32, Vec2> {
+        let mut new_monsters_position = BTreeMap::<i32, Vec2>::new();
+        let movement = map_guard.get_movement();
         let map_index = map_guard.current_map_index;
         if let Some(map_data) = map_guard.get_map_mut(map_index) {
             for monster in monster_manager.get_monsters_mut().values_mut() {
+                // only monsters with enough energy get a move this round
+                if !monster.energy.is_ready() {
+                    continue;
+                }
 
                 let cur_monster_pos = monster.position;
                 let mut new_pos = cur_monster_pos;
@@ -259,16 +332,16 @@ impl CollisionEngine {
                         cur_monster_pos,
                         player.position,
                         radius,
-                    )
-                        .await;
+                        movement,
+                    );
 
                     if new_pos == cur_monster_pos {
                         // if new pos is the same as cur mon pos than no path found to the player within given radius
                         // make the monster wander
-                        new_pos = Pathfinding::wander(cur_monster_pos, &map_data.map);
-                        chat.lock().await.process_debug_message("monster is wandering", 0);
+                        new_pos = Pathfinding::wander(cur_monster_pos, &map_data.map, movement, rng);
+                        chat.process_debug_message("monster is wandering", 0);
                     } else {
-                        chat.lock().await.process_debug_message("monster stopped wandering", 0);
+                        chat.process_debug_message("monster stopped wandering", 0);
                     }
                 }
 
@@ -278,13 +351,13 @@ impl CollisionEngine {
         new_monsters_position
     }
 
-    pub(crate) async fn process_monsters_move<'a>(
+    pub(crate) fn process_monsters_move(
         &mut self,
-        new_monsters_position: &mut HashMap<i32, Vec2>,
-        map_manager_clone: &mut MutexGuard<'a, MapManager>,
-        monster_manager: &mut MutexGuard<'a, MonsterManager>,
-    ) -> HashMap<i32, Vec2> {
-        let mut processed_monsters_move = HashMap::<i32, Vec2>::new();
+        new_monsters_position: &mut BTreeMap<i32, Vec2>,
+        map_manager_clone: &mut MapManager,
+        monster_manager: &mut MonsterManager,
+    ) -> BTreeMap<i32, Vec2> {
+        let mut processed_monsters_move = BTreeMap::<i32, Vec2>::new();
 
         for monster in monster_manager.get_monsters_mut().values_mut() {
             if let Some(new_enemy_pos) = new_monsters_position.get_mut(&monster.id) {
@@ -313,9 +386,9 @@ impl CollisionEngine {
 
     fn remove_duplicate_monster_positions(
         &self,
-        new_monsters_position: HashMap<i32, Vec2>,
-    ) -> HashMap<i32, Vec2> {
-        let mut non_duplicate_positions = HashMap::<i32, Vec2>::new();
+        new_monsters_position: BTreeMap<i32, Vec2>,
+    ) -> BTreeMap<i32, Vec2> {
+        let mut non_duplicate_positions = BTreeMap::<i32, Vec2>::new();
         let mut previous_positions: HashSet<Vec2> = HashSet::new();
 
         for (key, pos) in new_monsters_position {
@@ -330,11 +403,11 @@ impl CollisionEngine {
         non_duplicate_positions
     }
 
-    pub(crate) async fn update_monsters_position<'a>(
+    pub(crate) fn update_monsters_position(
         &mut self,
-        map_manager_clone: &mut MutexGuard<'a, MapManager>,
-        monster_manager: &mut MutexGuard<'a, MonsterManager>,
-        processed_monsters_positions: HashMap<i32, Vec2>,
+        map_manager_clone: &mut MapManager,
+        monster_manager: &mut MonsterManager,
+        processed_monsters_positions: BTreeMap<i32, Vec2>,
     ) {
         for monster in monster_manager.get_monsters_mut().values_mut() {
             if let Some(new_mons_pos) = processed_monsters_positions.get(&monster.id) {
@@ -343,6 +416,7 @@ impl CollisionEngine {
                 let tmp_tile = map_data.map[new_mons_pos.y][new_mons_pos.x].tile;
 
                 map_data.map[monster.position.y][monster.position.x] = Space::new(self.update_monster_previous_tile(monster, tmp_tile));
+                map_data.restore_object_tile(monster.position);
                 monster.position = *new_mons_pos;
                 monster.tile_below = tmp_tile;
 
@@ -389,4 +463,116 @@ impl CollisionEngine {
 
         tmp_tile
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::Inventory;
+    use crate::item::Item;
+    use crate::lock::{Lock, MASTER_KEY_ID};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const DOOR: Vec2 = Vec2::new(1, 0);
+
+    // a door between two floor tiles and a player carrying only what the test gives them
+    fn door_with_lock(lock: Lock) -> (MapData, Player) {
+        let mut map = MapData::from_rows(&[".|."]);
+        map.locks.insert(DOOR, lock);
+        let mut player = Player::new();
+        player.inventory = Inventory::new();
+        (map, player)
+    }
+
+    fn unlock(map: &mut MapData, player: &mut Player, chat: &mut Chat) -> bool {
+        let mut rng = StdRng::seed_from_u64(0);
+        CollisionEngine::new().try_unlock_door(map, player, chat, DOOR, &mut rng);
+        !map.locks[&DOOR].is_locked
+    }
+
+    fn last_message(chat: &Chat) -> String {
+        chat.messages.last().expect("a message").get_text()
+    }
+
+    #[test]
+    fn only_the_matching_key_or_a_master_key_opens_a_lock() {
+        let mut chat = Chat::new();
+        let mut lock = Lock::new("gold", DEFAULT_TILE_SET.closed_door_side);
+        lock.pick_chance = 0.0;
+
+        let (mut map, mut player) = door_with_lock(lock.clone());
+        player.inventory.add_key("rusty");
+        assert!(!unlock(&mut map, &mut player, &mut chat));
+        assert_eq!(last_message(&chat), "You need a gold key to open this door.");
+        assert_eq!(map.map[0][1].tile, DEFAULT_TILE_SET.closed_door_side);
+
+        // with both in the pocket the gold key is spent and the master key kept
+        player.inventory.add_key(MASTER_KEY_ID);
+        player.inventory.add_key("gold");
+        assert!(unlock(&mut map, &mut player, &mut chat));
+        assert_eq!(last_message(&chat), "You unlock the door using the gold key.");
+        assert_eq!(player.inventory.keys, vec!["rusty", MASTER_KEY_ID]);
+        assert_eq!(map.map[0][1].tile, DEFAULT_TILE_SET.open_door);
+
+        // master keys open everything and are never used up
+        let (mut map, mut player) = door_with_lock(lock);
+        player.inventory.add_key(MASTER_KEY_ID);
+        assert!(unlock(&mut map, &mut player, &mut chat));
+        assert_eq!(player.inventory.keys, vec![MASTER_KEY_ID]);
+    }
+
+    #[test]
+    fn a_failed_pick_snaps_the_lockpick() {
+        let mut chat = Chat::new();
+        let mut lock = Lock::rusty(DEFAULT_TILE_SET.closed_door_side);
+
+        lock.pick_chance = 1.0;
+        let (mut map, mut player) = door_with_lock(lock.clone());
+        player.inventory.add_item(Item::lockpick());
+        assert!(unlock(&mut map, &mut player, &mut chat));
+        assert_eq!(last_message(&chat), "You pick the lock.");
+        assert_eq!(player.inventory.items.len(), 1);
+
+        lock.pick_chance = f64::MIN_POSITIVE;
+        let (mut map, mut player) = door_with_lock(lock.clone());
+        player.inventory.add_item(Item::lockpick());
+        assert!(!unlock(&mut map, &mut player, &mut chat));
+        assert_eq!(
+            last_message(&chat),
+            "You fail to pick the lock and your lockpick snaps."
+        );
+        assert!(player.inventory.items.is_empty());
+
+        // a lock that can't be picked leaves the lockpicks alone
+        lock.pick_chance = 0.0;
+        let (mut map, mut player) = door_with_lock(lock);
+        player.inventory.add_item(Item::lockpick());
+        assert!(!unlock(&mut map, &mut player, &mut chat));
+        assert_eq!(last_message(&chat), "You need a rusty key to open this door.");
+        assert_eq!(player.inventory.items.len(), 1);
+    }
+
+    #[test]
+    fn a_relocking_door_shuts_once_the_player_steps_off() {
+        let mut chat = Chat::new();
+        let mut collision_engine = CollisionEngine::new();
+        let mut lock = Lock::rusty(DEFAULT_TILE_SET.closed_door_top);
+        lock.relocks = true;
+
+        let (mut map, mut player) = door_with_lock(lock.clone());
+        player.inventory.add_key("rusty");
+        assert!(unlock(&mut map, &mut player, &mut chat));
+        collision_engine.relock_door(&mut map, DOOR, &mut chat);
+        assert!(map.locks[&DOOR].is_locked);
+        assert_eq!(map.map[0][1].tile, DEFAULT_TILE_SET.closed_door_top);
+        assert_eq!(last_message(&chat), "The door swings shut and locks behind you.");
+
+        lock.relocks = false;
+        let (mut map, mut player) = door_with_lock(lock);
+        player.inventory.add_key("rusty");
+        assert!(unlock(&mut map, &mut player, &mut chat));
+        collision_engine.relock_door(&mut map, DOOR, &mut chat);
+        assert!(!map.locks[&DOOR].is_locked);
+        assert_eq!(map.map[0][1].tile, DEFAULT_TILE_SET.open_door);
+    }
+}
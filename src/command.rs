@@ -0,0 +1,350 @@
+use std::fmt;
+
+use crate::item::ITEM_NAMES;
+use crate::map_manager::MAP_NAMES;
+use crate::monster::MONSTER_NAMES;
+use crate::scheduler::TimingMode;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ArgumentKind {
+    Integer,
+    Text,
+    // one of a fixed set of words, these are also what tab completion offers
+    Choice(&'static [&'static str]),
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ArgumentSpec {
+    pub name: &'static str,
+    pub kind: ArgumentKind,
+    pub is_optional: bool,
+}
+
+impl ArgumentSpec {
+    const fn required(name: &'static str, kind: ArgumentKind) -> Self {
+        ArgumentSpec {
+            name,
+            kind,
+            is_optional: false,
+        }
+    }
+
+    const fn optional(name: &'static str, kind: ArgumentKind) -> Self {
+        ArgumentSpec {
+            name,
+            kind,
+            is_optional: true,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Argument {
+    Integer(i64),
+    Text(String),
+}
+
+impl Argument {
+    pub(crate) fn as_integer(&self) -> Option<i64> {
+        match self {
+            Argument::Integer(value) => Some(*value),
+            Argument::Text(_) => None,
+        }
+    }
+
+    pub(crate) fn as_text(&self) -> &str {
+        match self {
+            Argument::Integer(_) => "",
+            Argument::Text(text) => text,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub arguments: &'static [ArgumentSpec],
+    pub help: &'static str,
+}
+
+impl CommandSpec {
+    pub fn get_usage(&self) -> String {
+        let arguments: Vec<String> = self
+            .arguments
+            .iter()
+            .map(|argument| {
+                if argument.is_optional {
+                    format!("[{}]", argument.name)
+                } else {
+                    format!("<{}>", argument.name)
+                }
+            })
+            .collect();
+
+        if arguments.is_empty() {
+            self.name.to_string()
+        } else {
+            format!("{} {}", self.name, arguments.join(" "))
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParsedCommand {
+    pub name: &'static str,
+    pub arguments: Vec<Argument>,
+}
+
+impl ParsedCommand {
+    pub(crate) fn get(&self, index: usize) -> Option<&Argument> {
+        self.arguments.get(index)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum CommandError {
+    Empty,
+    UnknownCommand(String),
+    MissingArgument(String),
+    InvalidArgument {
+        argument: &'static str,
+        value: String,
+        expected: String,
+    },
+    TooManyArguments(String),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CommandError::Empty => write!(f, "Type a command, or help for a list."),
+            CommandError::UnknownCommand(name) => {
+                write!(f, "Unknown command '{}', try help.", name)
+            }
+            CommandError::MissingArgument(usage) => write!(f, "Missing argument, usage: {}", usage),
+            CommandError::InvalidArgument {
+                argument,
+                value,
+                expected,
+            } => write!(
+                f,
+                "'{}' is not a valid {}, expected {}.",
+                value, argument, expected
+            ),
+            CommandError::TooManyArguments(usage) => {
+                write!(f, "Too many arguments, usage: {}", usage)
+            }
+        }
+    }
+}
+
+const ON_OFF: &[&str] = &["on", "off"];
+
+// every command the console knows about, the command system runs them
+pub const COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        name: "help",
+        arguments: &[ArgumentSpec::optional("command", ArgumentKind::Text)],
+        help: "Lists the commands, or explains one of them.",
+    },
+    CommandSpec {
+        name: "teleport",
+        arguments: &[
+            ArgumentSpec::required("x", ArgumentKind::Integer),
+            ArgumentSpec::required("y", ArgumentKind::Integer),
+        ],
+        help: "Moves the player to a walkable tile on the current map.",
+    },
+    CommandSpec {
+        name: "spawn",
+        arguments: &[ArgumentSpec::required(
+            "monster",
+            ArgumentKind::Choice(MONSTER_NAMES),
+        )],
+        help: "Spawns a monster on a free tile next to the player.",
+    },
+    CommandSpec {
+        name: "give",
+        arguments: &[
+            ArgumentSpec::required("item", ArgumentKind::Choice(ITEM_NAMES)),
+            ArgumentSpec::optional("count", ArgumentKind::Integer),
+        ],
+        help: "Puts items into the player's inventory.",
+    },
+    CommandSpec {
+        name: "heal",
+        arguments: &[ArgumentSpec::optional("amount", ArgumentKind::Integer)],
+        help: "Restores health, all of it when no amount is given.",
+    },
+    CommandSpec {
+        name: "reveal",
+        arguments: &[],
+        help: "Marks the whole current map as explored.",
+    },
+    CommandSpec {
+        name: "godmode",
+        arguments: &[ArgumentSpec::optional(
+            "state",
+            ArgumentKind::Choice(ON_OFF),
+        )],
+        help: "Toggles taking no damage.",
+    },
+    CommandSpec {
+        name: "loadmap",
+        arguments: &[ArgumentSpec::required(
+            "map",
+            ArgumentKind::Choice(MAP_NAMES),
+        )],
+        help: "Switches to another loaded map.",
+    },
+    CommandSpec {
+        name: "seed",
+        arguments: &[ArgumentSpec::optional("phrase", ArgumentKind::Text)],
+        help: "Shows the seed of the generated map, or regenerates it from a new one.",
+    },
+    CommandSpec {
+        name: "fog",
+        arguments: &[ArgumentSpec::optional(
+            "state",
+            ArgumentKind::Choice(ON_OFF),
+        )],
+        help: "Toggles the fog of war.",
+    },
+    CommandSpec {
+        name: "diagonals",
+        arguments: &[ArgumentSpec::optional(
+            "state",
+            ArgumentKind::Choice(ON_OFF),
+        )],
+        help: "Toggles diagonal movement on maps that don't set their own.",
+    },
+    CommandSpec {
+        name: "timing",
+        arguments: &[ArgumentSpec::optional(
+            "mode",
+            ArgumentKind::Choice(TimingMode::NAMES),
+        )],
+        help: "Shows or switches between turn-based and real-time play.",
+    },
+    CommandSpec {
+        name: "save",
+        arguments: &[ArgumentSpec::optional("slot", ArgumentKind::Text)],
+        help: "Saves the game, to the quicksave slot when no name is given.",
+    },
+    CommandSpec {
+        name: "load",
+        arguments: &[ArgumentSpec::optional("slot", ArgumentKind::Text)],
+        help: "Loads a saved game, the quicksave when no name is given.",
+    },
+    CommandSpec {
+        name: "edit",
+        arguments: &[ArgumentSpec::optional(
+            "state",
+            ArgumentKind::Choice(ON_OFF),
+        )],
+        help: "Toggles the map editor for the current map.",
+    },
+    CommandSpec {
+        name: "saves",
+        arguments: &[],
+        help: "Lists the saved games.",
+    },
+];
+
+pub fn find_command(name: &str) -> Option<&'static CommandSpec> {
+    COMMANDS.iter().find(|command| command.name == name)
+}
+
+pub fn parse_command(line: &str) -> Result<ParsedCommand, CommandError> {
+    let mut words = line.split_whitespace();
+    let name = words.next().ok_or(CommandError::Empty)?;
+    let command = find_command(name).ok_or(CommandError::UnknownCommand(name.to_string()))?;
+    let values: Vec<&str> = words.collect();
+
+    if values.len() > command.arguments.len() {
+        return Err(CommandError::TooManyArguments(command.get_usage()));
+    }
+
+    let mut arguments = Vec::new();
+    for (index, spec) in command.arguments.iter().enumerate() {
+        let value = match values.get(index) {
+            Some(value) => *value,
+            None if spec.is_optional => break,
+            None => return Err(CommandError::MissingArgument(command.get_usage())),
+        };
+        arguments.push(parse_argument(spec, value)?);
+    }
+
+    Ok(ParsedCommand {
+        name: command.name,
+        arguments,
+    })
+}
+
+fn parse_argument(spec: &ArgumentSpec, value: &str) -> Result<Argument, CommandError> {
+    let invalid = |expected: String| CommandError::InvalidArgument {
+        argument: spec.name,
+        value: value.to_string(),
+        expected,
+    };
+
+    match spec.kind {
+        ArgumentKind::Integer => value
+            .parse()
+            .map(Argument::Integer)
+            .map_err(|_| invalid("a whole number".to_string())),
+        ArgumentKind::Text => Ok(Argument::Text(value.to_string())),
+        ArgumentKind::Choice(choices) => {
+            if choices.contains(&value) {
+                Ok(Argument::Text(value.to_string()))
+            } else {
+                Err(invalid(format!("one of {}", choices.join(", "))))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arguments_are_parsed_by_their_kind() {
+        assert_eq!(
+            parse_command("  teleport 3   -4 "),
+            Ok(ParsedCommand {
+                name: "teleport",
+                arguments: vec![Argument::Integer(3), Argument::Integer(-4)],
+            })
+        );
+        let give = parse_command("give lockpick").expect("a valid command");
+        assert_eq!(give.get(0).map(Argument::as_text), Some("lockpick"));
+        assert_eq!(give.get(1), None);
+        assert_eq!(parse_command("heal").map(|command| command.arguments), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn bad_input_says_what_was_expected() {
+        assert_eq!(parse_command("   "), Err(CommandError::Empty));
+        assert_eq!(
+            parse_command("fly 3"),
+            Err(CommandError::UnknownCommand("fly".to_string()))
+        );
+        assert_eq!(
+            parse_command("teleport 3"),
+            Err(CommandError::MissingArgument("teleport <x> <y>".to_string()))
+        );
+        assert_eq!(
+            parse_command("heal 5 6"),
+            Err(CommandError::TooManyArguments("heal [amount]".to_string()))
+        );
+        assert_eq!(
+            parse_command("teleport 3 up").map_err(|error| error.to_string()),
+            Err("'up' is not a valid y, expected a whole number.".to_string())
+        );
+        assert_eq!(
+            parse_command("fog maybe").map_err(|error| error.to_string()),
+            Err("'maybe' is not a valid state, expected one of on, off.".to_string())
+        );
+    }
+}
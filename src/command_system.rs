@@ -0,0 +1,331 @@
+use crate::chat::{Chat, MessageCategory};
+use crate::command::{parse_command, ParsedCommand, COMMANDS};
+use crate::effect::{Effect, EffectSystem};
+use crate::item::Item;
+use crate::map_factory::{MapFactory, GENERATED_MAP_SIZE, GENERATED_MAP_START};
+use crate::map_manager::{MapManager, GENERATED_MAP_INDEX};
+use crate::monster::Monster;
+use crate::monster_manager::MonsterManager;
+use crate::pathfinding::MovementMode;
+use crate::player::Player;
+use crate::save::{self, QUICKSAVE_SLOT};
+use crate::scheduler::{Scheduler, TimingMode};
+use crate::vec2::Vec2;
+use crate::MovementType;
+
+const MAX_GIVE_COUNT: i64 = 99;
+
+pub(crate) struct CommandSystem {}
+
+// commands that need the whole game rather than a few parts of it, the game carries these out
+pub(crate) enum CommandRequest {
+    Save(String),
+    Load(String),
+    // open or close the map editor, none flips it
+    Editor(Option<bool>),
+}
+
+impl CommandSystem {
+    // echoes the line into the message log, followed by the result or what went wrong
+    pub(crate) fn run(
+        line: &str,
+        player: &mut Player,
+        map_manager: &mut MapManager,
+        monster_manager: &mut MonsterManager,
+        scheduler: &mut Scheduler,
+        chat: &mut Chat,
+    ) -> Option<CommandRequest> {
+        chat.process_chat_message(&format!("> {}", line));
+
+        let command = match parse_command(line) {
+            Ok(command) => command,
+            Err(error) => {
+                chat.process_categorised_message(&error.to_string(), MessageCategory::Warning);
+                return None;
+            }
+        };
+
+        let result = match command.name {
+            "help" => CommandSystem::help(&command, chat),
+            "teleport" => CommandSystem::teleport(&command, player, map_manager),
+            "spawn" => CommandSystem::spawn(&command, player, map_manager, monster_manager),
+            "give" => CommandSystem::give(&command, player),
+            "heal" => CommandSystem::heal(&command, player),
+            "reveal" => {
+                map_manager.get_mut_current_map().reveal_all();
+                Ok("The whole map is revealed.".to_string())
+            }
+            "godmode" => {
+                player.is_god_mode = CommandSystem::get_toggle(&command, player.is_god_mode);
+                Ok(format!(
+                    "God mode {}.",
+                    CommandSystem::on_off(player.is_god_mode)
+                ))
+            }
+            "loadmap" => CommandSystem::load_map(&command, player, map_manager),
+            "seed" => CommandSystem::seed(&command, player, map_manager),
+            "fog" => {
+                player.fog_of_war = CommandSystem::get_toggle(&command, player.fog_of_war);
+                Ok(format!(
+                    "Fog of war {}.",
+                    CommandSystem::on_off(player.fog_of_war)
+                ))
+            }
+            "diagonals" => CommandSystem::diagonals(&command, map_manager),
+            "timing" => {
+                let mode = command.get(0).and_then(|argument| TimingMode::from_name(argument.as_text()));
+                if let Some(mode) = mode {
+                    scheduler.set_mode(mode);
+                }
+                Ok(format!("Timing: {}", scheduler.mode.name()))
+            }
+            "save" | "load" => {
+                let slot = command
+                    .get(0)
+                    .map_or(QUICKSAVE_SLOT, |argument| argument.as_text())
+                    .to_string();
+                return Some(if command.name == "save" {
+                    CommandRequest::Save(slot)
+                } else {
+                    CommandRequest::Load(slot)
+                });
+            }
+            "edit" => {
+                let state = command.get(0).map(|argument| argument.as_text() == "on");
+                return Some(CommandRequest::Editor(state));
+            }
+            "saves" => {
+                let slots = save::list_slots();
+                if slots.is_empty() {
+                    Ok("There are no saved games.".to_string())
+                } else {
+                    Ok(format!("Saved games: {}", slots.join(", ")))
+                }
+            }
+            _ => Err(format!("'{}' has no handler.", command.name)),
+        };
+
+        match result {
+            Ok(message) => chat.process_chat_message(&message),
+            Err(message) => {
+                chat.process_categorised_message(&message, MessageCategory::Warning)
+            }
+        }
+        None
+    }
+
+    fn on_off(state: bool) -> &'static str {
+        if state {
+            "on"
+        } else {
+            "off"
+        }
+    }
+
+    // an explicit on or off wins, no argument flips the current state
+    fn get_toggle(command: &ParsedCommand, current: bool) -> bool {
+        match command.get(0).map(|argument| argument.as_text()) {
+            Some("on") => true,
+            Some("off") => false,
+            _ => !current,
+        }
+    }
+
+    fn diagonals(command: &ParsedCommand, map_manager: &mut MapManager) -> Result<String, String> {
+        let is_on = map_manager.movement == MovementMode::EightWay;
+        map_manager.movement = if CommandSystem::get_toggle(command, is_on) {
+            MovementMode::EightWay
+        } else {
+            MovementMode::FourWay
+        };
+
+        // a map with its own movement line keeps it, say so rather than look broken
+        let current = map_manager.get_movement();
+        if current != map_manager.movement {
+            return Ok(format!(
+                "Diagonals {}, but this map always uses {} movement.",
+                CommandSystem::on_off(map_manager.movement == MovementMode::EightWay),
+                current.name()
+            ));
+        }
+        Ok(format!(
+            "Diagonals {}.",
+            CommandSystem::on_off(current == MovementMode::EightWay)
+        ))
+    }
+
+    fn help(command: &ParsedCommand, chat: &mut Chat) -> Result<String, String> {
+        if let Some(name) = command.get(0).map(|argument| argument.as_text()) {
+            return match COMMANDS.iter().find(|spec| spec.name == name) {
+                Some(spec) => Ok(format!("{} - {}", spec.get_usage(), spec.help)),
+                None => Err(format!("Unknown command '{}'.", name)),
+            };
+        }
+
+        for spec in COMMANDS {
+            chat.process_chat_message(&spec.get_usage());
+        }
+        Ok("help <command> explains one of them.".to_string())
+    }
+
+    fn teleport(
+        command: &ParsedCommand,
+        player: &mut Player,
+        map_manager: &mut MapManager,
+    ) -> Result<String, String> {
+        let coordinate = |index: usize| {
+            command
+                .get(index)
+                .and_then(|argument| argument.as_integer())
+                .and_then(|value| usize::try_from(value).ok())
+        };
+        let destination = match (coordinate(0), coordinate(1)) {
+            (Some(x), Some(y)) => Vec2::new(x, y),
+            _ => return Err("Coordinates can't be negative.".to_string()),
+        };
+
+        let map_data = map_manager.get_mut_current_map();
+        match map_data.get_space(destination) {
+            None => {
+                return Err(format!(
+                    "{},{} is outside the map.",
+                    destination.x, destination.y
+                ))
+            }
+            Some(space) if !space.is_traversable || space.is_occupied => {
+                return Err(format!(
+                    "{},{} is not walkable.",
+                    destination.x, destination.y
+                ))
+            }
+            Some(_) => {}
+        }
+
+        map_data.move_player_to(player, destination);
+        Ok(format!(
+            "Teleported to {},{}.",
+            destination.x, destination.y
+        ))
+    }
+
+    fn spawn(
+        command: &ParsedCommand,
+        player: &Player,
+        map_manager: &mut MapManager,
+        monster_manager: &mut MonsterManager,
+    ) -> Result<String, String> {
+        let name = command.get(0).map_or("", |argument| argument.as_text());
+        let monster_type =
+            Monster::tile_from_name(name).ok_or(format!("There is no monster called {}.", name))?;
+
+        let map_data = map_manager.get_mut_current_map();
+        let position = player.position;
+        let free_tile = (-1isize..=1)
+            .flat_map(|delta_y| (-1isize..=1).map(move |delta_x| (delta_x, delta_y)))
+            .filter_map(|(delta_x, delta_y)| {
+                Some(Vec2::new(
+                    position.x.checked_add_signed(delta_x)?,
+                    position.y.checked_add_signed(delta_y)?,
+                ))
+            })
+            .find(|candidate| {
+                map_data.get_space(*candidate).is_some_and(|space| {
+                    space.tile == map_data.tile_set.floor && !space.is_occupied
+                })
+            })
+            .ok_or("There is no room next to you.".to_string())?;
+
+        monster_manager.spawn_monster(map_data, monster_type, free_tile);
+        Ok(format!("A {} appears.", name))
+    }
+
+    fn give(command: &ParsedCommand, player: &mut Player) -> Result<String, String> {
+        let name = command.get(0).map_or("", |argument| argument.as_text());
+        let count = command
+            .get(1)
+            .and_then(|argument| argument.as_integer())
+            .unwrap_or(1);
+        if !(1..=MAX_GIVE_COUNT).contains(&count) {
+            return Err(format!("Count has to be between 1 and {}.", MAX_GIVE_COUNT));
+        }
+
+        let item = Item::from_name(name).ok_or(format!("There is no item called {}.", name))?;
+        for _ in 0..count {
+            player.inventory.add_item(item.clone());
+        }
+        Ok(format!("Gave {} x{}.", item.name, count))
+    }
+
+    fn heal(command: &ParsedCommand, player: &mut Player) -> Result<String, String> {
+        let amount = match command.get(0).and_then(|argument| argument.as_integer()) {
+            Some(amount) if amount <= 0 => return Err("Amount has to be positive.".to_string()),
+            Some(amount) => i32::try_from(amount).unwrap_or(i32::MAX),
+            None => player.status.max_health,
+        };
+
+        let healed = EffectSystem::apply_to_player(Effect::Heal(amount), player);
+        Ok(format!("Healed {} HP.", healed))
+    }
+
+    fn load_map(
+        command: &ParsedCommand,
+        player: &mut Player,
+        map_manager: &mut MapManager,
+    ) -> Result<String, String> {
+        let name = command.get(0).map_or("", |argument| argument.as_text());
+        let map_data = map_manager
+            .load_map(name, MovementType::Normal)
+            .ok_or(format!("Map {} is not loaded.", name))?;
+
+        // every map remembers where the player stood on it, fall back to the first floor tile
+        let position = map_data.find_player_position().or_else(|| {
+            map_data.map.iter().enumerate().find_map(|(pos_y, row)| {
+                row.iter()
+                    .position(|space| space.tile == map_data.tile_set.floor)
+                    .map(|pos_x| Vec2::new(pos_x, pos_y))
+            })
+        });
+        let position = position.ok_or(format!("Map {} has nowhere to stand.", name))?;
+
+        if map_data.find_player_position().is_none() {
+            player.update_tile_below_player(map_data.tile_set.floor);
+            map_data.set_player_position(position);
+        }
+        player.position = position;
+        Ok(format!("Loaded {}.", name))
+    }
+
+    fn seed(
+        command: &ParsedCommand,
+        player: &mut Player,
+        map_manager: &mut MapManager,
+    ) -> Result<String, String> {
+        let seed_phrase = match command.get(0) {
+            Some(argument) => argument.as_text().to_string(),
+            None => return Ok(format!("Seed: {}", map_manager.seed_phrase)),
+        };
+
+        // generating moves the player onto the new map, which only matters if they are on it
+        let is_on_generated_map = map_manager.current_map_index == GENERATED_MAP_INDEX;
+        let position = player.position;
+        let tile_below_player = player.tile_below_player;
+        let new_map = MapFactory::new().generate_map(
+            player,
+            GENERATED_MAP_SIZE,
+            GENERATED_MAP_SIZE,
+            GENERATED_MAP_START,
+            &seed_phrase,
+        );
+        if !is_on_generated_map {
+            player.position = position;
+            player.tile_below_player = tile_below_player;
+        }
+
+        map_manager.add_map(GENERATED_MAP_INDEX, new_map);
+        map_manager.seed_phrase = seed_phrase;
+        Ok(format!(
+            "Regenerated the generated map from seed {}.",
+            map_manager.seed_phrase
+        ))
+    }
+}
@@ -0,0 +1,244 @@
+use crossterm::event::KeyCode;
+
+use crate::command::{find_command, ArgumentKind, COMMANDS};
+
+// what is still a candidate for the word being typed, commands first and then argument choices
+fn get_candidates(line: &str) -> (usize, Vec<&'static str>) {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let is_new_word = line.is_empty() || line.ends_with(' ');
+    let word_index = if is_new_word {
+        words.len()
+    } else {
+        words.len() - 1
+    };
+    let partial = if is_new_word { "" } else { words[word_index] };
+
+    let options: Vec<&'static str> = if word_index == 0 {
+        COMMANDS.iter().map(|command| command.name).collect()
+    } else {
+        match find_command(words[0])
+            .and_then(|command| command.arguments.get(word_index - 1))
+            .map(|argument| argument.kind)
+        {
+            Some(ArgumentKind::Choice(choices)) => choices.to_vec(),
+            Some(_) if words[0] == "help" => COMMANDS.iter().map(|command| command.name).collect(),
+            _ => Vec::new(),
+        }
+    };
+
+    let candidates = options
+        .into_iter()
+        .filter(|option| option.starts_with(partial))
+        .collect();
+    (partial.len(), candidates)
+}
+
+fn get_common_prefix(candidates: &[&str]) -> String {
+    let first = candidates.first().copied().unwrap_or("");
+    let mut prefix_length = first.len();
+    for candidate in candidates.iter().skip(1) {
+        prefix_length = first
+            .chars()
+            .zip(candidate.chars())
+            .take(prefix_length)
+            .take_while(|(left, right)| left == right)
+            .count();
+    }
+    first[..prefix_length].to_string()
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ConsoleAction {
+    None,
+    Submit(String),
+    Close,
+}
+
+// the overlay's input line, submitted lines go to the command system
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Console {
+    pub is_open: bool,
+    pub input: String,
+    pub completions: Vec<&'static str>,
+    history: Vec<String>,
+    history_index: Option<usize>,
+}
+
+impl Console {
+    pub(crate) fn new() -> Self {
+        Console::default()
+    }
+
+    pub(crate) fn open(&mut self) {
+        self.is_open = true;
+        self.input.clear();
+        self.completions.clear();
+        self.history_index = None;
+    }
+
+    pub(crate) fn handle_key(&mut self, key_code: KeyCode) -> ConsoleAction {
+        if key_code != KeyCode::Tab {
+            self.completions.clear();
+        }
+
+        match key_code {
+            KeyCode::Esc => {
+                self.is_open = false;
+                return ConsoleAction::Close;
+            }
+            KeyCode::Enter => {
+                self.is_open = false;
+                let line = self.input.trim().to_string();
+                if !line.is_empty() {
+                    self.history.push(line.clone());
+                }
+                return ConsoleAction::Submit(line);
+            }
+            KeyCode::Tab => self.complete(),
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Up => self.browse_history(true),
+            KeyCode::Down => self.browse_history(false),
+            KeyCode::Char(character) => self.input.push(character),
+            _ => {}
+        }
+        ConsoleAction::None
+    }
+
+    fn complete(&mut self) {
+        let (partial_length, candidates) = get_candidates(&self.input);
+        if candidates.is_empty() {
+            return;
+        }
+
+        let prefix = get_common_prefix(&candidates);
+        self.input
+            .truncate(self.input.len().saturating_sub(partial_length));
+        self.input.push_str(&prefix);
+
+        if candidates.len() == 1 {
+            self.input.push(' ');
+        } else {
+            self.completions = candidates;
+        }
+    }
+
+    fn browse_history(&mut self, is_older: bool) {
+        if self.history.is_empty() {
+            return;
+        }
+
+        let index = match (self.history_index, is_older) {
+            (None, true) => Some(self.history.len() - 1),
+            (None, false) => None,
+            (Some(index), true) => Some(index.saturating_sub(1)),
+            (Some(index), false) if index + 1 < self.history.len() => Some(index + 1),
+            (Some(_), false) => None,
+        };
+
+        self.history_index = index;
+        self.input = index
+            .map(|index| self.history[index].clone())
+            .unwrap_or_default();
+    }
+
+    // the hint shown above the input, either the completions or the usage of the command typed
+    pub(crate) fn get_hint(&self) -> String {
+        if !self.completions.is_empty() {
+            return self.completions.join("  ");
+        }
+
+        match self.input.split_whitespace().next().and_then(find_command) {
+            Some(command) => format!("{} - {}", command.get_usage(), command.help),
+            None => "tab completes, enter runs, esc closes".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_line(console: &mut Console, line: &str) {
+        console.open();
+        for character in line.chars() {
+            console.handle_key(KeyCode::Char(character));
+        }
+    }
+
+    #[test]
+    fn a_single_candidate_is_completed_in_full() {
+        let mut console = Console::new();
+        type_line(&mut console, "te");
+        console.handle_key(KeyCode::Tab);
+        assert_eq!(console.input, "teleport ");
+        assert!(console.completions.is_empty());
+
+        type_line(&mut console, "spawn g");
+        console.handle_key(KeyCode::Tab);
+        assert_eq!(console.input, "spawn goblin ");
+
+        // help completes command names even though its argument is free text
+        type_line(&mut console, "help gi");
+        console.handle_key(KeyCode::Tab);
+        assert_eq!(console.input, "help give ");
+    }
+
+    #[test]
+    fn several_candidates_complete_their_common_prefix() {
+        let mut console = Console::new();
+        type_line(&mut console, "sa");
+        console.handle_key(KeyCode::Tab);
+        assert_eq!(console.input, "save");
+        assert_eq!(console.completions, ["save", "saves"]);
+        assert_eq!(console.get_hint(), "save  saves");
+
+        type_line(&mut console, "fog ");
+        console.handle_key(KeyCode::Tab);
+        assert_eq!(console.input, "fog o");
+        assert_eq!(console.completions, ["on", "off"]);
+
+        // typing on drops the list again
+        console.handle_key(KeyCode::Char('f'));
+        assert!(console.completions.is_empty());
+    }
+
+    #[test]
+    fn nothing_is_offered_for_free_arguments() {
+        let mut console = Console::new();
+        type_line(&mut console, "teleport ");
+        console.handle_key(KeyCode::Tab);
+        assert_eq!(console.input, "teleport ");
+        assert!(console.completions.is_empty());
+        assert_eq!(
+            console.get_hint(),
+            "teleport <x> <y> - Moves the player to a walkable tile on the current map."
+        );
+    }
+
+    #[test]
+    fn submitted_lines_can_be_browsed_back() {
+        let mut console = Console::new();
+        for line in ["reveal", "heal 5"] {
+            type_line(&mut console, line);
+            assert_eq!(
+                console.handle_key(KeyCode::Enter),
+                ConsoleAction::Submit(line.to_string())
+            );
+        }
+
+        console.open();
+        console.handle_key(KeyCode::Up);
+        assert_eq!(console.input, "heal 5");
+        console.handle_key(KeyCode::Up);
+        console.handle_key(KeyCode::Up);
+        assert_eq!(console.input, "reveal");
+        console.handle_key(KeyCode::Down);
+        assert_eq!(console.input, "heal 5");
+        console.handle_key(KeyCode::Down);
+        assert_eq!(console.input, "");
+        assert_eq!(console.handle_key(KeyCode::Esc), ConsoleAction::Close);
+        assert!(!console.is_open);
+    }
+}
@@ -0,0 +1,371 @@
+use crate::chat::{Chat, MessageCategory, Severity};
+use crate::map_manager::MapManager;
+use crate::monster_manager::MonsterManager;
+use crate::player::Player;
+use crate::status::Status;
+use crate::Vec2;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Effect {
+    Heal(i32),
+    Damage(i32),
+    RevealMap,
+    Teleport,
+    FireBolt { damage: i32, range: usize },
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum EffectTarget {
+    Player,
+    Monster(i32),
+}
+
+pub(crate) struct EffectSystem {}
+
+impl EffectSystem {
+    // heal and damage resolve against a bare status so combat, items and status effects share the same rules
+    pub(crate) fn apply_to_status(effect: Effect, status: &mut Status) -> i32 {
+        match effect {
+            Effect::Heal(amount) => {
+                let healed = amount.min(status.max_health - status.health).max(0);
+                status.health += healed;
+                healed
+            }
+            Effect::Damage(amount) => {
+                status.health -= amount;
+                amount
+            }
+            _ => 0,
+        }
+    }
+
+    // god mode shrugs off every source of damage, everything else goes through as normal
+    pub(crate) fn apply_to_player(effect: Effect, player: &mut Player) -> i32 {
+        match effect {
+            Effect::Damage(_) if player.is_god_mode => 0,
+            _ => EffectSystem::apply_to_status(effect, &mut player.status),
+        }
+    }
+
+    // the only place the player dies, so the death is announced once however many hits follow
+    pub(crate) fn check_player_death(player: &mut Player, chat: &mut Chat) {
+        if player.is_alive && player.status.health <= 0 {
+            chat.process_message("You have been slain.", MessageCategory::Combat, Severity::Danger);
+            player.is_alive = false;
+        }
+    }
+
+    // returns false when the effect had nothing to act on, so the caller can keep the item
+    pub(crate) fn apply_effect(
+        effect: Effect,
+        target: EffectTarget,
+        player: &mut Player,
+        map_manager: &mut MapManager,
+        monster_manager: &mut MonsterManager,
+        chat: &mut Chat,
+        rng: &mut impl Rng,
+    ) -> bool {
+        match effect {
+            Effect::Heal(_) | Effect::Damage(_) => {
+                EffectSystem::apply_status_effect(effect, target, player, monster_manager, chat)
+            }
+            Effect::RevealMap => {
+                let map_index = map_manager.current_map_index;
+                let map_data = map_manager.get_map_mut(map_index).expect("map data");
+                map_data.reveal_all();
+                chat.process_chat_message("The layout of the area floods into your mind.");
+                true
+            }
+            Effect::Teleport => EffectSystem::teleport_player(player, map_manager, chat, rng),
+            Effect::FireBolt { damage, range } => {
+                let target_id = EffectSystem::find_nearest_visible_monster(
+                    player.position,
+                    range,
+                    map_manager,
+                    monster_manager,
+                );
+
+                match target_id {
+                    Some(monster_id) => {
+                        chat.process_categorised_message(
+                            "A bolt of fire streaks from the scroll.",
+                            MessageCategory::Combat,
+                        );
+                        EffectSystem::apply_status_effect(
+                            Effect::Damage(damage),
+                            EffectTarget::Monster(monster_id),
+                            player,
+                            monster_manager,
+                            chat,
+                        )
+                    }
+                    None => {
+                        chat.process_categorised_message(
+                            "There is nothing in sight to burn.",
+                            MessageCategory::Warning,
+                        );
+                        false
+                    }
+                }
+            }
+        }
+    }
+
+    pub(crate) fn apply_status_effect(
+        effect: Effect,
+        target: EffectTarget,
+        player: &mut Player,
+        monster_manager: &mut MonsterManager,
+        chat: &mut Chat,
+    ) -> bool {
+
+        match target {
+            EffectTarget::Player => {
+                let amount = EffectSystem::apply_to_player(effect, player);
+                match effect {
+                    Effect::Heal(_) if amount == 0 => {
+                        chat.process_categorised_message(
+                            "You are already at full health.",
+                            MessageCategory::Warning,
+                        );
+                        return false;
+                    }
+                    Effect::Heal(_) => {
+                        chat.process_chat_message(&format!("You recover {} HP.", amount));
+                    }
+                    _ => {
+                        chat.process_categorised_message(
+                            &format!("You take {} damage.", amount),
+                            MessageCategory::Combat,
+                        );
+                    }
+                }
+
+                EffectSystem::check_player_death(player, chat);
+                true
+            }
+            EffectTarget::Monster(monster_id) => {
+                let monster = match monster_manager.get_monster_mut(&monster_id) {
+                    Some(monster) => monster,
+                    None => return false,
+                };
+                let amount = EffectSystem::apply_to_status(effect, &mut monster.status);
+                chat.process_categorised_message(
+                    &format!("The {} takes {} damage.", monster.get_name(), amount),
+                    MessageCategory::Combat,
+                );
+
+                if monster.status.health <= 0 {
+                    chat.process_categorised_message(
+                        &format!("The {} is destroyed.", monster.get_name()),
+                        MessageCategory::Combat,
+                    );
+                    monster.is_alive = false;
+                }
+                true
+            }
+        }
+    }
+
+    fn teleport_player(
+        player: &mut Player,
+        map_manager: &mut MapManager,
+        chat: &mut Chat,
+        rng: &mut impl Rng,
+    ) -> bool {
+        let map_index = map_manager.current_map_index;
+        let map_data = map_manager.get_map_mut(map_index).expect("map data");
+        let tile_set = map_data.tile_set.clone();
+
+        let mut destinations = Vec::<Vec2>::new();
+        for (pos_y, row) in map_data.map.iter().enumerate() {
+            for (pos_x, space) in row.iter().enumerate() {
+                if space.tile == tile_set.floor && !space.is_occupied {
+                    destinations.push(Vec2::new(pos_x, pos_y));
+                }
+            }
+        }
+
+        let destination = match destinations.choose(rng) {
+            Some(destination) => *destination,
+            None => {
+                chat.process_categorised_message(
+                    "The magic finds nowhere to take you.",
+                    MessageCategory::Warning,
+                );
+                return false;
+            }
+        };
+
+        map_data.move_player_to(player, destination);
+
+        chat.process_chat_message("The world twists and you find yourself elsewhere.");
+        true
+    }
+
+    fn find_nearest_visible_monster(
+        origin: Vec2,
+        range: usize,
+        map_manager: &MapManager,
+        monster_manager: &mut MonsterManager,
+    ) -> Option<i32> {
+        let map_data = map_manager.get_map(map_manager.current_map_index)?;
+
+        monster_manager
+            .get_monsters_mut()
+            .values()
+            .filter(|monster| monster.is_alive)
+            .filter(|monster| map_data.map[monster.position.y][monster.position.x].is_visible)
+            .map(|monster| {
+                let distance =
+                    origin.x.abs_diff(monster.position.x) + origin.y.abs_diff(monster.position.y);
+                (distance, monster.id)
+            })
+            .filter(|(distance, _)| *distance <= range)
+            .min()
+            .map(|(_, monster_id)| monster_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map_data::MapData;
+    use crate::tile_set::MONSTER_TILE_SET;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn count_messages(chat: &Chat, text: &str) -> usize {
+        chat.messages
+            .iter()
+            .filter(|message| message.get_text().starts_with(text))
+            .count()
+    }
+
+    #[test]
+    fn healing_stops_at_full_health() {
+        let mut player = Player::new();
+        let mut monster_manager = MonsterManager::new();
+        let mut chat = Chat::new();
+        player.status.health = 95;
+
+        let heal = |player: &mut Player, monster_manager: &mut MonsterManager, chat: &mut Chat| {
+            EffectSystem::apply_status_effect(
+                Effect::Heal(10),
+                EffectTarget::Player,
+                player,
+                monster_manager,
+                chat,
+            )
+        };
+        assert!(heal(&mut player, &mut monster_manager, &mut chat));
+        assert_eq!(player.status.health, 100);
+        assert_eq!(count_messages(&chat, "You recover 5 HP."), 1);
+
+        // nothing to heal, so the potion is kept
+        assert!(!heal(&mut player, &mut monster_manager, &mut chat));
+        assert_eq!(count_messages(&chat, "You are already at full health."), 1);
+    }
+
+    #[test]
+    fn damage_kills_the_player_once() {
+        let mut player = Player::new();
+        let mut chat = Chat::new();
+
+        player.is_god_mode = true;
+        assert_eq!(EffectSystem::apply_to_player(Effect::Damage(200), &mut player), 0);
+        assert_eq!(player.status.health, 100);
+
+        player.is_god_mode = false;
+        let mut monster_manager = MonsterManager::new();
+        for _ in 0..2 {
+            EffectSystem::apply_status_effect(
+                Effect::Damage(100),
+                EffectTarget::Player,
+                &mut player,
+                &mut monster_manager,
+                &mut chat,
+            );
+        }
+        assert!(!player.is_alive);
+        assert_eq!(count_messages(&chat, "You have been slain."), 1);
+    }
+
+    #[test]
+    fn a_monster_at_exactly_zero_health_is_destroyed() {
+        let mut map = MapData::from_rows(&["...."]);
+        let mut player = Player::new();
+        let mut monster_manager = MonsterManager::new();
+        let mut chat = Chat::new();
+        let monster_id =
+            monster_manager.spawn_monster(&mut map, MONSTER_TILE_SET.goblin, Vec2::new(2, 0));
+
+        let max_health = monster_manager
+            .get_monster(&monster_id)
+            .expect("the goblin")
+            .status
+            .max_health;
+        assert!(EffectSystem::apply_status_effect(
+            Effect::Damage(max_health),
+            EffectTarget::Monster(monster_id),
+            &mut player,
+            &mut monster_manager,
+            &mut chat,
+        ));
+        let monster = monster_manager.get_monster(&monster_id).expect("the goblin");
+        assert_eq!(monster.status.health, 0);
+        assert!(!monster.is_alive);
+
+        assert!(!EffectSystem::apply_status_effect(
+            Effect::Damage(1),
+            EffectTarget::Monster(monster_id + 1),
+            &mut player,
+            &mut monster_manager,
+            &mut chat,
+        ));
+    }
+
+    #[test]
+    fn a_fire_bolt_only_finds_monsters_in_sight_and_range() {
+        let mut map = MapData::from_rows(&["@......."]);
+        let mut monster_manager = MonsterManager::new();
+        // the nearer goblin stands in the dark
+        let hidden =
+            monster_manager.spawn_monster(&mut map, MONSTER_TILE_SET.goblin, Vec2::new(1, 0));
+        let seen =
+            monster_manager.spawn_monster(&mut map, MONSTER_TILE_SET.goblin, Vec2::new(3, 0));
+        for x in 2..map.width {
+            map.map[0][x].is_visible = true;
+        }
+        let mut map_manager = MapManager::new();
+        map_manager.add_map(0, map);
+        let mut player = Player::new();
+        player.position = Vec2::ZERO;
+        let mut chat = Chat::new();
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let mut fire_bolt = |range: usize, monster_manager: &mut MonsterManager| {
+            EffectSystem::apply_effect(
+                Effect::FireBolt { damage: 4, range },
+                EffectTarget::Player,
+                &mut player,
+                &mut map_manager,
+                monster_manager,
+                &mut chat,
+                &mut rng,
+            )
+        };
+        assert!(!fire_bolt(2, &mut monster_manager));
+        assert!(fire_bolt(3, &mut monster_manager));
+
+        let damage_taken = |monster_manager: &mut MonsterManager, id: i32| {
+            let monster = monster_manager.get_monster(&id).expect("a goblin");
+            monster.status.max_health - monster.status.health
+        };
+        assert_eq!(damage_taken(&mut monster_manager, hidden), 0);
+        assert_eq!(damage_taken(&mut monster_manager, seen), 4);
+    }
+}
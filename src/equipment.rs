@@ -0,0 +1,233 @@
+use crate::item::{Item, StatModifiers};
+use std::fmt;
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum EquipmentSlot {
+    Weapon,
+    Armor,
+    Shield,
+    Ring,
+}
+
+impl EquipmentSlot {
+    pub const ALL: [EquipmentSlot; 4] = [
+        EquipmentSlot::Weapon,
+        EquipmentSlot::Armor,
+        EquipmentSlot::Shield,
+        EquipmentSlot::Ring,
+    ];
+
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            EquipmentSlot::Weapon => "Weapon",
+            EquipmentSlot::Armor => "Armor",
+            EquipmentSlot::Shield => "Shield",
+            EquipmentSlot::Ring => "Ring",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum EquipError {
+    NotEquippable(String),
+    NoSuchItem,
+    SlotEmpty(EquipmentSlot),
+    ShieldBlockedByTwoHanded(String),
+    TwoHandedBlockedByShield(String),
+}
+
+impl fmt::Display for EquipError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EquipError::NotEquippable(name) => write!(f, "You can't equip the {}.", name),
+            EquipError::NoSuchItem => write!(f, "You have nothing to equip."),
+            EquipError::SlotEmpty(slot) => {
+                write!(
+                    f,
+                    "You have nothing in your {} slot.",
+                    slot.name().to_lowercase()
+                )
+            }
+            EquipError::ShieldBlockedByTwoHanded(weapon) => {
+                write!(f, "You need both hands for the {}.", weapon)
+            }
+            EquipError::TwoHandedBlockedByShield(shield) => {
+                write!(
+                    f,
+                    "Remove the {} before wielding a two-handed weapon.",
+                    shield
+                )
+            }
+        }
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Equipment {
+    pub weapon: Option<Item>,
+    pub armor: Option<Item>,
+    pub shield: Option<Item>,
+    pub ring: Option<Item>,
+}
+
+impl Equipment {
+    pub(crate) fn new() -> Self {
+        Equipment::default()
+    }
+
+    pub(crate) fn get_slot(&self, slot: EquipmentSlot) -> &Option<Item> {
+        match slot {
+            EquipmentSlot::Weapon => &self.weapon,
+            EquipmentSlot::Armor => &self.armor,
+            EquipmentSlot::Shield => &self.shield,
+            EquipmentSlot::Ring => &self.ring,
+        }
+    }
+
+    fn get_slot_mut(&mut self, slot: EquipmentSlot) -> &mut Option<Item> {
+        match slot {
+            EquipmentSlot::Weapon => &mut self.weapon,
+            EquipmentSlot::Armor => &mut self.armor,
+            EquipmentSlot::Shield => &mut self.shield,
+            EquipmentSlot::Ring => &mut self.ring,
+        }
+    }
+
+    // checks the item can be worn with what is already equipped and returns the slot it goes into
+    pub(crate) fn can_equip(&self, item: &Item) -> Result<EquipmentSlot, EquipError> {
+        let slot = item
+            .equipment_slot()
+            .ok_or_else(|| EquipError::NotEquippable(item.name.clone()))?;
+
+        if slot == EquipmentSlot::Shield {
+            if let Some(weapon) = self.weapon.as_ref().filter(|weapon| weapon.is_two_handed()) {
+                return Err(EquipError::ShieldBlockedByTwoHanded(weapon.name.clone()));
+            }
+        }
+
+        if item.is_two_handed() {
+            if let Some(shield) = &self.shield {
+                return Err(EquipError::TwoHandedBlockedByShield(shield.name.clone()));
+            }
+        }
+
+        Ok(slot)
+    }
+
+    // equips the item and hands back whatever was previously in that slot
+    pub(crate) fn equip(&mut self, item: Item) -> Result<Option<Item>, EquipError> {
+        let slot = self.can_equip(&item)?;
+        Ok(self.get_slot_mut(slot).replace(item))
+    }
+
+    pub(crate) fn unequip(&mut self, slot: EquipmentSlot) -> Result<Item, EquipError> {
+        self.get_slot_mut(slot)
+            .take()
+            .ok_or(EquipError::SlotEmpty(slot))
+    }
+
+    pub(crate) fn first_occupied_slot(&self) -> Option<EquipmentSlot> {
+        EquipmentSlot::ALL
+            .into_iter()
+            .find(|slot| self.get_slot(*slot).is_some())
+    }
+
+    pub(crate) fn total_modifiers(&self) -> StatModifiers {
+        EquipmentSlot::ALL
+            .iter()
+            .filter_map(|slot| self.get_slot(*slot).as_ref())
+            .fold(StatModifiers::default(), |total, item| {
+                total.combine(item.modifiers)
+            })
+    }
+
+    pub fn get_equipment(&self) -> Vec<String> {
+        EquipmentSlot::ALL
+            .iter()
+            .map(|slot| {
+                let item_name = self
+                    .get_slot(*slot)
+                    .as_ref()
+                    .map(|item| item.name.as_str())
+                    .unwrap_or("-");
+                format!("{}: {}", slot.name(), item_name)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::Player;
+
+    #[test]
+    fn every_slot_adds_its_modifiers() {
+        let mut equipment = Equipment::new();
+        assert_eq!(equipment.total_modifiers(), StatModifiers::default());
+
+        for item in [
+            Item::rusty_dagger(),
+            Item::leather_armor(),
+            Item::wooden_shield(),
+            Item::ring_of_might(),
+        ] {
+            assert_eq!(equipment.equip(item), Ok(None));
+        }
+        assert_eq!(equipment.total_modifiers(), StatModifiers::new(3, 3));
+
+        let dagger = equipment.unequip(EquipmentSlot::Weapon).expect("the dagger");
+        assert_eq!(dagger.name, "Rusty Dagger");
+        assert_eq!(equipment.total_modifiers(), StatModifiers::new(1, 3));
+        assert_eq!(
+            equipment.unequip(EquipmentSlot::Weapon).map(|item| item.name),
+            Err(EquipError::SlotEmpty(EquipmentSlot::Weapon))
+        );
+    }
+
+    #[test]
+    fn the_shield_and_a_two_handed_weapon_exclude_each_other() {
+        let mut equipment = Equipment::new();
+        equipment.equip(Item::great_axe()).expect("the axe");
+        assert_eq!(
+            equipment.can_equip(&Item::wooden_shield()),
+            Err(EquipError::ShieldBlockedByTwoHanded("Great Axe".to_string()))
+        );
+
+        let mut equipment = Equipment::new();
+        equipment.equip(Item::wooden_shield()).expect("the shield");
+        assert_eq!(
+            equipment.can_equip(&Item::great_axe()),
+            Err(EquipError::TwoHandedBlockedByShield("Wooden Shield".to_string()))
+        );
+        assert_eq!(equipment.total_modifiers(), StatModifiers::new(0, 1));
+    }
+
+    #[test]
+    fn the_effective_status_layers_equipment_over_the_base() {
+        let mut player = Player::new();
+        let dagger = player
+            .inventory
+            .items
+            .iter()
+            .position(|item| item.name == "Rusty Dagger")
+            .expect("the starting dagger");
+        player.equip_item(dagger).expect("the dagger is equipped");
+
+        let status = player.get_effective_status();
+        assert_eq!((status.str, status.def), (5, 1));
+        assert_eq!((player.status.str, player.status.def), (3, 1));
+
+        player.inventory.add_item(Item::great_axe());
+        let axe = player.inventory.items.len() - 1;
+        assert_eq!(
+            player.equip_item(axe),
+            Ok("You swap the Rusty Dagger for the Great Axe.".to_string())
+        );
+        assert_eq!(player.get_effective_status().str, 8);
+
+        player.unequip_item(EquipmentSlot::Weapon).expect("the axe comes off");
+        assert_eq!(player.get_effective_status().str, 3);
+    }
+}
@@ -0,0 +1,878 @@
+use crate::battle_system::BattleSystem;
+use crate::chat::{Chat, MessageCategory};
+use crate::collision_engine::CollisionEngine;
+use crate::command_system::{CommandRequest, CommandSystem};
+use crate::action::Action;
+use crate::interaction_system::InteractionSystem;
+use crate::item_system::ItemSystem;
+use crate::loot::LootTable;
+use crate::map_factory::{MapFactory, GENERATED_MAP_SIZE, GENERATED_MAP_START};
+use crate::map_editor::{EditorInput, MapEditor};
+use crate::map_loader::{self, MapError};
+use crate::map_manager::{get_map_spawn, MapManager, GENERATED_MAP_INDEX};
+pub use crate::map_manager::MAP_NAMES;
+use crate::monster_generator::MonsterFactory;
+use crate::map_data::MapData;
+use crate::monster::Monster;
+use crate::monster_manager::MonsterManager;
+use crate::pathfinding::MovementMode;
+use crate::player::Player;
+use crate::save::{self, SaveData, SaveError, AUTOSAVE_SLOT, SAVE_VERSION};
+use crate::scheduler::{Scheduler, TimingMode, ACTION_COST};
+use crate::space::Space;
+use crate::tile_set::{DEFAULT_TILE_SET, LADDER_TILE_SET};
+use crate::vec2::Vec2;
+use crate::MovementType;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const DEFAULT_START_MAP: &str = "map2";
+
+// everything that can move the world on, a frontend turns keys and timers into these
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum GameEvent {
+    // an action that got past the frontend's own screens
+    Input(Action),
+    // a line for the command system, as typed into the console
+    Command(String),
+    // a real-time tick came due
+    Tick,
+    // something for the map editor while it is open
+    Edit(EditorInput),
+}
+
+// what a new game is set up with
+#[derive(Clone, Debug)]
+pub struct GameConfig {
+    pub seed_phrase: String,
+    pub timing: TimingMode,
+    // one of MAP_NAMES, the game starts on it with the monsters spawned there
+    pub start_map: String,
+    // where the player stands on the start map instead of its drawn starting spot
+    pub spawn: Option<Vec2>,
+    // hide whatever the player can't see, off unless asked for
+    pub fog_of_war: bool,
+    // save to the autosave slot whenever the player changes maps
+    pub autosave: bool,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            seed_phrase: "seedphrase".to_string(),
+            timing: TimingMode::TurnBased,
+            start_map: DEFAULT_START_MAP.to_string(),
+            spawn: None,
+            fog_of_war: false,
+            autosave: false,
+        }
+    }
+}
+
+// why a new game couldn't be set up from its config
+#[derive(Debug)]
+pub enum StartError {
+    UnknownMap(String),
+    Map(MapError),
+    SpawnOutside(Vec2),
+    SpawnBlocked(Vec2),
+}
+
+impl fmt::Display for StartError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StartError::UnknownMap(name) => write!(
+                f,
+                "There is no map called {}, pick one of {}.",
+                name,
+                MAP_NAMES.join(", ")
+            ),
+            StartError::Map(error) => write!(f, "A map could not be loaded: {}", error),
+            StartError::SpawnOutside(position) => write!(
+                f,
+                "{},{} is outside the starting map.",
+                position.x, position.y
+            ),
+            StartError::SpawnBlocked(position) => write!(
+                f,
+                "{},{} is not walkable on the starting map.",
+                position.x, position.y
+            ),
+        }
+    }
+}
+
+// the whole world in one place, only ever changed through step, everything else is read-only
+pub struct Game {
+    player: Player,
+    map_manager: MapManager,
+    monster_manager: MonsterManager,
+    chat: Chat,
+    scheduler: Scheduler,
+    // every random roll in the world comes from here so a save can carry it on exactly
+    rng: ChaCha8Rng,
+    turn: u64,
+    autosave: bool,
+    collision_engine: CollisionEngine,
+    item_system: ItemSystem,
+    map_factory: MapFactory,
+    // open while the current map is being edited, the world waits until it closes
+    editor: Option<MapEditor>,
+}
+
+impl Game {
+    pub fn new(config: GameConfig) -> Result<Self, StartError> {
+        if !MAP_NAMES.contains(&config.start_map.as_str()) {
+            return Err(StartError::UnknownMap(config.start_map));
+        }
+
+        let mut player = Player::new();
+        player.fog_of_war = config.fog_of_war;
+        let mut map_manager = MapManager::new();
+        let mut map_factory = MapFactory::new();
+        let mut hasher = DefaultHasher::new();
+        config.seed_phrase.hash(&mut hasher);
+        let mut rng = ChaCha8Rng::seed_from_u64(hasher.finish());
+        map_manager.seed_phrase = config.seed_phrase;
+
+        // the maps go in the order of MAP_NAMES, load_map finds them by their place in it
+        let mut map_warnings = Vec::new();
+        for (index, map_name) in MAP_NAMES.iter().enumerate() {
+            if index == GENERATED_MAP_INDEX {
+                let seed_phrase = map_manager.seed_phrase.clone();
+                let new_map = map_factory.generate_map(
+                    &mut player,
+                    GENERATED_MAP_SIZE,
+                    GENERATED_MAP_SIZE,
+                    GENERATED_MAP_START,
+                    &seed_phrase,
+                );
+                map_manager.add_generated_map(new_map);
+                continue;
+            }
+            map_warnings.extend(
+                map_manager
+                    .add_map_set_player_position(&mut player, map_name, get_map_spawn(map_name))
+                    .map_err(StartError::Map)?,
+            );
+        }
+
+        map_manager.load_map(&config.start_map, MovementType::Normal);
+        // every map was given a starting spot when it was added, the one being played decides
+        if let Some(position) = map_manager.get_current_map().find_player_position() {
+            player.position = position;
+        }
+        if let Some(spawn) = config.spawn {
+            let map_data = map_manager.get_mut_current_map();
+            match map_data.get_space(spawn) {
+                None => return Err(StartError::SpawnOutside(spawn)),
+                Some(space) if !space.is_traversable || space.is_occupied => {
+                    return Err(StartError::SpawnBlocked(spawn))
+                }
+                Some(_) => map_data.move_player_to(&mut player, spawn),
+            }
+        }
+
+        let mut monster_manager = MonsterManager::new();
+        monster_manager.follow_map(map_manager.current_map_index);
+        monster_manager.spawn_placed_monsters(map_manager.get_mut_current_map());
+        monster_manager.spawn_monsters(&mut map_manager, MonsterFactory::new(), &mut rng);
+
+        let mut collision_engine = CollisionEngine::new();
+        collision_engine.update_player_vision(&mut map_manager, &player, Vec2::ZERO);
+
+        // a map that loaded with warnings still plays, but whoever drew it should hear about it
+        let mut chat = Chat::new();
+        for warning in map_warnings {
+            chat.process_categorised_message(&warning.to_string(), MessageCategory::Warning);
+        }
+
+        Ok(Game {
+            player,
+            map_manager,
+            monster_manager,
+            chat,
+            scheduler: Scheduler::new(config.timing),
+            rng,
+            turn: 0,
+            autosave: config.autosave,
+            collision_engine,
+            item_system: ItemSystem::new(),
+            map_factory,
+            editor: None,
+        })
+    }
+
+    // settings that aren't part of a save, like autosaving, still come from the config
+    pub fn load(slot: &str, config: GameConfig) -> Result<Self, SaveError> {
+        Ok(Game::from_save(save::read(slot)?, config))
+    }
+
+    // a save file from anywhere on disk, older formats are upgraded on the way in
+    pub fn load_file(path: &Path, config: GameConfig) -> Result<Self, SaveError> {
+        Ok(Game::from_save(save::read_file(path)?, config))
+    }
+
+    fn from_save(data: SaveData, config: GameConfig) -> Self {
+        let mut game = Game {
+            player: data.player,
+            map_manager: data.map_manager,
+            monster_manager: data.monster_manager,
+            chat: Chat::new(),
+            scheduler: Scheduler::new(data.timing),
+            rng: data.rng,
+            turn: data.turn,
+            autosave: config.autosave,
+            collision_engine: CollisionEngine::new(),
+            item_system: ItemSystem::new(),
+            map_factory: MapFactory::new(),
+            editor: None,
+        };
+
+        // saves from before monsters were kept per map hold only the current map's
+        game.monster_manager
+            .follow_map(game.map_manager.current_map_index);
+        let player_position = game.player.position;
+        game.collision_engine
+            .update_player_vision(&mut game.map_manager, &game.player, player_position);
+        game
+    }
+
+    pub fn save(&self, slot: &str) -> Result<PathBuf, SaveError> {
+        save::write(slot, &self.get_save_data())
+    }
+
+    // a fingerprint of everything a save would hold, two games with the same hash play out the same
+    pub fn state_hash(&self) -> u64 {
+        save::hash(&self.get_save_data())
+    }
+
+    fn get_save_data(&self) -> SaveData {
+        SaveData {
+            version: SAVE_VERSION,
+            turn: self.turn,
+            timing: self.scheduler.mode,
+            player: self.player.clone(),
+            map_manager: self.map_manager.clone(),
+            monster_manager: self.monster_manager.clone(),
+            rng: self.rng.clone(),
+        }
+    }
+
+    pub fn player(&self) -> &Player {
+        &self.player
+    }
+
+    pub fn map(&self) -> &MapData {
+        self.map_manager.get_current_map()
+    }
+
+    pub fn monsters(&self) -> impl Iterator<Item = &Monster> {
+        self.monster_manager.iter()
+    }
+
+    pub fn messages(&self) -> &Chat {
+        &self.chat
+    }
+
+    pub fn editor(&self) -> Option<&MapEditor> {
+        self.editor.as_ref()
+    }
+
+    pub fn movement(&self) -> MovementMode {
+        self.map_manager.get_movement()
+    }
+
+    pub fn timing(&self) -> TimingMode {
+        self.scheduler.mode
+    }
+
+    // how many turns the player has taken
+    pub fn turn(&self) -> u64 {
+        self.turn
+    }
+
+    // the player has died, the frontend shows the game over screen from here on
+    pub fn is_over(&self) -> bool {
+        !self.player.is_alive
+    }
+
+    // how long a frontend may wait for input before the next tick is due, none while turn-based
+    pub fn get_wait_time(&self) -> Option<Duration> {
+        self.scheduler.get_wait_time()
+    }
+
+    // true once per real-time tick that has come due, the frontend then calls tick
+    pub fn is_tick_due(&mut self) -> bool {
+        self.scheduler.take_tick()
+    }
+
+    // a frontend's own notices, e.g. a changed setting, share the message log with the game's
+    pub fn add_message(&mut self, message: &str, category: MessageCategory) {
+        self.chat.process_categorised_message(message, category);
+    }
+
+    // returns false when the action was ignored, e.g. a turn that hasn't been earned yet
+    pub fn apply(&mut self, action: Action) -> bool {
+        self.step(GameEvent::Input(action))
+    }
+
+    pub fn run_command(&mut self, line: &str) {
+        self.step(GameEvent::Command(line.to_string()));
+    }
+
+    pub fn tick(&mut self) {
+        self.step(GameEvent::Tick);
+    }
+
+    // applies one event, returns false when it was ignored and nothing changed
+    pub fn step(&mut self, event: GameEvent) -> bool {
+        let map_index = self.map_manager.current_map_index;
+        let was_applied = match event {
+            // nothing moves while the map is being edited
+            GameEvent::Input(_) | GameEvent::Tick if self.editor.is_some() => false,
+            // a dead player can still use the console, e.g. to load a save, but nothing else
+            GameEvent::Input(_) | GameEvent::Tick if self.is_over() => false,
+            GameEvent::Input(action) => self.take_turn(action),
+            GameEvent::Command(line) => {
+                let request = CommandSystem::run(
+                    &line,
+                    &mut self.player,
+                    &mut self.map_manager,
+                    &mut self.monster_manager,
+                    &mut self.scheduler,
+                    &mut self.chat,
+                );
+                match request {
+                    Some(CommandRequest::Save(slot)) => self.save_to_slot(&slot),
+                    Some(CommandRequest::Load(slot)) => {
+                        // a loaded game is already where it should be, there is nothing to autosave
+                        self.load_from_slot(&slot);
+                        return true;
+                    }
+                    Some(CommandRequest::Editor(state)) => {
+                        self.toggle_editor(state.unwrap_or(self.editor.is_none()))
+                    }
+                    None => {}
+                }
+                let player_position = self.player.position;
+                self.collision_engine.update_player_vision(
+                    &mut self.map_manager,
+                    &self.player,
+                    player_position,
+                );
+                true
+            }
+            GameEvent::Tick => {
+                self.advance_time();
+                true
+            }
+            GameEvent::Edit(input) => self.edit(input),
+        };
+        self.monster_manager
+            .follow_map(self.map_manager.current_map_index);
+
+        // moving to another map is a natural checkpoint
+        if self.autosave && self.map_manager.current_map_index != map_index {
+            if let Err(error) = self.save(AUTOSAVE_SLOT) {
+                self.chat
+                    .process_categorised_message(&error.to_string(), MessageCategory::Warning);
+            }
+        }
+        was_applied
+    }
+
+    fn toggle_editor(&mut self, is_open: bool) {
+        if is_open == self.editor.is_some() {
+            return;
+        }
+        if is_open {
+            // edits also go into a fresh copy of the map file, which is what saving writes out
+            let map_index = self.map_manager.current_map_index;
+            let authored_map = if map_index == GENERATED_MAP_INDEX {
+                None
+            } else {
+                let map_name = MAP_NAMES[map_index];
+                let path = map_loader::get_map_path(map_name);
+                match map_loader::load(&path, get_map_spawn(map_name)) {
+                    Ok(loaded_map) => Some(loaded_map.map_data),
+                    Err(error) => {
+                        self.chat.process_categorised_message(
+                            &format!("The editor can't open the map: {}", error),
+                            MessageCategory::Warning,
+                        );
+                        return;
+                    }
+                }
+            };
+            self.editor = Some(MapEditor::new(self.player.position, authored_map));
+            self.chat.process_chat_message(
+                "Map editor open, the world waits until it is closed again.",
+            );
+        } else {
+            self.editor = None;
+            self.chat.process_chat_message("Map editor closed.");
+        }
+    }
+
+    // returns false when the editor isn't open or the input changed nothing
+    fn edit(&mut self, input: EditorInput) -> bool {
+        let editor = match self.editor.as_mut() {
+            Some(editor) => editor,
+            None => return false,
+        };
+        let result = match input {
+            EditorInput::Close => {
+                self.toggle_editor(false);
+                return true;
+            }
+            EditorInput::Save => self.save_map().map(Some),
+            _ => editor.handle(
+                &input,
+                self.map_manager.get_mut_current_map(),
+                &mut self.monster_manager,
+            ),
+        };
+
+        match result {
+            Ok(Some(message)) => self.chat.process_chat_message(&message),
+            Ok(None) => {}
+            Err(message) => {
+                self.chat
+                    .process_categorised_message(&message, MessageCategory::Warning);
+                return false;
+            }
+        }
+        let player_position = self.player.position;
+        self.collision_engine
+            .update_player_vision(&mut self.map_manager, &self.player, player_position);
+        true
+    }
+
+    // the map goes back into the file it was loaded from
+    fn save_map(&self) -> Result<String, String> {
+        let map_index = self.map_manager.current_map_index;
+        if map_index == GENERATED_MAP_INDEX {
+            return Err("The generated map has no file, it is rebuilt from the seed.".to_string());
+        }
+        // a map drawn in Tiled is saved next to the original, whose .txt file is read first from
+        // then on
+        let path = map_loader::get_map_path(MAP_NAMES[map_index]).with_extension("txt");
+        self.save_map_to(&path)
+            .map_err(|error| format!("Could not save {}: {}", path.display(), error))?;
+        Ok(format!("Saved the map to {}.", path.display()))
+    }
+
+    // writes the map being edited in the map file format, with a .meta file next to it when
+    // needed, what happened to it in play is left out
+    pub fn save_map_to(&self, path: &Path) -> io::Result<()> {
+        match &self.editor {
+            Some(editor) => editor.save(path),
+            None => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "only a map open in the editor can be saved",
+            )),
+        }
+    }
+
+    fn save_to_slot(&mut self, slot: &str) {
+        match self.save(slot) {
+            Ok(path) => self
+                .chat
+                .process_chat_message(&format!("Saved the game to {}.", path.display())),
+            Err(error) => self
+                .chat
+                .process_categorised_message(&error.to_string(), MessageCategory::Warning),
+        }
+    }
+
+    fn load_from_slot(&mut self, slot: &str) {
+        let config = GameConfig {
+            autosave: self.autosave,
+            ..GameConfig::default()
+        };
+        match Game::load(slot, config) {
+            Ok(game) => {
+                *self = game;
+                self.chat
+                    .process_chat_message(&format!("Loaded '{}' at turn {}.", slot, self.turn));
+            }
+            Err(error) => self
+                .chat
+                .process_categorised_message(&error.to_string(), MessageCategory::Warning),
+        }
+    }
+
+    fn take_turn(&mut self, action: Action) -> bool {
+        let takes_turn = action.takes_turn(self.player.inventory.is_open);
+        // a refused diagonal costs nothing, the monsters mustn't get a free move out of it
+        let is_diagonal = action
+            .get_direction()
+            .is_some_and(|(delta_x, delta_y)| delta_x != 0 && delta_y != 0);
+        if takes_turn && is_diagonal && self.map_manager.get_movement() == MovementMode::FourWay {
+            self.chat
+                .process_chat_message("You can only move in four directions.");
+            return true;
+        }
+
+        // in real time a turn has to be earned before it can be taken
+        if takes_turn {
+            if !self.player.energy.is_ready() {
+                return false;
+            }
+            self.player.energy.spend();
+            self.turn += 1;
+        }
+        self.player.action = Some(action);
+
+        let is_item_input = self.item_system.process_item_input(
+            &mut self.player,
+            &mut self.map_manager,
+            &mut self.monster_manager,
+            &mut self.chat,
+            &mut self.rng,
+        ) || InteractionSystem::process_interaction_input(
+            &mut self.player,
+            &mut self.map_manager,
+            &mut self.chat,
+            &mut self.rng,
+        );
+
+        let new_player_pos = if is_item_input {
+            self.player.position
+        } else {
+            self.collision_engine
+                .try_move_player(&mut self.player, &mut self.chat)
+        };
+
+        let player_move_type = self.collision_engine.try_process_move(
+            &mut self.map_manager,
+            &mut self.player,
+            &mut self.monster_manager,
+            &mut self.chat,
+            new_player_pos,
+            &mut self.rng,
+        );
+        self.player.previous_tile_below_player = self.player.tile_below_player;
+
+        match player_move_type {
+            MovementType::Normal => {
+                self.collision_engine.update_player_position(
+                    &mut self.map_manager,
+                    &mut self.player,
+                    &mut self.chat,
+                    new_player_pos,
+                );
+            }
+            MovementType::LadderUp => {
+                self.map_manager
+                    .load_map("scene_ladder", MovementType::LadderUp);
+                self.player.update_tile_below_player(LADDER_TILE_SET.floor);
+                self.player.position = Vec2::new(3, 2);
+            }
+            MovementType::LadderDown => {
+                self.map_manager
+                    .load_map("scene_ladder", MovementType::LadderDown);
+                self.player.position = Vec2::new(3, 2);
+            }
+            MovementType::LadderExit => {
+                self.climb_off_ladder("map2");
+                self.player.tile_below_player = self.player.previous_tile_below_player;
+            }
+            MovementType::LadderEnter => self.climb_off_ladder("map1"),
+            MovementType::Battle => {
+                if let Some(monster) = self.monster_manager.get_monster_at_position(new_player_pos)
+                {
+                    BattleSystem::start_battle(&mut self.player, monster, &mut self.chat);
+                }
+            }
+            MovementType::Unable => {}
+        }
+
+        let terrain_data = self.map_factory.generate_terrain(
+            &mut self.map_manager,
+            new_player_pos,
+            &mut self.chat,
+        );
+        if let Some(terrain_data) = terrain_data {
+            self.map_manager
+                .update_current_map(terrain_data, &mut self.chat);
+        }
+
+        self.collision_engine.update_player_vision(
+            &mut self.map_manager,
+            &self.player,
+            new_player_pos,
+        );
+
+        // turn-based monsters act once the player has, until the player can go again
+        if self.scheduler.mode == TimingMode::TurnBased {
+            while self.player.is_alive && !self.player.energy.is_ready() {
+                self.advance_time();
+            }
+        }
+        true
+    }
+
+    // the ladder leads down to map2 and up to map1, the player steps off where they last stood on
+    // that map, which is its starting spot when they started out on the ladder
+    fn climb_off_ladder(&mut self, map_name: &str) {
+        let map_data = self
+            .map_manager
+            .load_map(map_name, MovementType::Normal)
+            .expect("map data");
+        if let Some(position) = map_data.find_player_position() {
+            self.player.position = position;
+        }
+    }
+
+    // one step of time, every actor earns energy and each monster that can afford it acts
+    fn advance_time(&mut self) {
+        // only the monsters on the map being played move, a ladder may have just changed it
+        self.monster_manager
+            .follow_map(self.map_manager.current_map_index);
+        let player_gain = self.scheduler.get_energy_gain(self.player.energy.speed);
+        self.player.energy.gain(player_gain);
+        if self.scheduler.mode == TimingMode::RealTime {
+            // standing around in real time shouldn't bank up a burst of moves
+            self.player.energy.amount = self.player.energy.amount.min(ACTION_COST);
+        }
+
+        for monster in self.monster_manager.get_monsters_mut().values_mut() {
+            monster
+                .energy
+                .gain(self.scheduler.get_energy_gain(monster.energy.speed));
+        }
+
+        self.remove_dead_monsters();
+
+        // fast monsters can afford more than one action per step
+        while self
+            .monster_manager
+            .get_monsters_mut()
+            .values()
+            .any(|monster| monster.energy.is_ready())
+        {
+            let mut new_monsters_pos = self.collision_engine.try_move_monsters(
+                &self.player,
+                &mut self.monster_manager,
+                &mut self.map_manager,
+                &mut self.chat,
+                &mut self.rng,
+            );
+            let processed_monsters_positions = self.collision_engine.process_monsters_move(
+                &mut new_monsters_pos,
+                &mut self.map_manager,
+                &mut self.monster_manager,
+            );
+            self.collision_engine.update_monsters_position(
+                &mut self.map_manager,
+                &mut self.monster_manager,
+                processed_monsters_positions,
+            );
+
+            for monster in self.monster_manager.get_monsters_mut().values_mut() {
+                if monster.energy.is_ready() {
+                    monster.energy.spend();
+                }
+            }
+        }
+
+        self.collision_engine
+            .update_player_vision(&mut self.map_manager, &self.player, Vec2::ZERO);
+    }
+
+    fn remove_dead_monsters(&mut self) {
+        let mut monsters_to_remove = Vec::<i32>::new();
+
+        for monster in self.monster_manager.get_monsters_mut().values_mut() {
+            if monster.is_alive {
+                continue;
+            }
+            let map = self.map_manager.get_mut_current_map();
+            monsters_to_remove.push(monster.id);
+
+            self.chat
+                .process_debug_message(&format!("mon dead at pos: {:?}", monster.position), 3);
+
+            // put back whatever the monster was standing on, e.g. an open door, rather than always floor
+            let tile_below = if monster.tile_below == ' ' {
+                DEFAULT_TILE_SET.floor
+            } else {
+                monster.tile_below
+            };
+            map.map[monster.position.y][monster.position.x] = Space::new(tile_below);
+            map.restore_object_tile(monster.position);
+
+            let drops = LootTable::for_species(monster.tile)
+                .roll(self.player.current_floor, &mut self.rng);
+            if !drops.is_empty() {
+                self.chat.process_categorised_message(
+                    &format!("The {} drops something.", monster.get_name()),
+                    MessageCategory::Loot,
+                );
+                map.add_to_item_pile(monster.position, drops);
+            }
+        }
+
+        for monster_id in monsters_to_remove {
+            self.monster_manager.despawn(monster_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tile_set::MONSTER_TILE_SET;
+
+    fn new_game(start_map: &str) -> Game {
+        Game::new(GameConfig {
+            start_map: start_map.to_string(),
+            ..GameConfig::default()
+        })
+        .expect("a new game")
+    }
+
+    fn count_messages(game: &Game, text: &str) -> usize {
+        game.messages()
+            .messages
+            .iter()
+            .filter(|message| message.get_text().starts_with(text))
+            .count()
+    }
+
+    #[test]
+    fn a_dead_player_takes_no_more_turns() {
+        // the snake right above the start on map2 strikes back for at least one point
+        let mut game = new_game("map2");
+        game.player.status.health = 1;
+        assert!(game.apply(Action::MoveUp));
+        assert!(game.is_over());
+        assert_eq!(count_messages(&game, "You have been slain."), 1);
+
+        let turn = game.turn();
+        assert!(!game.apply(Action::MoveUp));
+        assert!(!game.apply(Action::Wait));
+        game.tick();
+        assert_eq!(game.turn(), turn);
+        assert_eq!(count_messages(&game, "You have been slain."), 1);
+    }
+
+    #[test]
+    fn a_dead_monster_leaves_behind_what_it_stood_on() {
+        let mut game = new_game("map2");
+        let snake = Vec2::new(6, 1);
+        let plate = Vec2::new(2, 2);
+        let map = game.map_manager.get_mut_current_map();
+        let goblin_id = game
+            .monster_manager
+            .spawn_monster(map, MONSTER_TILE_SET.goblin, plate);
+        assert!(map.map[plate.y][plate.x].is_monster);
+
+        let monster = game
+            .monster_manager
+            .get_monster_at_position(snake)
+            .expect("the snake above the start");
+        let snake_id = monster.id;
+        monster.tile_below = DEFAULT_TILE_SET.open_door;
+        monster.is_alive = false;
+        game.monster_manager
+            .get_monster_mut(&goblin_id)
+            .expect("the goblin")
+            .is_alive = false;
+        game.remove_dead_monsters();
+
+        assert!(game.monster_manager.get_monster(&snake_id).is_none());
+        assert!(game.monster_manager.get_monster(&goblin_id).is_none());
+        assert_eq!(game.map().map[snake.y][snake.x].tile, DEFAULT_TILE_SET.open_door);
+        assert_eq!(game.map().map[plate.y][plate.x].tile, DEFAULT_TILE_SET.pressure_plate);
+    }
+
+    // how many moves each monster has made so far, worked out from the energy it has spent
+    // over that many turns, or ticks in real time
+    fn count_monster_moves(game: &Game, steps: i32) -> Vec<(char, i32)> {
+        let mut moves: Vec<(char, i32)> = game
+            .monsters()
+            .map(|monster| {
+                let earned = game.scheduler.get_energy_gain(monster.energy.speed) * steps;
+                (monster.tile, (earned - monster.energy.amount) / ACTION_COST)
+            })
+            .collect();
+        moves.sort();
+        moves.dedup();
+        moves
+    }
+
+    #[test]
+    fn turn_based_monsters_move_after_the_player_at_their_own_speed() {
+        let mut game = new_game("map2");
+        game.run_command("godmode on");
+        let snake = MONSTER_TILE_SET.snake;
+        let goblin = MONSTER_TILE_SET.goblin;
+
+        for (turn, snake_moves) in [(1, 0), (2, 1), (3, 1), (4, 2)] {
+            assert!(game.apply(Action::Wait));
+            assert_eq!(game.turn(), turn as u64);
+            assert!(game.player().energy.is_ready());
+            assert_eq!(
+                count_monster_moves(&game, turn),
+                [(goblin, turn), (snake, snake_moves)]
+            );
+        }
+    }
+
+    #[test]
+    fn real_time_turns_are_earned_tick_by_tick() {
+        let mut game = Game::new(GameConfig {
+            start_map: "map2".to_string(),
+            timing: TimingMode::RealTime,
+            ..GameConfig::default()
+        })
+        .expect("a new game");
+        game.run_command("godmode on");
+        let snake = MONSTER_TILE_SET.snake;
+        let goblin = MONSTER_TILE_SET.goblin;
+
+        assert!(game.apply(Action::Wait));
+        assert!(!game.apply(Action::Wait));
+        for _ in 0..4 {
+            game.tick();
+        }
+        assert!(!game.apply(Action::Wait));
+        assert_eq!(count_monster_moves(&game, 4), [(goblin, 0), (snake, 0)]);
+
+        game.tick();
+        assert_eq!(count_monster_moves(&game, 5), [(goblin, 1), (snake, 0)]);
+        assert!(game.apply(Action::Wait));
+
+        // standing still banks no more than the one move
+        for _ in 5..20 {
+            game.tick();
+        }
+        assert_eq!(count_monster_moves(&game, 20), [(goblin, 4), (snake, 2)]);
+        assert!(game.apply(Action::Wait));
+        assert!(!game.apply(Action::Wait));
+        assert_eq!(game.turn(), 3);
+    }
+
+    #[test]
+    fn a_refused_diagonal_costs_no_turn() {
+        let mut game = new_game("map3");
+        game.run_command("diagonals off");
+        let position = game.player().position;
+        let energy = game.player().energy.amount;
+
+        assert!(game.apply(Action::MoveDownRight));
+        assert_eq!(game.turn(), 0);
+        assert_eq!(game.player().position, position);
+        assert_eq!(game.player().energy.amount, energy);
+        assert_eq!(count_messages(&game, "You can only move in four directions."), 1);
+    }
+}
@@ -1,84 +1,542 @@
-use crate::chat::Chat;
+use crate::binding_screen::{BindingScreen, BindingScreenAction};
+use crate::camera::Camera;
+use crate::chat::{Chat, MessageCategory};
+use crate::console::{Console, ConsoleAction};
+use crate::hud::{HudLayout, Panel, Rect, Region};
+use crate::input::KeyBindings;
+use crate::load_screen::LoadScreen;
+use crate::log_viewer::LogViewer;
+use crate::minimap::{Minimap, MinimapMode};
 use std::io;
 
+use crate::palette::{CellStyle, Palette, PaletteKind};
 use crate::player::Player;
+use crate::renderer::{Cell, FrameBuffer, RenderTarget, TerminalTarget};
 use crate::tile_set::DEFAULT_TILE_SET;
+use crate::vec2::Vec2;
 
-use crossterm::{terminal, ExecutableCommand, QueueableCommand};
+use crate::action::Action;
+use crate::game::Game;
+use crate::map_data::MapData;
+use crate::map_editor::MapEditor;
+use crossterm::event::{KeyCode, KeyEvent};
 
-use crate::map_manager::MapManager;
-use crossterm::cursor::{DisableBlinking, SetCursorStyle};
-use futures::lock::{Mutex, MutexGuard};
-use std::io::{stdout, Write};
-use std::sync::Arc;
+const MESSAGE_LINES: usize = 8;
+// how far the player can walk from the center of the view before it scrolls, --dead-zone sets it
+pub const DEFAULT_DEAD_ZONE: Vec2 = Vec2::new(4, 2);
 
-#[derive(Clone)]
-pub struct GameClient {}
+// draws the game into a render target, the terminal unless told otherwise
+pub struct GameClient<T: RenderTarget = TerminalTarget> {
+    target: T,
+    palette: Palette,
+    camera: Camera,
+    minimap: Minimap,
+    overview: Option<Camera>,
+    log_viewer: Option<LogViewer>,
+    is_debug_overlay_open: bool,
+    console: Console,
+    key_bindings: KeyBindings,
+    binding_screen: Option<BindingScreen>,
+}
 
 impl GameClient {
-    pub(crate) fn new() -> Self {
-        GameClient {}
+    pub fn new(key_bindings: KeyBindings) -> Self {
+        GameClient::with_target(key_bindings, TerminalTarget::new())
+    }
+}
+
+impl<T: RenderTarget> GameClient<T> {
+    pub fn with_target(key_bindings: KeyBindings, target: T) -> Self {
+        GameClient {
+            target,
+            palette: Palette::new(PaletteKind::Default),
+            camera: Camera::new(DEFAULT_DEAD_ZONE),
+            minimap: Minimap::new(MinimapMode::Blocks(3)),
+            overview: None,
+            log_viewer: None,
+            is_debug_overlay_open: false,
+            console: Console::new(),
+            key_bindings,
+            binding_screen: None,
+        }
+    }
+
+    pub fn with_dead_zone(mut self, dead_zone: Vec2) -> Self {
+        self.camera.dead_zone = dead_zone;
+        self
+    }
+
+    // switches to the alternate screen in raw mode, call end_session before exiting
+    pub fn start_session(&mut self) -> io::Result<()> {
+        self.target.enter()
+    }
+
+    pub fn end_session(&mut self) -> io::Result<()> {
+        self.target.leave()
+    }
+
+    pub fn get_target(&self) -> &T {
+        &self.target
+    }
+
+    // switches to the next palette and returns its name so it can be announced
+    pub fn cycle_palette(&mut self) -> &'static str {
+        self.palette = Palette::new(self.palette.kind.next());
+        self.palette.kind.name()
+    }
+
+    pub fn cycle_minimap_mode(&mut self) -> &'static str {
+        self.minimap.mode = self.minimap.mode.next();
+        self.minimap.mode.name()
+    }
+
+    pub fn is_overview_open(&self) -> bool {
+        self.overview.is_some()
+    }
+
+    // the overview opens on whatever the game view was showing
+    pub fn toggle_overview(&mut self) {
+        self.overview = match self.overview {
+            Some(_) => None,
+            None => Some(Camera::new(Vec2::ZERO).with_offset(self.camera.offset)),
+        };
+    }
+
+    pub fn scroll_overview(&mut self, delta_x: isize, delta_y: isize) {
+        if let Some(overview) = self.overview.as_mut() {
+            overview.scroll(delta_x, delta_y);
+        }
+    }
+
+    pub fn is_log_open(&self) -> bool {
+        self.log_viewer.is_some()
+    }
+
+    pub fn toggle_log(&mut self) {
+        self.log_viewer = match self.log_viewer {
+            Some(_) => None,
+            None => Some(LogViewer::new()),
+        };
+    }
+
+    pub fn scroll_log(&mut self, delta: isize) {
+        if let Some(log_viewer) = self.log_viewer.as_mut() {
+            log_viewer.scroll(delta);
+        }
+    }
+
+    // filters are numbered from 1 in the order of MessageCategory::ALL
+    pub fn toggle_log_category(&mut self, number: usize) {
+        let category = number
+            .checked_sub(1)
+            .and_then(|index| MessageCategory::ALL.get(index));
+        if let (Some(log_viewer), Some(category)) = (self.log_viewer.as_mut(), category) {
+            log_viewer.toggle_category(*category);
+        }
+    }
+
+    pub fn is_console_open(&self) -> bool {
+        self.console.is_open
+    }
+
+    pub fn open_console(&mut self) {
+        self.console.open();
+    }
+
+    pub fn handle_console_key(&mut self, key_code: KeyCode) -> ConsoleAction {
+        self.console.handle_key(key_code)
+    }
+
+    pub fn get_action(&self, event: &KeyEvent) -> Option<Action> {
+        self.key_bindings.get_action(event)
+    }
+
+    pub fn is_binding_screen_open(&self) -> bool {
+        self.binding_screen.is_some()
+    }
+
+    pub fn open_binding_screen(&mut self) {
+        self.binding_screen = Some(BindingScreen::new());
+    }
+
+    // returns what should be announced in the message log, if anything
+    pub fn handle_binding_screen_key(&mut self, event: &KeyEvent) -> Option<String> {
+        let binding_screen = self.binding_screen.as_mut()?;
+        match binding_screen.handle_key(event, &mut self.key_bindings) {
+            BindingScreenAction::None => None,
+            BindingScreenAction::Message(message) => Some(message),
+            BindingScreenAction::Close => {
+                self.binding_screen = None;
+                None
+            }
+        }
+    }
+
+    pub fn toggle_debug_overlay(&mut self) {
+        self.is_debug_overlay_open = !self.is_debug_overlay_open;
+    }
+
+    pub fn print_terminal(&mut self, game: &Game) {
+        let player = game.player();
+        let map_guard = game.map();
+        let chat = game.messages();
+
+        let mut frame = self.create_frame();
+        if self.binding_screen.is_some() {
+            self.draw_binding_screen(&mut frame);
+        } else if self.log_viewer.is_some() {
+            self.draw_log(&mut frame, chat);
+        } else if self.overview.is_some() {
+            self.draw_overview(&mut frame, map_guard);
+        } else {
+            self.draw_game(&mut frame, player, map_guard, chat, game.editor());
+            if game.is_over() {
+                self.draw_game_over(&mut frame, game.turn());
+            }
+        }
+
+        // a failed write only loses this frame, the next one is drawn from scratch
+        if self.target.present(frame).is_err() {
+            self.target.invalidate();
+        }
     }
 
-    pub(crate) async fn print_terminal<'a>(
-        &self,
+    // drawn before there is a game, so it gets a frame of its own
+    pub fn print_load_screen(&mut self, load_screen: &LoadScreen) {
+        let mut frame = self.create_frame();
+        frame.put_str(
+            0,
+            0,
+            "Project Aether (up/down select, enter start, esc quit)",
+            CellStyle::PLAIN.bold(),
+        );
+        for (row, line) in load_screen.get_lines().iter().enumerate() {
+            frame.put_str(0, row + 2, line, CellStyle::PLAIN);
+        }
+        if let Some(error) = &load_screen.error {
+            let row = load_screen.slots.len() + 4;
+            frame.put_str(0, row, error, CellStyle::PLAIN.bold());
+        }
+
+        if self.target.present(frame).is_err() {
+            self.target.invalidate();
+        }
+    }
+
+    fn create_frame(&self) -> FrameBuffer {
+        let (width, height) = self.target.get_size();
+        FrameBuffer::new(width, height)
+    }
+
+    fn draw_game(
+        &mut self,
+        frame: &mut FrameBuffer,
         player: &Player,
-        map_manager_clone: &mut MutexGuard<'a, MapManager>,
-        chat: &mut Arc<Mutex<Chat>>,
+        map_guard: &MapData,
+        chat: &Chat,
+        editor: Option<&MapEditor>,
     ) {
-        let mut stdout = stdout();
-        let player_pos = player.position;
-        //stdout.queue(crossterm::cursor::MoveTo(player_pos.x as u16, player_pos.y as u16)).unwrap();
-
-        let mut str_map = String::new();
-        let mut tmp_plr = player.clone();
-        let mut tmp_chat = chat.lock().await;
-        let map_guard = map_manager_clone
-            .get_map(map_manager_clone.current_map_index)
-            .expect("map data");
-        let modules = [
-            tmp_plr.status.get_status(),
-            tmp_plr
-                .inventory
-                .get_inventory_to_size(2, format!("FLOOR: {}", player.current_floor)),
+        let mut panels = self.get_panels(player, chat);
+        if let Some(editor) = editor {
+            // right under the status bar, it matters more than anything else while editing
+            panels.insert(1, self.get_editor_panel(editor));
+        }
+        // the minimap is sized up front but can only mark the viewport once the layout is done
+        let minimap_index = panels.len();
+        panels.push(
+            Panel::new("Minimap", Region::SideBar).with_lines(self.minimap.get_lines(
+                map_guard,
+                player.position,
+                None,
+            )),
+        );
+        let layout = HudLayout::arrange(frame.width, frame.height, &panels);
+
+        let map_area = layout.map_area;
+        let map_size = map_guard.get_size();
+        let view_size = Vec2::new(
+            map_size.x.min(map_area.width),
+            map_size.y.min(map_area.height),
+        );
+        let focus = editor.map_or(player.position, |editor| editor.cursor);
+        self.camera.follow(focus, map_size, view_size);
+
+        for view_y in 0..view_size.y {
+            for view_x in 0..view_size.x {
+                let position = self.camera.to_map_position(view_x, view_y);
+                let cell = match editor {
+                    Some(editor) => self.get_editor_cell(map_guard, position, editor),
+                    None => self.get_map_cell(map_guard, position),
+                };
+                frame.set(map_area.x + view_x, map_area.y + view_y, cell);
+            }
+        }
+
+        // no point outlining the viewport when the whole map is on screen
+        if view_size != map_size {
+            let viewport = Rect::new(
+                self.camera.offset.x,
+                self.camera.offset.y,
+                view_size.x,
+                view_size.y,
+            );
+            panels[minimap_index] = Panel::new("Minimap", Region::SideBar).with_lines(
+                self.minimap
+                    .get_lines(map_guard, player.position, Some(viewport)),
+            );
+        }
+
+        for (index, area) in layout.panel_areas {
+            panels[index].draw(frame, area);
+        }
+
+        // debug readouts float over the top right of the map so they never push the hud around
+        if self.is_debug_overlay_open && !chat.debug_lines.is_empty() {
+            let debug_panel = Panel::new("Debug", Region::SideBar).with_lines(
+                chat.debug_lines
+                    .iter()
+                    .map(|(slot, line)| format!("{}: {}", slot, line))
+                    .collect(),
+            );
+            let width = debug_panel.get_width().min(map_area.width);
+            let height = debug_panel.get_height().min(map_area.height);
+            let area = Rect::new(
+                map_area.x + map_area.width - width,
+                map_area.y,
+                width,
+                height,
+            );
+            debug_panel.draw(frame, area);
+        }
+    }
+
+    // the game stays drawn underneath, so what killed the player is still there to see
+    fn draw_game_over(&self, frame: &mut FrameBuffer, turn: u64) {
+        let panel = Panel::new("Game over", Region::SideBar).with_styled_lines(vec![
+            (
+                format!("You died on turn {}.", turn),
+                CellStyle::PLAIN.bold(),
+            ),
+            (String::new(), CellStyle::PLAIN),
+            (
+                format!(
+                    "{} opens the console, load <slot> goes back to a save.",
+                    self.key_bindings.get_label(Action::Console)
+                ),
+                CellStyle::PLAIN,
+            ),
+            (
+                format!("{} quits.", self.key_bindings.get_label(Action::Quit)),
+                CellStyle::PLAIN,
+            ),
+        ]);
+        let width = panel.get_width().min(frame.width);
+        let height = panel.get_height().min(frame.height);
+        let area = Rect::new(
+            (frame.width - width) / 2,
+            (frame.height - height) / 2,
+            width,
+            height,
+        );
+        let blank = " ".repeat(width);
+        for y in area.y..area.y + height {
+            frame.put_str(area.x, y, &blank, CellStyle::PLAIN);
+        }
+        panel.draw(frame, area);
+    }
+
+    fn draw_log(&mut self, frame: &mut FrameBuffer, chat: &Chat) {
+        let log_viewer = match self.log_viewer.as_mut() {
+            Some(log_viewer) => log_viewer,
+            None => return,
+        };
+
+        let title = format!(
+            "Message log (up/down/pgup/pgdn scroll, 1-5 filter, {} close)",
+            self.key_bindings.get_label(Action::Log)
+        );
+        frame.put_str(0, 0, &title, CellStyle::PLAIN.bold());
+        frame.put_str(0, 1, &log_viewer.get_filter_line(), CellStyle::PLAIN);
+
+        let height = frame.height.saturating_sub(3);
+        for (row, message) in log_viewer.get_lines(chat, height).into_iter().enumerate() {
+            frame.put_str(
+                0,
+                row + 3,
+                &message.get_log_line(),
+                self.palette
+                    .message_style(message.category, message.severity),
+            );
+        }
+    }
+
+    // the whole screen shows the remembered level, scrolled independently of the player
+    fn draw_overview(&mut self, frame: &mut FrameBuffer, map_guard: &MapData) {
+        let mut overview = match self.overview {
+            Some(overview) => overview,
+            None => return,
+        };
+
+        let title = format!(
+            "Overview (movement keys scroll, {} close)",
+            self.key_bindings.get_label(Action::Overview)
+        );
+        frame.put_str(0, 0, &title, CellStyle::PLAIN.bold());
+        let map_size = map_guard.get_size();
+        let view_size = Vec2::new(
+            map_size.x.min(frame.width),
+            map_size.y.min(frame.height.saturating_sub(1)),
+        );
+        overview.clamp(map_size, view_size);
+
+        for view_y in 0..view_size.y {
+            for view_x in 0..view_size.x {
+                let position = overview.to_map_position(view_x, view_y);
+                frame.set(view_x, view_y + 1, self.get_map_cell(map_guard, position));
+            }
+        }
+        self.overview = Some(overview);
+    }
+
+    fn draw_binding_screen(&mut self, frame: &mut FrameBuffer) {
+        let binding_screen = match self.binding_screen {
+            Some(binding_screen) => binding_screen,
+            None => return,
+        };
+
+        frame.put_str(
+            0,
+            0,
+            "Key bindings (up/down select, enter rebind, tab next preset, w save, esc close)",
+            CellStyle::PLAIN.bold(),
+        );
+        frame.put_str(
+            0,
+            1,
+            &format!("Preset: {}", self.key_bindings.preset.name()),
+            CellStyle::PLAIN,
+        );
+
+        // keep the selected line on screen when the list is taller than the terminal
+        let height = frame.height.saturating_sub(3);
+        let first_line = (binding_screen.selected_index + 1).saturating_sub(height);
+        let lines = binding_screen.get_lines(&self.key_bindings);
+        for (row, line) in lines.iter().skip(first_line).take(height).enumerate() {
+            frame.put_str(0, row + 3, line, CellStyle::PLAIN);
+        }
+    }
+
+    // new hud modules only need a panel here, the layout finds room for them
+    fn get_panels(&self, player: &Player, chat: &Chat) -> Vec<Panel> {
+        let mut status_line = player.get_effective_status().get_status();
+        status_line.push(format!("FLOOR: {}", player.current_floor));
+
+        let mut panels =
+            vec![Panel::new("Status", Region::TopBar).with_lines(vec![status_line.join("  ")])];
+
+        // an open backpack goes first so it is never the side panel that gets cut short
+        if player.inventory.is_open {
+            panels.push(
+                Panel::new("Backpack", Region::SideBar)
+                    .with_lines(player.inventory.get_selection_menu()),
+            );
+        }
+
+        panels.extend([
+            Panel::new("Inventory", Region::SideBar).with_lines(player.inventory.get_inventory()),
+            Panel::new("Equipment", Region::SideBar).with_lines(player.equipment.get_equipment()),
+        ]);
+
+        if self.console.is_open {
+            panels.push(Panel::new("Console", Region::BottomLog).with_lines(vec![
+                self.console.get_hint(),
+                format!("> {}_", self.console.input),
+            ]));
+        }
+
+        let messages = chat
+            .get_chat_lines(MESSAGE_LINES)
+            .into_iter()
+            .map(|message| {
+                (
+                    message.get_text(),
+                    self.palette
+                        .message_style(message.category, message.severity),
+                )
+            })
+            .collect();
+        panels.push(
+            Panel::new("Messages", Region::BottomLog)
+                .with_styled_lines(messages)
+                .with_height(MESSAGE_LINES),
+        );
+
+        panels
+    }
+
+    fn get_editor_panel(&self, editor: &MapEditor) -> Panel {
+        let mut lines = vec![
+            format!("Brush: {}", editor.get_brush().get_name()),
+            format!("Cursor: {},{}", editor.cursor.x, editor.cursor.y),
         ];
+        if let Some(corner) = editor.corner {
+            lines.push(format!("Corner: {},{}", corner.x, corner.y));
+        }
+        lines.extend([
+            format!(
+                "Undo: {}  Redo: {}",
+                editor.get_undo_count(),
+                editor.get_redo_count()
+            ),
+            "[ ] brush  Enter paint".to_string(),
+            "r corner  ctrl+z undo".to_string(),
+            "ctrl+y redo  ctrl+s save".to_string(),
+            "Esc close".to_string(),
+        ]);
+        Panel::new("Editor", Region::SideBar).with_lines(lines)
+    }
 
-        let mut counter = 0;
-        for tile in &map_guard.map {
-            let tile_line: String = tile
-                .iter()
-                .map(|space| {
-                    if space.is_visible || space.tile == DEFAULT_TILE_SET.player {
-                        space.tile
-                    } else {
-                        ' ' //show no map tile at iteration if not visible or player
-                    }
-                })
-                .collect();
-
-            if counter <= modules.len() {
-                str_map += &*format!(
-                    "{}      {}      {}\n",
-                    tile_line, modules[0][counter], modules[1][counter]
-                );
-                counter += 1;
+    // the whole map shows while editing, fog or not, with the cursor and corner picked out
+    fn get_editor_cell(&self, map_guard: &MapData, position: Vec2, editor: &MapEditor) -> Cell {
+        if map_guard.get_space(position).is_none() {
+            return Cell::EMPTY;
+        }
+        let display_tile = map_guard.get_display_tile(position);
+        let style = self.palette.tile_style(display_tile, &map_guard.tile_set);
+        if position == editor.cursor || editor.corner == Some(position) {
+            Cell::new(display_tile, style.reversed())
+        } else {
+            Cell::new(display_tile, style)
+        }
+    }
+
+    fn get_map_cell(&self, map_guard: &MapData, position: Vec2) -> Cell {
+        let space = match map_guard.get_space(position) {
+            Some(space) => space,
+            None => return Cell::EMPTY,
+        };
+
+        if space.is_visible || space.tile == DEFAULT_TILE_SET.player {
+            let display_tile = map_guard.get_display_tile(position);
+            Cell::new(
+                display_tile,
+                self.palette.tile_style(display_tile, &map_guard.tile_set),
+            )
+        } else if map_guard.is_explored(position) {
+            // remembered tiles only show the terrain, not whatever was standing on it
+            let display_tile = if space.is_monster {
+                map_guard.tile_set.floor
             } else {
-                //println!("{}", tile_line);
-                str_map += &*format!("{}\n", tile_line);
-            }
+                map_guard.get_display_tile(position)
+            };
+            Cell::new(
+                display_tile,
+                self.palette
+                    .tile_style(display_tile, &map_guard.tile_set)
+                    .dimmed(),
+            )
+        } else {
+            Cell::EMPTY //show no map tile at iteration if not visible or player
         }
-        //stdout.queue(crossterm::cursor::MoveTo(player.player_position.y as u16, player.player_position.x as u16)).unwrap();
-        stdout.queue(crossterm::cursor::MoveTo(0, 0)).unwrap();
-        stdout
-            .queue(terminal::Clear(terminal::ClearType::All))
-            .unwrap();
-        /*stdout
-        .queue(terminal::Clear(terminal::ClearType::All))
-        .unwrap();*/
-
-        println!("{}", str_map);
-        tmp_chat.print_chat();
-        drop(tmp_chat);
-        //drop(map_manager_guard);
     }
 }
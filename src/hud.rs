@@ -0,0 +1,263 @@
+use crate::palette::CellStyle;
+use crate::renderer::{Cell, FrameBuffer};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    pub const fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    // the area left for content once the border is drawn
+    pub(crate) fn inner(&self) -> Rect {
+        Rect::new(
+            self.x + 1,
+            self.y + 1,
+            self.width.saturating_sub(2),
+            self.height.saturating_sub(2),
+        )
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Region {
+    SideBar,
+    TopBar,
+    BottomLog,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Panel {
+    pub title: String,
+    pub region: Region,
+    pub lines: Vec<(String, CellStyle)>,
+    // bottom panels can ask for a fixed number of content rows, the rest size to their lines
+    pub height: Option<usize>,
+}
+
+impl Panel {
+    pub(crate) fn new(title: &str, region: Region) -> Self {
+        Panel {
+            title: title.to_string(),
+            region,
+            lines: Vec::new(),
+            height: None,
+        }
+    }
+
+    pub(crate) fn with_lines(mut self, lines: Vec<String>) -> Self {
+        self.lines
+            .extend(lines.into_iter().map(|line| (line, CellStyle::PLAIN)));
+        self
+    }
+
+    pub(crate) fn with_styled_lines(mut self, lines: Vec<(String, CellStyle)>) -> Self {
+        self.lines.extend(lines);
+        self
+    }
+
+    pub(crate) fn with_height(mut self, height: usize) -> Self {
+        self.height = Some(height);
+        self
+    }
+
+    // outer size including the border
+    pub(crate) fn get_width(&self) -> usize {
+        let content_width = self
+            .lines
+            .iter()
+            .map(|(line, _)| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        content_width.max(self.title.chars().count() + 2) + 2
+    }
+
+    pub(crate) fn get_height(&self) -> usize {
+        self.height.unwrap_or(self.lines.len()) + 2
+    }
+
+    pub(crate) fn draw(&self, frame: &mut FrameBuffer, area: Rect) {
+        if area.width < 2 || area.height < 2 {
+            return;
+        }
+
+        let right = area.x + area.width - 1;
+        let bottom = area.y + area.height - 1;
+        for x in area.x + 1..right {
+            frame.set(x, area.y, Cell::new('─', CellStyle::PLAIN));
+            frame.set(x, bottom, Cell::new('─', CellStyle::PLAIN));
+        }
+        for y in area.y + 1..bottom {
+            frame.set(area.x, y, Cell::new('│', CellStyle::PLAIN));
+            frame.set(right, y, Cell::new('│', CellStyle::PLAIN));
+        }
+        frame.set(area.x, area.y, Cell::new('┌', CellStyle::PLAIN));
+        frame.set(right, area.y, Cell::new('┐', CellStyle::PLAIN));
+        frame.set(area.x, bottom, Cell::new('└', CellStyle::PLAIN));
+        frame.set(right, bottom, Cell::new('┘', CellStyle::PLAIN));
+
+        let inner = area.inner();
+        let title: String = self.title.chars().take(inner.width).collect();
+        frame.put_str(inner.x, area.y, &title, CellStyle::PLAIN.bold());
+
+        for (row, (line, style)) in self.lines.iter().take(inner.height).enumerate() {
+            let line: String = line.chars().take(inner.width).collect();
+            frame.put_str(inner.x, inner.y + row, &line, *style);
+        }
+    }
+}
+
+// where everything ended up, panels that did not fit on screen are left out
+#[derive(Clone, PartialEq, Debug)]
+pub struct HudLayout {
+    pub map_area: Rect,
+    pub panel_areas: Vec<(usize, Rect)>,
+}
+
+impl HudLayout {
+    // top panels sit side by side in one row, bottom panels stack above the bottom edge
+    // and side panels stack down the right in the order given, the map gets whatever is
+    // left in the middle
+    pub(crate) fn arrange(width: usize, height: usize, panels: &[Panel]) -> HudLayout {
+        let mut panel_areas = Vec::new();
+
+        let top_height = panels
+            .iter()
+            .filter(|panel| panel.region == Region::TopBar)
+            .map(|panel| panel.get_height())
+            .max()
+            .unwrap_or(0)
+            .min(height);
+        let mut top_x = 0;
+        for (index, panel) in panels.iter().enumerate() {
+            if panel.region != Region::TopBar || top_x >= width {
+                continue;
+            }
+            let panel_width = panel.get_width().min(width - top_x);
+            panel_areas.push((index, Rect::new(top_x, 0, panel_width, top_height)));
+            top_x += panel_width;
+        }
+
+        let mut bottom_y = height;
+        for (index, panel) in panels.iter().enumerate().rev() {
+            if panel.region != Region::BottomLog {
+                continue;
+            }
+            let panel_height = panel.get_height();
+            if bottom_y < top_height + panel_height {
+                continue;
+            }
+            bottom_y -= panel_height;
+            panel_areas.push((index, Rect::new(0, bottom_y, width, panel_height)));
+        }
+
+        // the side bar never takes more than half the width so the map stays playable
+        let side_width = panels
+            .iter()
+            .filter(|panel| panel.region == Region::SideBar)
+            .map(|panel| panel.get_width())
+            .max()
+            .unwrap_or(0)
+            .min(width / 2);
+        let mut side_x = width - side_width;
+        let mut side_y = top_height;
+        for (index, panel) in panels.iter().enumerate() {
+            if panel.region != Region::SideBar {
+                continue;
+            }
+            // a side panel that runs out of room is cut short as long as one line still shows
+            let panel_height = panel.get_height().min(bottom_y.saturating_sub(side_y));
+            if panel_height < 3 {
+                continue;
+            }
+            panel_areas.push((index, Rect::new(side_x, side_y, side_width, panel_height)));
+            side_y += panel_height;
+        }
+        // the map keeps the full width when not one side panel fit
+        if side_y == top_height {
+            side_x = width;
+        }
+
+        HudLayout {
+            map_area: Rect::new(0, top_height, side_x, bottom_y - top_height),
+            panel_areas,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn panels() -> Vec<Panel> {
+        vec![
+            Panel::new("Status", Region::TopBar).with_lines(vec!["HP 10/10".to_string()]),
+            Panel::new("Inventory", Region::SideBar)
+                .with_lines(vec!["Rusty Dagger".to_string(); 5]),
+            Panel::new("Log", Region::BottomLog).with_height(4),
+        ]
+    }
+
+    fn get_area(layout: &HudLayout, index: usize) -> Option<Rect> {
+        layout
+            .panel_areas
+            .iter()
+            .find(|(panel_index, _)| *panel_index == index)
+            .map(|(_, area)| *area)
+    }
+
+    #[test]
+    fn every_panel_fits_on_a_full_terminal() {
+        let layout = HudLayout::arrange(80, 24, &panels());
+        assert_eq!(get_area(&layout, 0), Some(Rect::new(0, 0, 10, 3)));
+        assert_eq!(get_area(&layout, 1), Some(Rect::new(66, 3, 14, 7)));
+        assert_eq!(get_area(&layout, 2), Some(Rect::new(0, 18, 80, 6)));
+        assert_eq!(layout.map_area, Rect::new(0, 3, 66, 15));
+    }
+
+    #[test]
+    fn a_small_terminal_drops_the_log_and_cuts_the_side_bar_short() {
+        let layout = HudLayout::arrange(20, 8, &panels());
+        assert_eq!(get_area(&layout, 0), Some(Rect::new(0, 0, 10, 3)));
+        // never more than half the width, and only the rows left under the top bar
+        assert_eq!(get_area(&layout, 1), Some(Rect::new(10, 3, 10, 5)));
+        assert_eq!(get_area(&layout, 2), None);
+        assert_eq!(layout.map_area, Rect::new(0, 3, 10, 5));
+    }
+
+    #[test]
+    fn the_map_takes_the_width_no_side_panel_could_use() {
+        let layout = HudLayout::arrange(20, 5, &panels());
+        assert_eq!(get_area(&layout, 1), None);
+        assert_eq!(layout.map_area, Rect::new(0, 3, 20, 2));
+    }
+
+    #[test]
+    fn top_panels_are_clipped_at_the_right_edge() {
+        let mut panels = panels();
+        panels.push(Panel::new("Depth", Region::TopBar).with_lines(vec!["Floor 1".to_string()]));
+        let layout = HudLayout::arrange(15, 24, &panels);
+        assert_eq!(get_area(&layout, 3), Some(Rect::new(10, 0, 5, 3)));
+
+        for (width, height) in [(0, 0), (1, 1), (4, 2)] {
+            let layout = HudLayout::arrange(width, height, &panels);
+            let map_area = layout.map_area;
+            assert!(map_area.x + map_area.width <= width);
+            assert!(map_area.y + map_area.height <= height);
+            for (_, area) in layout.panel_areas {
+                assert!(area.x + area.width <= width && area.y + area.height <= height);
+            }
+        }
+    }
+}
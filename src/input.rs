@@ -0,0 +1,544 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::action::Action;
+
+pub const KEY_BINDINGS_PATH: &str = "keybindings.cfg";
+
+// a key together with the modifiers that have to be held for it
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    // shift is already part of an upper case letter, so it only counts for the other keys
+    pub(crate) fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let mut modifiers =
+            modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        if matches!(code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        KeyBinding { code, modifiers }
+    }
+
+    pub(crate) fn from_event(event: &KeyEvent) -> Self {
+        KeyBinding::new(event.code, event.modifiers)
+    }
+
+    // e.g. "k", "up", "ctrl+p", "shift+f1", see get_key_name for the names
+    pub(crate) fn parse(text: &str) -> Option<Self> {
+        let mut parts: Vec<&str> = text.split('+').collect();
+        // "+" and "ctrl++" bind the plus key itself
+        if text.ends_with("++") || text == "+" {
+            parts.truncate(parts.len() - 2);
+            parts.push("+");
+        }
+        let key = parts.pop()?;
+
+        let mut modifiers = KeyModifiers::NONE;
+        for modifier in parts {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return None,
+            };
+        }
+
+        let mut code = parse_key_code(key)?;
+        if let KeyCode::Char(character) = code {
+            if modifiers.contains(KeyModifiers::SHIFT) {
+                code = KeyCode::Char(character.to_ascii_uppercase());
+            }
+        }
+        Some(KeyBinding::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "shift+")?;
+        }
+        write!(f, "{}", get_key_name(self.code))
+    }
+}
+
+fn parse_key_code(key: &str) -> Option<KeyCode> {
+    let mut characters = key.chars();
+    if let (Some(character), None) = (characters.next(), characters.next()) {
+        return Some(KeyCode::Char(character));
+    }
+
+    let code = match key.to_lowercase().as_str() {
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        // the middle of the numpad with num lock off
+        "begin" => KeyCode::KeypadBegin,
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "backspace" => KeyCode::Backspace,
+        "delete" => KeyCode::Delete,
+        "insert" => KeyCode::Insert,
+        "space" => KeyCode::Char(' '),
+        name => {
+            let number = name.strip_prefix('f')?.parse().ok()?;
+            if !(1..=12).contains(&number) {
+                return None;
+            }
+            KeyCode::F(number)
+        }
+    };
+    Some(code)
+}
+
+fn get_key_name(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => "space".to_string(),
+        KeyCode::Char(character) => character.to_string(),
+        KeyCode::Up => "up".to_string(),
+        KeyCode::Down => "down".to_string(),
+        KeyCode::Left => "left".to_string(),
+        KeyCode::Right => "right".to_string(),
+        KeyCode::Home => "home".to_string(),
+        KeyCode::End => "end".to_string(),
+        KeyCode::PageUp => "pageup".to_string(),
+        KeyCode::PageDown => "pagedown".to_string(),
+        KeyCode::KeypadBegin => "begin".to_string(),
+        KeyCode::Enter => "enter".to_string(),
+        KeyCode::Esc => "esc".to_string(),
+        KeyCode::Tab => "tab".to_string(),
+        KeyCode::BackTab => "backtab".to_string(),
+        KeyCode::Backspace => "backspace".to_string(),
+        KeyCode::Delete => "delete".to_string(),
+        KeyCode::Insert => "insert".to_string(),
+        KeyCode::F(number) => format!("f{}", number),
+        other => format!("{:?}", other).to_lowercase(),
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Preset {
+    Arrows,
+    Vi,
+    Wasd,
+    Numpad,
+}
+
+impl Preset {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Preset::Arrows => "arrows",
+            Preset::Vi => "vi",
+            Preset::Wasd => "wasd",
+            Preset::Numpad => "numpad",
+        }
+    }
+
+    pub(crate) fn next(&self) -> Preset {
+        match self {
+            Preset::Arrows => Preset::Vi,
+            Preset::Vi => Preset::Wasd,
+            Preset::Wasd => Preset::Numpad,
+            Preset::Numpad => Preset::Arrows,
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Preset> {
+        [Preset::Arrows, Preset::Vi, Preset::Wasd, Preset::Numpad]
+            .into_iter()
+            .find(|preset| preset.name() == name)
+    }
+
+    // presets are written in the config format, each one goes on top of the common bindings
+    fn get_bindings(&self) -> &'static str {
+        match self {
+            Preset::Arrows => "",
+            Preset::Vi => VI_BINDINGS,
+            Preset::Wasd => WASD_BINDINGS,
+            Preset::Numpad => NUMPAD_BINDINGS,
+        }
+    }
+}
+
+// the arrow keys move in every preset, the diagonals sit where num lock off puts them
+const COMMON_BINDINGS: &str = "
+move_up = up
+move_down = down
+move_left = left
+move_right = right
+move_up_left = home
+move_up_right = pageup
+move_down_left = end
+move_down_right = pagedown
+wait = . begin
+pick_up = g ,
+use = enter
+inventory = i
+equip = e
+unequip = u
+search = s
+drink = q
+read = r
+eat = a
+console = tab
+log = l
+overview = m
+minimap = M
+palette = f2
+debug_overlay = f3
+key_bindings = ?
+quit = esc ctrl+c
+";
+
+const VI_BINDINGS: &str = "
+move_up = k up
+move_down = j down
+move_left = h left
+move_right = l right
+move_up_left = y home
+move_up_right = u pageup
+move_down_left = b end
+move_down_right = n pagedown
+unequip = T
+log = ctrl+p
+";
+
+const WASD_BINDINGS: &str = "
+move_up = w up
+move_down = s down
+move_left = a left
+move_right = d right
+move_up_left = q home
+move_up_right = e pageup
+move_down_left = z end
+move_down_right = c pagedown
+wait = x . begin
+equip = t
+search = f
+drink = p
+eat = v
+";
+
+const NUMPAD_BINDINGS: &str = "
+move_up = 8 up
+move_down = 2 down
+move_left = 4 left
+move_right = 6 right
+move_up_left = 7 home
+move_up_right = 9 pageup
+move_down_left = 1 end
+move_down_right = 3 pagedown
+wait = 5 . begin
+";
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BindingError {
+    pub path: PathBuf,
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for BindingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} line {}: {}",
+            self.path.display(),
+            self.line,
+            self.message
+        )
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct KeyBindings {
+    pub preset: Preset,
+    // the file they were loaded from, saving writes them back there
+    pub path: PathBuf,
+    bindings: HashMap<Action, Vec<KeyBinding>>,
+}
+
+impl KeyBindings {
+    pub fn new(preset: Preset) -> Self {
+        let mut key_bindings = KeyBindings {
+            preset,
+            path: PathBuf::from(KEY_BINDINGS_PATH),
+            bindings: HashMap::new(),
+        };
+        // the built-in tables are known to parse, so there are no errors to report
+        key_bindings.apply(COMMON_BINDINGS);
+        key_bindings.apply(preset.get_bindings());
+        key_bindings
+    }
+
+    // a missing file just means the default preset, a broken line is skipped and reported
+    pub fn load(path: &Path) -> (Self, Vec<BindingError>) {
+        let (mut key_bindings, errors) = match fs::read_to_string(path) {
+            Ok(contents) => KeyBindings::parse(&contents, path),
+            Err(_) => (KeyBindings::new(Preset::Arrows), Vec::new()),
+        };
+        key_bindings.path = path.to_path_buf();
+        (key_bindings, errors)
+    }
+
+    // drops every changed key and starts over from a preset, still saving to the same file
+    pub(crate) fn reset_to(&mut self, preset: Preset) {
+        let path = std::mem::take(&mut self.path);
+        *self = KeyBindings::new(preset);
+        self.path = path;
+    }
+
+    //   # comments start with a hash
+    //   preset = vi
+    //   quit = esc ctrl+q
+    //
+    // the preset line has to come first, every action line replaces the keys of that action
+    pub(crate) fn parse(contents: &str, path: &Path) -> (Self, Vec<BindingError>) {
+        let mut key_bindings = KeyBindings::new(Preset::Arrows);
+        let mut errors = Vec::new();
+
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = |message: String| BindingError {
+                path: path.to_path_buf(),
+                line: index + 1,
+                message,
+            };
+            let (name, keys) = match line.split_once('=') {
+                Some((name, keys)) => (name.trim(), keys.trim()),
+                None => {
+                    errors.push(error("expected name = keys".to_string()));
+                    continue;
+                }
+            };
+
+            if name == "preset" {
+                match Preset::from_name(keys) {
+                    Some(preset) => key_bindings.reset_to(preset),
+                    None => errors.push(error(format!("unknown preset '{}'", keys))),
+                }
+                continue;
+            }
+
+            if let Err(message) = key_bindings.apply_line(name, keys) {
+                errors.push(error(message));
+            }
+        }
+
+        (key_bindings, errors)
+    }
+
+    fn apply(&mut self, bindings: &str) {
+        for line in bindings.lines() {
+            if let Some((name, keys)) = line.split_once('=') {
+                let _ = self.apply_line(name.trim(), keys.trim());
+            }
+        }
+    }
+
+    fn apply_line(&mut self, name: &str, keys: &str) -> Result<(), String> {
+        let action = Action::from_name(name).ok_or(format!("unknown action '{}'", name))?;
+        let keys = keys
+            .split_whitespace()
+            .map(|key| KeyBinding::parse(key).ok_or(format!("unknown key '{}'", key)))
+            .collect::<Result<Vec<KeyBinding>, String>>()?;
+        self.bindings.insert(action, keys);
+        Ok(())
+    }
+
+    // a key bound twice goes to whichever action comes first in Action::ALL
+    pub(crate) fn get_action(&self, event: &KeyEvent) -> Option<Action> {
+        let binding = KeyBinding::from_event(event);
+        Action::ALL
+            .into_iter()
+            .find(|action| self.get_keys(*action).contains(&binding))
+    }
+
+    pub(crate) fn get_keys(&self, action: Action) -> &[KeyBinding] {
+        self.bindings
+            .get(&action)
+            .map_or(&[], |keys| keys.as_slice())
+    }
+
+    pub(crate) fn get_label(&self, action: Action) -> String {
+        match self.get_keys(action) {
+            [] => "unbound".to_string(),
+            keys => keys
+                .iter()
+                .map(|key| key.to_string())
+                .collect::<Vec<String>>()
+                .join(" "),
+        }
+    }
+
+    // the key moves over from whatever action had it before
+    pub(crate) fn rebind(&mut self, action: Action, binding: KeyBinding) {
+        for keys in self.bindings.values_mut() {
+            keys.retain(|key| *key != binding);
+        }
+        self.bindings.insert(action, vec![binding]);
+    }
+
+    pub(crate) fn to_config(&self) -> String {
+        let mut lines = vec![
+            "# written by the key bindings screen".to_string(),
+            format!("preset = {}", self.preset.name()),
+        ];
+        for action in Action::ALL {
+            let keys: Vec<String> = self
+                .get_keys(action)
+                .iter()
+                .map(|key| key.to_string())
+                .collect();
+            lines.push(format!("{} = {}", action.name(), keys.join(" ")));
+        }
+        lines.join("\n") + "\n"
+    }
+
+    pub(crate) fn save(&self) -> std::io::Result<()> {
+        fs::write(&self.path, self.to_config())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn keys_parse_with_their_modifiers() {
+        let parse = |text| KeyBinding::parse(text);
+        assert_eq!(parse("k"), Some(KeyBinding::new(KeyCode::Char('k'), KeyModifiers::NONE)));
+        assert_eq!(parse("up"), Some(KeyBinding::new(KeyCode::Up, KeyModifiers::NONE)));
+        assert_eq!(
+            parse("Ctrl+P"),
+            Some(KeyBinding::new(KeyCode::Char('P'), KeyModifiers::CONTROL))
+        );
+        // shift on a letter is the upper case letter, on anything else it stays a modifier
+        assert_eq!(parse("shift+a"), parse("A"));
+        assert_eq!(
+            parse("shift+f1"),
+            Some(KeyBinding::new(KeyCode::F(1), KeyModifiers::SHIFT))
+        );
+        assert_eq!(parse("+"), Some(KeyBinding::new(KeyCode::Char('+'), KeyModifiers::NONE)));
+        assert_eq!(
+            parse("ctrl++"),
+            Some(KeyBinding::new(KeyCode::Char('+'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(parse("space"), parse(" "));
+
+        for text in ["", "f13", "f0", "hyper+k", "ctrl+", "upp"] {
+            assert_eq!(parse(text), None, "{:?}", text);
+        }
+        for text in ["k", "ctrl+p", "alt+enter", "shift+tab", "f12", "space", "ctrl++"] {
+            assert_eq!(parse(text).map(|binding| binding.to_string()).as_deref(), Some(text));
+        }
+    }
+
+    #[test]
+    fn presets_go_on_top_of_the_common_bindings() {
+        let arrows = KeyBindings::new(Preset::Arrows);
+        assert_eq!(arrows.get_action(&key(KeyCode::Char('k'), KeyModifiers::NONE)), None);
+        assert_eq!(
+            arrows.get_action(&key(KeyCode::Char('u'), KeyModifiers::NONE)),
+            Some(Action::Unequip)
+        );
+
+        let vi = KeyBindings::new(Preset::Vi);
+        assert_eq!(
+            vi.get_action(&key(KeyCode::Char('k'), KeyModifiers::NONE)),
+            Some(Action::MoveUp)
+        );
+        assert_eq!(
+            vi.get_action(&key(KeyCode::Char('u'), KeyModifiers::NONE)),
+            Some(Action::MoveUpRight)
+        );
+        // the terminal reports shift with the upper case letter
+        assert_eq!(
+            vi.get_action(&key(KeyCode::Char('T'), KeyModifiers::SHIFT)),
+            Some(Action::Unequip)
+        );
+        assert_eq!(vi.get_action(&key(KeyCode::Up, KeyModifiers::NONE)), Some(Action::MoveUp));
+        assert_eq!(vi.get_label(Action::Log), "ctrl+p");
+
+        let mut preset = Preset::Arrows;
+        for _ in 0..4 {
+            assert_eq!(Preset::from_name(preset.name()), Some(preset));
+            preset = preset.next();
+        }
+        assert_eq!(preset, Preset::Arrows);
+    }
+
+    #[test]
+    fn broken_lines_are_reported_and_skipped() {
+        let contents = "
+# my keys
+preset = wasd
+quit = ctrl+q
+fly = x
+wait = hyper+x
+nonsense
+preset = dvorak
+";
+        let path = Path::new("keys.cfg");
+        let (key_bindings, errors) = KeyBindings::parse(contents, path);
+        assert_eq!(key_bindings.preset, Preset::Wasd);
+        assert_eq!(key_bindings.get_label(Action::Quit), "ctrl+q");
+        assert_eq!(key_bindings.get_label(Action::MoveUp), "w up");
+        assert_eq!(key_bindings.get_label(Action::Wait), "x . begin");
+
+        let errors: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+        assert_eq!(
+            errors,
+            [
+                "keys.cfg line 5: unknown action 'fly'",
+                "keys.cfg line 6: unknown key 'hyper+x'",
+                "keys.cfg line 7: expected name = keys",
+                "keys.cfg line 8: unknown preset 'dvorak'",
+            ]
+        );
+    }
+
+    #[test]
+    fn a_rebound_key_survives_saving_and_loading() {
+        let mut key_bindings = KeyBindings::new(Preset::Vi);
+        let binding = KeyBinding::parse("k").expect("a key");
+        key_bindings.rebind(Action::Search, binding);
+        assert_eq!(key_bindings.get_label(Action::MoveUp), "up");
+        assert_eq!(key_bindings.get_label(Action::Search), "k");
+
+        let (loaded, errors) = KeyBindings::parse(&key_bindings.to_config(), Path::new("keys"));
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(loaded.preset, Preset::Vi);
+        for action in Action::ALL {
+            assert_eq!(loaded.get_keys(action), key_bindings.get_keys(action), "{:?}", action);
+        }
+    }
+}
@@ -0,0 +1,444 @@
+use crate::chat::{Chat, MessageCategory};
+use crate::effect::{Effect, EffectSystem, EffectTarget};
+use crate::action::Action;
+use crate::item::Item;
+use crate::loot::LootTable;
+use crate::map_data::MapData;
+use crate::map_manager::MapManager;
+use crate::monster_manager::MonsterManager;
+use crate::player::Player;
+use crate::space::Space;
+use crate::tile_set::DEFAULT_TILE_SET;
+use crate::world_object::WorldObjectKind;
+use crate::Vec2;
+use rand::Rng;
+
+pub(crate) struct InteractionSystem {}
+
+impl InteractionSystem {
+    // bumping into a lever, chest or breakable wall, returns false when there is nothing to interact with
+    pub(crate) fn interact(
+        map: &mut MapData,
+        player: &mut Player,
+        monster_manager: &mut MonsterManager,
+        chat: &mut Chat,
+        position: Vec2,
+        rng: &mut impl Rng,
+    ) -> bool {
+        let object = match map.objects.get_mut(&position) {
+            Some(object) => object,
+            None => return false,
+        };
+
+        match &mut object.kind {
+            WorldObjectKind::Lever { is_on } => {
+                *is_on = !*is_on;
+                chat.process_chat_message("You pull the lever.");
+                let targets = object.targets.clone();
+                for target in targets {
+                    InteractionSystem::activate_target(map, player, monster_manager, chat, target);
+                }
+            }
+            WorldObjectKind::Chest {
+                contents,
+                loot_table,
+                is_open,
+            } => {
+                let mut items: Vec<Item> = Vec::new();
+                if !*is_open {
+                    items = contents
+                        .drain(..)
+                        .filter_map(|name| Item::from_name(&name))
+                        .collect();
+
+                    if let Some(loot_table) = loot_table.as_deref().and_then(LootTable::for_chest) {
+                        items.extend(loot_table.roll(player.current_floor, rng));
+                    }
+                }
+                *is_open = true;
+
+                if items.is_empty() {
+                    chat.process_chat_message("The chest is empty.");
+                    return true;
+                }
+
+                let item_names: Vec<String> = items.iter().map(|item| item.name.clone()).collect();
+
+                for item in items {
+                    player.inventory.add_item(item);
+                }
+                chat.process_categorised_message(
+                    &format!("You open the chest and find: {}.", item_names.join(", ")),
+                    MessageCategory::Loot,
+                );
+            }
+            WorldObjectKind::BreakableWall { durability } => {
+                *durability -= player.get_effective_status().str;
+                if *durability > 0 {
+                    chat.process_chat_message("The wall cracks.");
+                } else {
+                    map.objects.remove(&position);
+                    map.map[position.y][position.x] = Space::new(DEFAULT_TILE_SET.floor);
+                    chat.process_chat_message("The wall crumbles to rubble.");
+                }
+            }
+            WorldObjectKind::PressurePlate | WorldObjectKind::Trap { .. } => return false,
+        }
+        true
+    }
+
+    // stepping onto a pressure plate or a trap
+    pub(crate) fn step_on(
+        map: &mut MapData,
+        player: &mut Player,
+        monster_manager: &mut MonsterManager,
+        chat: &mut Chat,
+        position: Vec2,
+    ) {
+        let object = match map.objects.get(&position) {
+            Some(object) => object.clone(),
+            None => return,
+        };
+
+        match object.kind {
+            WorldObjectKind::PressurePlate => {
+                chat.process_chat_message("Something clicks beneath your feet.");
+                for target in object.targets {
+                    InteractionSystem::activate_target(map, player, monster_manager, chat, target);
+                }
+            }
+            WorldObjectKind::Trap { .. } => {
+                InteractionSystem::trigger_trap(map, player, monster_manager, chat, position);
+            }
+            _ => {}
+        }
+    }
+
+    // searching and picking up, returns true when the action was consumed
+    pub(crate) fn process_interaction_input(
+        player: &mut Player,
+        map_manager: &mut MapManager,
+        chat: &mut Chat,
+        rng: &mut impl Rng,
+    ) -> bool {
+        let map_index = map_manager.current_map_index;
+        let map = map_manager.get_map_mut(map_index).expect("map data");
+
+        match player.action {
+            Some(Action::Search) => InteractionSystem::search(map, player, chat, rng),
+            Some(Action::PickUp) => InteractionSystem::pick_up(map, player, chat),
+            _ => return false,
+        }
+        true
+    }
+
+    pub(crate) fn pick_up(map: &mut MapData, player: &mut Player, chat: &mut Chat) {
+        let items = match map.item_piles.remove(&player.position) {
+            Some(items) => items,
+            None => {
+                chat.process_chat_message("There is nothing here to pick up.");
+                return;
+            }
+        };
+
+        for item in items {
+            chat.process_categorised_message(
+                &format!("You pick up the {}.", item.name),
+                MessageCategory::Loot,
+            );
+            player.inventory.add_item(item);
+        }
+    }
+
+    // reveals each hidden trap around the player with even odds
+    fn search(map: &mut MapData, player: &Player, chat: &mut Chat, rng: &mut impl Rng) {
+        chat.process_chat_message("You search your surroundings.");
+
+        for (dx, dy) in [
+            (-1, -1),
+            (0, -1),
+            (1, -1),
+            (-1, 0),
+            (1, 0),
+            (-1, 1),
+            (0, 1),
+            (1, 1),
+        ] {
+            let position = match (
+                player.position.x.checked_add_signed(dx),
+                player.position.y.checked_add_signed(dy),
+            ) {
+                (Some(x), Some(y)) => Vec2::new(x, y),
+                _ => continue,
+            };
+            // a monster standing on the trap stays drawn, the trap shows once it moves off
+            let is_occupied = match map.get_space(position) {
+                Some(space) => space.is_monster,
+                None => continue,
+            };
+
+            if let Some(WorldObjectKind::Trap { is_hidden, .. }) = map
+                .objects
+                .get_mut(&position)
+                .map(|object| &mut object.kind)
+            {
+                if *is_hidden && rng.gen::<f64>() < 0.5 {
+                    *is_hidden = false;
+                    if !is_occupied {
+                        map.map[position.y][position.x] = Space::new(DEFAULT_TILE_SET.trap);
+                    }
+                    chat.process_categorised_message(
+                        "You find a hidden trap!",
+                        MessageCategory::Warning,
+                    );
+                }
+            }
+        }
+    }
+
+    // what a lever or plate does to each of its wired tiles
+    fn activate_target(
+        map: &mut MapData,
+        player: &mut Player,
+        monster_manager: &mut MonsterManager,
+        chat: &mut Chat,
+        target: Vec2,
+    ) {
+        if let Some(WorldObjectKind::Trap { .. }) =
+            map.objects.get(&target).map(|object| &object.kind)
+        {
+            InteractionSystem::trigger_trap(map, player, monster_manager, chat, target);
+            return;
+        }
+
+        let tile = map.map[target.y][target.x].tile;
+        if tile == DEFAULT_TILE_SET.closed_door_side || tile == DEFAULT_TILE_SET.closed_door_top {
+            map.get_lock_mut(target).is_locked = false;
+            map.map[target.y][target.x] = Space::new(DEFAULT_TILE_SET.open_door);
+            chat.process_chat_message("You hear a door grind open.");
+        } else if tile == DEFAULT_TILE_SET.open_door {
+            let lock = map.get_lock_mut(target);
+            lock.is_locked = true;
+            let closed_tile = lock.closed_tile;
+            map.map[target.y][target.x] = Space::new(closed_tile);
+            chat.process_chat_message("You hear a door slam shut.");
+        }
+    }
+
+    // whoever stands on the trap gets hurt, the tile under them keeps showing them until they
+    // move off and the revealed trap is put back
+    fn trigger_trap(
+        map: &mut MapData,
+        player: &mut Player,
+        monster_manager: &mut MonsterManager,
+        chat: &mut Chat,
+        trap_position: Vec2,
+    ) {
+        let damage = match map
+            .objects
+            .get_mut(&trap_position)
+            .map(|object| &mut object.kind)
+        {
+            Some(WorldObjectKind::Trap { damage, is_hidden }) => {
+                *is_hidden = false;
+                *damage
+            }
+            _ => return,
+        };
+
+        let monster_id = monster_manager
+            .get_monster_at_position(trap_position)
+            .map(|monster| monster.id);
+        if trap_position == player.position {
+            chat.process_categorised_message("A trap springs!", MessageCategory::Combat);
+            let damage = EffectSystem::apply_to_player(Effect::Damage(damage), player);
+            chat.process_categorised_message(
+                &format!("You take {} damage.", damage),
+                MessageCategory::Combat,
+            );
+            EffectSystem::check_player_death(player, chat);
+        } else if let Some(monster_id) = monster_id {
+            chat.process_categorised_message("A trap springs!", MessageCategory::Combat);
+            EffectSystem::apply_status_effect(
+                Effect::Damage(damage),
+                EffectTarget::Monster(monster_id),
+                player,
+                monster_manager,
+                chat,
+            );
+        } else {
+            map.map[trap_position.y][trap_position.x] = Space::new(DEFAULT_TILE_SET.trap);
+            chat.process_categorised_message(
+                "You hear a trap spring somewhere nearby.",
+                MessageCategory::Warning,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tile_set::MONSTER_TILE_SET;
+    use crate::world_object::WorldObject;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn a_lever_toggles_every_door_wired_to_it() {
+        let mut map = MapData::from_rows(&["!.|", "#/#"]);
+        let lever = Vec2::new(0, 0);
+        let doors = [Vec2::new(2, 0), Vec2::new(1, 1)];
+        map.objects.insert(
+            lever,
+            WorldObject::new(WorldObjectKind::Lever { is_on: false }).with_targets(doors.to_vec()),
+        );
+        let mut monster_manager = MonsterManager::new();
+        let mut player = Player::new();
+        player.position = Vec2::new(1, 0);
+        let mut chat = Chat::new();
+        let mut rng = StdRng::seed_from_u64(0);
+        let tiles = |map: &MapData| doors.map(|door| map.map[door.y][door.x].tile);
+
+        let mut pull = |map: &mut MapData| {
+            InteractionSystem::interact(
+                map,
+                &mut player,
+                &mut monster_manager,
+                &mut chat,
+                lever,
+                &mut rng,
+            )
+        };
+        assert!(pull(&mut map));
+        // the door drawn open shuts as a top door, it sits in a wall running left to right
+        assert_eq!(tiles(&map), ['/', '-']);
+        assert!(!map.get_lock_mut(doors[0]).is_locked);
+        assert!(map.get_lock_mut(doors[1]).is_locked);
+        assert!(matches!(
+            map.objects[&lever].kind,
+            WorldObjectKind::Lever { is_on: true }
+        ));
+
+        assert!(pull(&mut map));
+        assert_eq!(tiles(&map), ['|', '/']);
+        assert!(matches!(
+            map.objects[&lever].kind,
+            WorldObjectKind::Lever { is_on: false }
+        ));
+    }
+
+    #[test]
+    fn a_pressure_plate_fires_its_targets_when_stepped_on() {
+        let mut map = MapData::from_rows(&["....|"]);
+        let plate = Vec2::new(1, 0);
+        let trap = Vec2::new(3, 0);
+        let door = Vec2::new(4, 0);
+        map.objects.insert(
+            plate,
+            WorldObject::new(WorldObjectKind::PressurePlate).with_targets(vec![trap, door]),
+        );
+        map.objects.insert(
+            trap,
+            WorldObject::new(WorldObjectKind::Trap {
+                damage: 4,
+                is_hidden: true,
+            }),
+        );
+        let mut monster_manager = MonsterManager::new();
+        let mut player = Player::new();
+        let mut chat = Chat::new();
+
+        // plain floor does nothing
+        InteractionSystem::step_on(
+            &mut map,
+            &mut player,
+            &mut monster_manager,
+            &mut chat,
+            Vec2::new(0, 0),
+        );
+        assert!(chat.messages.is_empty());
+
+        player.position = plate;
+        InteractionSystem::step_on(&mut map, &mut player, &mut monster_manager, &mut chat, plate);
+        assert_eq!(map.map[door.y][door.x].tile, DEFAULT_TILE_SET.open_door);
+        assert_eq!(map.map[trap.y][trap.x].tile, DEFAULT_TILE_SET.trap);
+        assert!(matches!(
+            map.objects[&trap].kind,
+            WorldObjectKind::Trap {
+                is_hidden: false,
+                ..
+            }
+        ));
+        assert_eq!(player.status.health, player.status.max_health);
+        let texts: Vec<String> = chat.messages.iter().map(|message| message.get_text()).collect();
+        assert_eq!(
+            texts,
+            [
+                "Something clicks beneath your feet.",
+                "You hear a trap spring somewhere nearby.",
+                "You hear a door grind open.",
+            ]
+        );
+    }
+
+    #[test]
+    fn a_trap_sprung_under_a_monster_hurts_it() {
+        let mut map = MapData::from_rows(&["#####", "#!..#", "#####"]);
+        let lever = Vec2::new(1, 1);
+        let trap = Vec2::new(3, 1);
+        map.objects.insert(
+            lever,
+            WorldObject::new(WorldObjectKind::Lever { is_on: false }).with_targets(vec![trap]),
+        );
+        map.objects.insert(
+            trap,
+            WorldObject::new(WorldObjectKind::Trap {
+                damage: 4,
+                is_hidden: true,
+            }),
+        );
+        let mut monster_manager = MonsterManager::new();
+        let monster_id = monster_manager.spawn_monster(&mut map, MONSTER_TILE_SET.goblin, trap);
+        let mut player = Player::new();
+        player.position = Vec2::new(2, 1);
+        let mut chat = Chat::new();
+
+        let mut rng = StdRng::seed_from_u64(0);
+        assert!(InteractionSystem::interact(
+            &mut map,
+            &mut player,
+            &mut monster_manager,
+            &mut chat,
+            lever,
+            &mut rng,
+        ));
+
+        let monster = monster_manager.get_monster(&monster_id).expect("the goblin");
+        assert_eq!(monster.status.health, monster.status.max_health - 4);
+        // the goblin is still drawn where it stands, the revealed trap waits underneath
+        assert!(map.map[trap.y][trap.x].is_monster);
+        assert_eq!(map.objects[&trap].get_tile(), DEFAULT_TILE_SET.trap);
+    }
+
+    #[test]
+    fn searching_from_the_corner_of_the_map() {
+        let mut map = MapData::from_rows(&["@.", ".."]);
+        let trap = Vec2::new(1, 1);
+        map.objects.insert(
+            trap,
+            WorldObject::new(WorldObjectKind::Trap {
+                damage: 4,
+                is_hidden: true,
+            }),
+        );
+        let player = Player::new();
+        let mut chat = Chat::new();
+
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..20 {
+            InteractionSystem::search(&mut map, &player, &mut chat, &mut rng);
+        }
+        assert_eq!(map.map[trap.y][trap.x].tile, DEFAULT_TILE_SET.trap);
+    }
+}
@@ -1,36 +1,110 @@
-#[derive(Clone)]
+use crate::item::{Item, ItemKind};
+use crate::lock::{is_master_key, Lock};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Inventory {
-    pub(crate) keys: i32,
+    pub(crate) keys: Vec<String>,
+    pub(crate) items: Vec<Item>,
+    pub(crate) selected_index: usize,
+    pub is_open: bool,
 }
 
 impl Inventory {
     pub(crate) fn new() -> Self {
-        Inventory { keys: 0 }
+        Inventory {
+            keys: Vec::new(),
+            items: Vec::new(),
+            selected_index: 0,
+            is_open: false,
+        }
+    }
+
+    pub(crate) fn add_item(&mut self, item: Item) {
+        self.items.push(item);
+    }
+
+    pub(crate) fn take_item(&mut self, index: usize) -> Option<Item> {
+        if index < self.items.len() {
+            let item = self.items.remove(index);
+            // keep the selection cursor on a valid entry
+            if self.selected_index >= self.items.len() && self.selected_index > 0 {
+                self.selected_index = self.items.len().saturating_sub(1);
+            }
+            Some(item)
+        } else {
+            None
+        }
     }
 
-    pub(crate) fn add_key(&mut self, amount: i32) {
-        self.keys += amount;
+    pub(crate) fn first_index_of(&self, kind: &ItemKind) -> Option<usize> {
+        self.items
+            .iter()
+            .position(|item| std::mem::discriminant(&item.kind) == std::mem::discriminant(kind))
     }
 
-    pub(crate) fn remove_key(&mut self, amount: i32) {
-        self.keys -= amount;
+    pub(crate) fn select_previous(&mut self) {
+        if self.selected_index > 0 {
+            self.selected_index -= 1;
+        }
     }
 
-    fn get_inventory(&mut self) -> [String; 1] {
-        [format!("Keys: {}", self.keys)]
+    pub(crate) fn select_next(&mut self) {
+        if self.selected_index + 1 < self.items.len() {
+            self.selected_index += 1;
+        }
     }
 
-    pub(crate) fn get_inventory_to_size(
-        &mut self,
-        _size: usize,
-        module_part: String,
-    ) -> [String; 3] {
-        let inventory_items = [
-            format!("{}", module_part),
-            format!("Keys: {}", self.keys),
-            String::new(),
-        ];
+    pub fn get_selection_menu(&self) -> Vec<String> {
+        let mut menu = vec!["up/down select, enter use, i close".to_string()];
+
+        if self.items.is_empty() {
+            menu.push("  (empty)".to_string());
+        }
+
+        for (index, item) in self.items.iter().enumerate() {
+            let cursor = if index == self.selected_index {
+                ">"
+            } else {
+                " "
+            };
+            menu.push(format!("{} {}", cursor, item.name));
+        }
+
+        menu
+    }
+
+    pub(crate) fn first_equippable_index(&self) -> Option<usize> {
+        self.items
+            .iter()
+            .position(|item| item.equipment_slot().is_some())
+    }
+
+    pub(crate) fn add_key(&mut self, key_id: &str) {
+        self.keys.push(key_id.to_string());
+    }
+
+    // prefers a matching key over a master key so master keys are kept for later
+    pub(crate) fn find_key_for(&self, lock: &Lock) -> Option<usize> {
+        self.keys
+            .iter()
+            .position(|key_id| *key_id == lock.id)
+            .or_else(|| self.keys.iter().position(|key_id| lock.is_opened_by(key_id)))
+    }
+
+    // uses up the key at the index, master keys are never used up
+    pub(crate) fn use_key(&mut self, index: usize) -> String {
+        let key_id = self.keys[index].clone();
+        if !is_master_key(&key_id) {
+            self.keys.remove(index);
+        }
+        key_id
+    }
 
-        inventory_items
+    pub fn get_inventory(&self) -> Vec<String> {
+        vec![
+            format!("Keys: {}", self.keys.len()),
+            format!("Items: {}", self.items.len()),
+        ]
     }
 }
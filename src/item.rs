@@ -0,0 +1,187 @@
+use crate::effect::Effect;
+use crate::equipment::EquipmentSlot;
+use serde::{Deserialize, Serialize};
+
+// every name from_name understands
+pub const ITEM_NAMES: &[&str] = &[
+    "rusty_dagger",
+    "great_axe",
+    "leather_armor",
+    "wooden_shield",
+    "ring_of_might",
+    "healing_potion",
+    "scroll_of_mapping",
+    "scroll_of_teleport",
+    "scroll_of_fire_bolt",
+    "ration",
+    "lockpick",
+];
+
+#[derive(Copy, Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+pub struct StatModifiers {
+    pub str: i32,
+    pub def: i32,
+}
+
+impl StatModifiers {
+    pub(crate) fn new(str: i32, def: i32) -> Self {
+        StatModifiers { str, def }
+    }
+
+    pub(crate) fn combine(self, other: StatModifiers) -> Self {
+        StatModifiers {
+            str: self.str + other.str,
+            def: self.def + other.def,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum ItemKind {
+    Weapon { two_handed: bool },
+    Armor,
+    Shield,
+    Ring,
+    Potion,
+    Scroll,
+    Food,
+    Lockpick,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Item {
+    pub name: String,
+    pub kind: ItemKind,
+    pub modifiers: StatModifiers,
+    pub effect: Option<Effect>,
+}
+
+impl Item {
+    pub(crate) fn new(name: &str, kind: ItemKind, modifiers: StatModifiers) -> Self {
+        Item {
+            name: name.to_string(),
+            kind,
+            modifiers,
+            effect: None,
+        }
+    }
+
+    pub(crate) fn new_consumable(name: &str, kind: ItemKind, effect: Effect) -> Self {
+        Item {
+            name: name.to_string(),
+            kind,
+            modifiers: StatModifiers::default(),
+            effect: Some(effect),
+        }
+    }
+
+    pub(crate) fn rusty_dagger() -> Self {
+        Item::new(
+            "Rusty Dagger",
+            ItemKind::Weapon { two_handed: false },
+            StatModifiers::new(2, 0),
+        )
+    }
+
+    pub(crate) fn great_axe() -> Self {
+        Item::new(
+            "Great Axe",
+            ItemKind::Weapon { two_handed: true },
+            StatModifiers::new(5, 0),
+        )
+    }
+
+    pub(crate) fn leather_armor() -> Self {
+        Item::new("Leather Armor", ItemKind::Armor, StatModifiers::new(0, 2))
+    }
+
+    pub(crate) fn wooden_shield() -> Self {
+        Item::new("Wooden Shield", ItemKind::Shield, StatModifiers::new(0, 1))
+    }
+
+    pub(crate) fn ring_of_might() -> Self {
+        Item::new("Ring of Might", ItemKind::Ring, StatModifiers::new(1, 0))
+    }
+
+    pub(crate) fn healing_potion() -> Self {
+        Item::new_consumable("Healing Potion", ItemKind::Potion, Effect::Heal(25))
+    }
+
+    pub(crate) fn scroll_of_mapping() -> Self {
+        Item::new_consumable("Scroll of Mapping", ItemKind::Scroll, Effect::RevealMap)
+    }
+
+    pub(crate) fn scroll_of_teleport() -> Self {
+        Item::new_consumable("Scroll of Teleport", ItemKind::Scroll, Effect::Teleport)
+    }
+
+    pub(crate) fn scroll_of_fire_bolt() -> Self {
+        Item::new_consumable(
+            "Scroll of Fire Bolt",
+            ItemKind::Scroll,
+            Effect::FireBolt {
+                damage: 8,
+                range: 6,
+            },
+        )
+    }
+
+    pub(crate) fn ration() -> Self {
+        Item::new_consumable("Ration", ItemKind::Food, Effect::Heal(5))
+    }
+
+    pub(crate) fn lockpick() -> Self {
+        Item::new("Lockpick", ItemKind::Lockpick, StatModifiers::default())
+    }
+
+    // looks up an item by the snake_case name used in map metadata
+    pub(crate) fn from_name(name: &str) -> Option<Item> {
+        match name {
+            "rusty_dagger" => Some(Item::rusty_dagger()),
+            "great_axe" => Some(Item::great_axe()),
+            "leather_armor" => Some(Item::leather_armor()),
+            "wooden_shield" => Some(Item::wooden_shield()),
+            "ring_of_might" => Some(Item::ring_of_might()),
+            "healing_potion" => Some(Item::healing_potion()),
+            "scroll_of_mapping" => Some(Item::scroll_of_mapping()),
+            "scroll_of_teleport" => Some(Item::scroll_of_teleport()),
+            "scroll_of_fire_bolt" => Some(Item::scroll_of_fire_bolt()),
+            "ration" => Some(Item::ration()),
+            "lockpick" => Some(Item::lockpick()),
+            _ => None,
+        }
+    }
+
+    // the reverse of from_name, none for an item no name builds
+    pub(crate) fn get_metadata_name(&self) -> Option<&'static str> {
+        ITEM_NAMES
+            .iter()
+            .copied()
+            .find(|name| Item::from_name(name).is_some_and(|item| item == *self))
+    }
+
+    // the slot this item goes into when worn, none if it can't be worn at all
+    pub(crate) fn equipment_slot(&self) -> Option<EquipmentSlot> {
+        match self.kind {
+            ItemKind::Weapon { .. } => Some(EquipmentSlot::Weapon),
+            ItemKind::Armor => Some(EquipmentSlot::Armor),
+            ItemKind::Shield => Some(EquipmentSlot::Shield),
+            ItemKind::Ring => Some(EquipmentSlot::Ring),
+            ItemKind::Potion | ItemKind::Scroll | ItemKind::Food | ItemKind::Lockpick => None,
+        }
+    }
+
+    // the verb shown in chat when the item is used
+    pub(crate) fn use_verb(&self) -> &'static str {
+        match self.kind {
+            ItemKind::Potion => "drink",
+            ItemKind::Scroll => "read",
+            ItemKind::Food => "eat",
+            _ => "use",
+        }
+    }
+
+    pub(crate) fn is_two_handed(&self) -> bool {
+        matches!(self.kind, ItemKind::Weapon { two_handed: true })
+    }
+}
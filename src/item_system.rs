@@ -0,0 +1,122 @@
+use crate::chat::Chat;
+use crate::effect::{EffectSystem, EffectTarget};
+use crate::action::Action;
+use crate::item::ItemKind;
+use crate::map_manager::MapManager;
+use crate::monster_manager::MonsterManager;
+use crate::player::Player;
+use rand::Rng;
+
+#[derive(Clone)]
+pub struct ItemSystem {}
+
+impl ItemSystem {
+    pub(crate) fn new() -> Self {
+        ItemSystem {}
+    }
+
+    // handles the inventory menu and the quick use keys, returns true when the key press was consumed
+    pub(crate) fn process_item_input(
+        &mut self,
+        player: &mut Player,
+        map_manager: &mut MapManager,
+        monster_manager: &mut MonsterManager,
+        chat: &mut Chat,
+        rng: &mut impl Rng,
+    ) -> bool {
+        if player.inventory.is_open {
+            match player.action {
+                Some(Action::MoveUp) => player.inventory.select_previous(),
+                Some(Action::MoveDown) => player.inventory.select_next(),
+                Some(Action::Use) => {
+                    let index = player.inventory.selected_index;
+                    self.use_item(index, player, map_manager, monster_manager, chat, rng);
+                }
+                Some(Action::Inventory | Action::Quit) => player.inventory.is_open = false,
+                _ => {}
+            }
+            // the menu swallows every key while it is open
+            return true;
+        }
+
+        let quick_use_kind = match player.action {
+            // using something means choosing it from the menu first
+            Some(Action::Inventory | Action::Use) => {
+                player.inventory.is_open = true;
+                player.inventory.selected_index = 0;
+                return true;
+            }
+            Some(Action::Drink) => ItemKind::Potion,
+            Some(Action::Read) => ItemKind::Scroll,
+            Some(Action::Eat) => ItemKind::Food,
+            _ => return false,
+        };
+
+        match player.inventory.first_index_of(&quick_use_kind) {
+            Some(index) => {
+                self.use_item(index, player, map_manager, monster_manager, chat, rng)
+            }
+            None => {
+                let message = match quick_use_kind {
+                    ItemKind::Potion => "You have no potions to drink.",
+                    ItemKind::Scroll => "You have no scrolls to read.",
+                    _ => "You have nothing to eat.",
+                };
+                chat.process_chat_message(message);
+            }
+        }
+        true
+    }
+
+    pub(crate) fn use_item(
+        &mut self,
+        index: usize,
+        player: &mut Player,
+        map_manager: &mut MapManager,
+        monster_manager: &mut MonsterManager,
+        chat: &mut Chat,
+        rng: &mut impl Rng,
+    ) {
+        let item = match player.inventory.items.get(index) {
+            Some(item) => item.clone(),
+            None => return,
+        };
+
+        if item.equipment_slot().is_some() {
+            let message = match player.equip_item(index) {
+                Ok(message) => message,
+                Err(error) => error.to_string(),
+            };
+            chat.process_chat_message(&message);
+            return;
+        }
+
+        let effect = match item.effect {
+            Some(effect) => effect,
+            None => {
+                chat.process_chat_message(&format!("You can't use the {}.", item.name));
+                return;
+            }
+        };
+
+        chat.process_chat_message(&format!(
+            "You {} the {}.",
+            item.use_verb(),
+            item.name
+        ));
+
+        let was_applied = EffectSystem::apply_effect(
+            effect,
+            EffectTarget::Player,
+            player,
+            map_manager,
+            monster_manager,
+            chat,
+            rng,
+        );
+
+        if was_applied {
+            player.inventory.take_item(index);
+        }
+    }
+}
@@ -0,0 +1,67 @@
+// the game itself, everything a frontend, a bot or a test needs to run it without a terminal
+pub mod action;
+pub mod chat;
+pub mod command;
+pub mod equipment;
+pub mod game;
+pub mod inventory;
+pub mod item;
+pub mod lock;
+pub mod map_data;
+pub mod map_editor;
+pub mod map_loader;
+pub mod monster;
+pub mod pathfinding;
+pub mod player;
+pub mod replay;
+pub mod save;
+pub mod scheduler;
+pub mod space;
+pub mod status;
+pub mod tile_set;
+pub mod vec2;
+pub mod world_object;
+
+// the terminal frontend, it only reads the game and draws into whatever render target it is given
+pub mod console;
+pub mod game_client;
+pub mod input;
+pub mod load_screen;
+pub mod renderer;
+
+mod battle_system;
+mod binding_screen;
+mod camera;
+mod collision_engine;
+mod command_system;
+mod effect;
+mod hud;
+mod interaction_system;
+mod item_system;
+mod log_viewer;
+mod loot;
+mod map_factory;
+mod map_manager;
+mod map_metadata;
+mod minimap;
+mod monster_generator;
+mod monster_manager;
+mod palette;
+mod player_movement_data;
+mod terrain_data;
+mod tiled;
+
+pub type Map = Vec<Vec<Space>>;
+
+use crate::space::Space;
+use crate::vec2::Vec2;
+
+enum MovementType {
+    Unable,
+    Normal,
+    LadderUp,
+    LadderDown,
+    LadderEnter,
+    LadderExit,
+    Battle,
+}
@@ -0,0 +1,59 @@
+use crossterm::event::KeyCode;
+
+// offered at startup when there are saved games, the first entry always starts a new one
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct LoadScreen {
+    pub slots: Vec<String>,
+    pub selected_index: usize,
+    // why the last load failed, shown until another choice is made
+    pub error: Option<String>,
+}
+
+pub enum LoadScreenAction {
+    None,
+    NewGame,
+    Load(String),
+    Quit,
+}
+
+impl LoadScreen {
+    pub fn new(slots: Vec<String>) -> Self {
+        LoadScreen {
+            slots,
+            ..LoadScreen::default()
+        }
+    }
+
+    pub fn handle_key(&mut self, key_code: KeyCode) -> LoadScreenAction {
+        match key_code {
+            KeyCode::Up => self.selected_index = self.selected_index.saturating_sub(1),
+            KeyCode::Down => self.selected_index = (self.selected_index + 1).min(self.slots.len()),
+            KeyCode::Enter => {
+                self.error = None;
+                return match self.selected_index {
+                    0 => LoadScreenAction::NewGame,
+                    index => LoadScreenAction::Load(self.slots[index - 1].clone()),
+                };
+            }
+            KeyCode::Esc => return LoadScreenAction::Quit,
+            _ => {}
+        }
+        LoadScreenAction::None
+    }
+
+    pub(crate) fn get_lines(&self) -> Vec<String> {
+        let entries = std::iter::once("New game".to_string())
+            .chain(self.slots.iter().map(|slot| format!("Load {}", slot)));
+        entries
+            .enumerate()
+            .map(|(index, entry)| {
+                let cursor = if index == self.selected_index {
+                    ">"
+                } else {
+                    " "
+                };
+                format!("{} {}", cursor, entry)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+pub const RUSTY_LOCK_ID: &str = "rusty";
+pub const MASTER_KEY_ID: &str = "master";
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Lock {
+    pub id: String,
+    pub is_locked: bool,
+    pub relocks: bool,
+    // chance between 0 and 1 that a single lockpick attempt succeeds
+    pub pick_chance: f64,
+    // the closed door glyph to put back when the door locks again
+    pub closed_tile: char,
+}
+
+impl Lock {
+    pub(crate) fn new(id: &str, closed_tile: char) -> Self {
+        Lock {
+            id: id.to_string(),
+            is_locked: true,
+            relocks: false,
+            pick_chance: 0.5,
+            closed_tile,
+        }
+    }
+
+    // doors without any metadata behave like they always have and take any rusty key
+    pub(crate) fn rusty(closed_tile: char) -> Self {
+        Lock::new(RUSTY_LOCK_ID, closed_tile)
+    }
+
+    pub(crate) fn is_opened_by(&self, key_id: &str) -> bool {
+        key_id == self.id || key_id == MASTER_KEY_ID
+    }
+
+    pub(crate) fn can_be_picked(&self) -> bool {
+        self.pick_chance > 0.0
+    }
+}
+
+pub(crate) fn is_master_key(key_id: &str) -> bool {
+    key_id == MASTER_KEY_ID
+}
@@ -0,0 +1,59 @@
+use crate::chat::{Chat, Message, MessageCategory};
+use std::collections::HashSet;
+
+// full-screen history, scroll counts lines back from the newest message
+#[derive(Clone, PartialEq, Debug)]
+pub struct LogViewer {
+    pub scroll: usize,
+    pub hidden_categories: HashSet<MessageCategory>,
+}
+
+impl LogViewer {
+    // debug output is noisy, it has to be switched on explicitly
+    pub(crate) fn new() -> Self {
+        LogViewer {
+            scroll: 0,
+            hidden_categories: HashSet::from([MessageCategory::Debug]),
+        }
+    }
+
+    pub(crate) fn scroll(&mut self, delta: isize) {
+        self.scroll = self.scroll.saturating_add_signed(delta);
+    }
+
+    pub(crate) fn toggle_category(&mut self, category: MessageCategory) {
+        if !self.hidden_categories.remove(&category) {
+            self.hidden_categories.insert(category);
+        }
+        self.scroll = 0;
+    }
+
+    pub(crate) fn get_filter_line(&self) -> String {
+        MessageCategory::ALL
+            .iter()
+            .enumerate()
+            .map(|(index, category)| {
+                let mark = if self.hidden_categories.contains(category) {
+                    ' '
+                } else {
+                    'x'
+                };
+                format!("{}:[{}] {}", index + 1, mark, category.name())
+            })
+            .collect::<Vec<String>>()
+            .join("  ")
+    }
+
+    // the window of messages that fits the height, scrolling stops at the oldest one
+    pub(crate) fn get_lines<'a>(&mut self, chat: &'a Chat, height: usize) -> Vec<&'a Message> {
+        let messages: Vec<&Message> = chat
+            .messages
+            .iter()
+            .filter(|message| !self.hidden_categories.contains(&message.category))
+            .collect();
+
+        self.scroll = self.scroll.min(messages.len().saturating_sub(height));
+        let end = messages.len() - self.scroll;
+        messages[end.saturating_sub(height)..end].to_vec()
+    }
+}
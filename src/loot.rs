@@ -0,0 +1,199 @@
+use crate::item::Item;
+use crate::tile_set::MONSTER_TILE_SET;
+use rand::Rng;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct LootEntry {
+    pub item_name: &'static str,
+    pub weight: u32,
+    // extra weight added per level of depth, lets better items show up more often further down
+    pub weight_per_depth: u32,
+    pub min_depth: usize,
+}
+
+impl LootEntry {
+    const fn new(
+        item_name: &'static str,
+        weight: u32,
+        weight_per_depth: u32,
+        min_depth: usize,
+    ) -> Self {
+        LootEntry {
+            item_name,
+            weight,
+            weight_per_depth,
+            min_depth,
+        }
+    }
+
+    fn get_weight(&self, depth: usize) -> u32 {
+        if depth < self.min_depth {
+            return 0;
+        }
+        self.weight + self.weight_per_depth * depth as u32
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct LootTable {
+    pub entries: Vec<LootEntry>,
+    pub rolls: usize,
+    pub drop_chance: f64,
+}
+
+impl LootTable {
+    pub(crate) fn new(entries: Vec<LootEntry>, rolls: usize, drop_chance: f64) -> Self {
+        LootTable {
+            entries,
+            rolls,
+            drop_chance,
+        }
+    }
+
+    pub(crate) fn for_species(monster_tile: char) -> LootTable {
+        if monster_tile == MONSTER_TILE_SET.goblin {
+            LootTable::new(
+                vec![
+                    LootEntry::new("healing_potion", 30, 0, 0),
+                    LootEntry::new("ration", 25, 0, 0),
+                    LootEntry::new("lockpick", 15, 0, 0),
+                    LootEntry::new("rusty_dagger", 10, 0, 0),
+                    LootEntry::new("wooden_shield", 8, 1, 0),
+                    LootEntry::new("great_axe", 2, 2, 2),
+                ],
+                1,
+                0.6,
+            )
+        } else if monster_tile == MONSTER_TILE_SET.snake {
+            LootTable::new(
+                vec![
+                    LootEntry::new("ration", 20, 0, 0),
+                    LootEntry::new("healing_potion", 10, 1, 0),
+                ],
+                1,
+                0.3,
+            )
+        } else {
+            LootTable::new(Vec::new(), 0, 0.0)
+        }
+    }
+
+    // tables chests can refer to from map metadata with loot:<name>
+    pub(crate) fn for_chest(table_name: &str) -> Option<LootTable> {
+        match table_name {
+            "common_chest" => Some(LootTable::new(
+                vec![
+                    LootEntry::new("healing_potion", 30, 0, 0),
+                    LootEntry::new("ration", 20, 0, 0),
+                    LootEntry::new("scroll_of_mapping", 10, 1, 0),
+                    LootEntry::new("lockpick", 10, 0, 0),
+                    LootEntry::new("leather_armor", 5, 1, 0),
+                ],
+                2,
+                1.0,
+            )),
+            "rare_chest" => Some(LootTable::new(
+                vec![
+                    LootEntry::new("scroll_of_fire_bolt", 20, 1, 0),
+                    LootEntry::new("scroll_of_teleport", 20, 0, 0),
+                    LootEntry::new("ring_of_might", 10, 2, 0),
+                    LootEntry::new("great_axe", 5, 3, 1),
+                ],
+                2,
+                1.0,
+            )),
+            _ => None,
+        }
+    }
+
+    // deeper levels get an extra roll every third floor on top of the weight scaling
+    pub(crate) fn roll(&self, depth: usize, rng: &mut impl Rng) -> Vec<Item> {
+        let mut items = Vec::new();
+
+        if self.rolls == 0 || rng.gen::<f64>() >= self.drop_chance {
+            return items;
+        }
+
+        let total_weight: u32 = self
+            .entries
+            .iter()
+            .map(|entry| entry.get_weight(depth))
+            .sum();
+        if total_weight == 0 {
+            return items;
+        }
+
+        for _ in 0..self.rolls + depth / 3 {
+            let mut pick = rng.gen_range(0..total_weight);
+            for entry in &self.entries {
+                let weight = entry.get_weight(depth);
+                if pick < weight {
+                    if let Some(item) = Item::from_name(entry.item_name) {
+                        items.push(item);
+                    }
+                    break;
+                }
+                pick -= weight;
+            }
+        }
+
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn count_drops(table: &LootTable, depth: usize, item_name: &str) -> usize {
+        let mut rng = StdRng::seed_from_u64(7);
+        (0..4000)
+            .flat_map(|_| table.roll(depth, &mut rng))
+            .filter(|item| item.get_metadata_name() == Some(item_name))
+            .count()
+    }
+
+    #[test]
+    fn weights_grow_with_depth_from_the_minimum_depth() {
+        let entry = LootEntry::new("great_axe", 2, 3, 2);
+        assert_eq!(entry.get_weight(0), 0);
+        assert_eq!(entry.get_weight(1), 0);
+        assert_eq!(entry.get_weight(2), 8);
+        assert_eq!(entry.get_weight(5), 17);
+    }
+
+    #[test]
+    fn items_drop_in_proportion_to_their_weight() {
+        let table = LootTable::new(
+            vec![
+                LootEntry::new("ration", 3, 0, 0),
+                LootEntry::new("lockpick", 1, 0, 0),
+                LootEntry::new("great_axe", 100, 0, 1),
+            ],
+            1,
+            1.0,
+        );
+
+        let rations = count_drops(&table, 0, "ration");
+        let lockpicks = count_drops(&table, 0, "lockpick");
+        assert_eq!(rations + lockpicks, 4000);
+        assert!((2850..=3150).contains(&rations), "{} rations", rations);
+        assert_eq!(count_drops(&table, 0, "great_axe"), 0);
+        assert!(count_drops(&table, 1, "great_axe") > 3500);
+    }
+
+    #[test]
+    fn deeper_floors_roll_more_often() {
+        let table = LootTable::new(vec![LootEntry::new("ration", 1, 0, 0)], 1, 1.0);
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(table.roll(2, &mut rng).len(), 1);
+        assert_eq!(table.roll(3, &mut rng).len(), 2);
+        assert_eq!(table.roll(6, &mut rng).len(), 3);
+
+        let never = LootTable::new(vec![LootEntry::new("ration", 1, 0, 0)], 1, 0.0);
+        assert!(never.roll(6, &mut rng).is_empty());
+        assert!(LootTable::for_species('?').roll(6, &mut rng).is_empty());
+    }
+}
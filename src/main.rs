@@ -1,319 +1,439 @@
-mod chat;
-mod collision_engine;
-mod game_client;
-mod inventory;
-mod map_data;
-mod map_factory;
-mod map_manager;
-mod monster;
-mod monster_generator;
-mod monster_manager;
-mod pathfinding;
-mod player;
-mod player_movement_data;
-mod space;
-mod status;
-mod terrain_data;
-mod tile_set;
-mod vec2;
-mod battle_system;
-
-type Map = Vec<Vec<Space>>;
-
-use crate::game_client::GameClient;
-use crate::space::Space;
-use std::io::stdout;
-
-use crossterm::event::{Event, KeyCode, KeyEventKind};
-use crossterm::{event, terminal, QueueableCommand};
-use futures::lock::Mutex;
-use futures::TryFutureExt;
-use std::sync::Arc;
-use std::time::Duration;
-use vec2::Vec2;
-use crate::battle_system::BattleSystem;
-
-use crate::chat::Chat;
-use crate::collision_engine::CollisionEngine;
-
-use crate::map_factory::MapFactory;
-use crate::map_manager::MapManager;
-use crate::monster_generator::MonsterFactory;
-use crate::monster_manager::MonsterManager;
-use crate::player::Player;
-use crate::tile_set::{DEFAULT_TILE_SET, LADDER_TILE_SET};
-
-enum MovementType {
-    Unable,
-    Normal,
-    LadderUp,
-    LadderDown,
-    LadderEnter,
-    LadderExit,
-    Battle,
-}
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::event;
 
-#[tokio::main]
-async fn main() {
-    let mut player = Arc::new(Mutex::new(Player::new()));
-    let player_clone = Arc::clone(&player);
-    let mut player_guard = player_clone.lock().await;
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::Duration;
 
-    let mut map_factory = MapFactory::new();
-    let monster_factory = MonsterFactory::new();
+use project_aether::action::Action;
+use project_aether::chat::MessageCategory;
+use project_aether::console::ConsoleAction;
+use project_aether::game::{Game, GameConfig, GameEvent};
+use project_aether::game_client::GameClient;
+use project_aether::input::KeyBindings;
+use project_aether::load_screen::{LoadScreen, LoadScreenAction};
+use project_aether::map_editor::EditorInput;
+use project_aether::map_loader::{self, Severity};
+use project_aether::replay::{Replay, ReplayWriter};
+use project_aether::save;
+
+mod cli;
+use cli::{Command, ReplayOption, HELP, USAGE};
+
+// how long each event stays on screen when a replay plays at speed
+const REPLAY_EVENT_DELAY: Duration = Duration::from_millis(80);
+
+fn main() -> ExitCode {
+    let options = match cli::parse_arguments(std::env::args().skip(1)) {
+        Ok(Command::Play(options)) => options,
+        Ok(Command::Help) => {
+            print!("{}", HELP);
+            return ExitCode::SUCCESS;
+        }
+        Ok(Command::ValidateMaps(directory)) => return validate_maps(&directory),
+        Err(message) => {
+            eprintln!("{}\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    let replay_option = &options.replay_option;
+
+    // verifying needs no terminal, it only reports whether the run still plays out the same
+    let replay = match replay_option {
+        ReplayOption::Play { path, .. } | ReplayOption::Verify(path) => match Replay::read(path) {
+            Ok(replay) => Some(replay),
+            Err(error) => {
+                eprintln!("{}", error);
+                return ExitCode::FAILURE;
+            }
+        },
+        _ => None,
+    };
+    if let (ReplayOption::Verify(_), Some(replay)) = (replay_option, &replay) {
+        return match replay.verify() {
+            Ok(game) => {
+                println!(
+                    "Replay verified, {} events up to turn {}.",
+                    replay.entries.len(),
+                    game.turn()
+                );
+                ExitCode::SUCCESS
+            }
+            Err(error) => {
+                eprintln!("{}", error);
+                ExitCode::FAILURE
+            }
+        };
+    }
 
-    let terminal = Arc::new(Mutex::new(GameClient::new()));
-    let terminal_clone = Arc::clone(&terminal);
-    let mut terminal_guard = terminal_clone.lock().await;
+    // anything that can keep the game from starting is reported before the terminal is taken over
+    let config = &options.config;
+    let game = match (&options.load_slot, &replay) {
+        (Some(slot), _) => Game::load(slot, config.clone()).map_err(|error| error.to_string()),
+        (None, Some(replay)) => Game::new(replay.get_config()).map_err(|error| error.to_string()),
+        (None, None) => Game::new(config.clone()).map_err(|error| error.to_string()),
+    };
+    let game = match game {
+        Ok(game) => game,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut recorder = match replay_option {
+        ReplayOption::Record(path) => match ReplayWriter::create(path, config) {
+            Ok(recorder) => Some(recorder),
+            Err(error) => {
+                eprintln!("Replay {} could not be written: {}", path.display(), error);
+                return ExitCode::FAILURE;
+            }
+        },
+        _ => None,
+    };
+
+    let (key_bindings, binding_errors) = KeyBindings::load(&options.key_bindings_path);
+    let mut terminal = GameClient::new(key_bindings).with_dead_zone(options.dead_zone);
+
+    if let Err(error) = terminal.start_session() {
+        // raw mode may already be on when switching screens is what failed
+        let _ = terminal.end_session();
+        eprintln!("The terminal could not be prepared: {}", error);
+        return ExitCode::FAILURE;
+    }
+    // a recording or a replay has to start from a new game, saves only come into it by command
+    let is_game_chosen = options.load_slot.is_some()
+        || options.has_new_game_options
+        || replay.is_some()
+        || recorder.is_some();
+    let game = if is_game_chosen {
+        Some(game)
+    } else {
+        choose_game(&mut terminal, game, config)
+    };
+    let mut game = match game {
+        Some(game) => game,
+        None => {
+            let _ = terminal.end_session();
+            return ExitCode::SUCCESS;
+        }
+    };
 
-    let monster_manager = Arc::new(Mutex::new(MonsterManager::new()));
-    let monster_manager_clone = Arc::clone(&monster_manager);
-    let mut monster_manager_guard = monster_manager_clone.lock().await;
+    for error in binding_errors {
+        game.add_message(&error.to_string(), MessageCategory::Warning);
+    }
+    terminal.print_terminal(&game);
+
+    if let (
+        ReplayOption::Play {
+            path,
+            is_step_by_step,
+        },
+        Some(replay),
+    ) = (&replay_option, &replay)
+    {
+        let has_quit = play_replay(&mut terminal, &mut game, replay, *is_step_by_step);
+        if has_quit {
+            let _ = terminal.end_session();
+            return ExitCode::SUCCESS;
+        }
+        game.add_message(
+            &format!("Finished playing {}, the game is yours.", path.display()),
+            MessageCategory::General,
+        );
+        terminal.print_terminal(&game);
+    }
 
-    let mut map_manager = Arc::new(Mutex::new(MapManager::new()));
-    let map_manager_clone = Arc::clone(&map_manager);
-    let mut map_manager_guard = map_manager_clone.lock().await;
+    loop {
+        // real time lets the world move on while waiting for a key, turn-based waits as long as it takes
+        let has_event = match game.get_wait_time() {
+            Some(wait_time) => event::poll(wait_time).unwrap(),
+            None => true,
+        };
+
+        if game.is_tick_due() {
+            step(&mut game, &mut recorder, GameEvent::Tick);
+            terminal.print_terminal(&game);
+        }
 
-    map_manager_guard.add_map_set_player_position(
-        &mut player_guard,
-        "scene_ladder",
-        Vec2::new(3, 2),
-    );
-    map_manager_guard.add_map_set_player_position(&mut player_guard, "map1", Vec2::new(5, 2));
-    let new_map =
-        map_factory.generate_map(&mut player_guard, 20, 20, Vec2::new(2, 1), "seedphrase");
-    map_manager_guard.add_generated_map(new_map);
-    map_manager_guard.add_map_set_player_position(&mut player_guard, "test_map", Vec2::new(10, 10));
-    //map_manager_guard.add_map_set_player_position(&mut player_guard, "map3", Vec2::new(4, 5));
-    map_manager_guard.add_map_set_player_position(&mut player_guard, "map2", Vec2::new(6, 2));
-    map_manager_guard.load_map("map2", MovementType::Normal);
-
-    let collision_engine = Arc::new(Mutex::new(CollisionEngine::new()));
-    let collision_engine_clone = Arc::clone(&collision_engine);
-    let mut collision_engine_guard = collision_engine_clone.lock().await;
-
-    let chat = Arc::new(Mutex::new(Chat::new()));
-    let mut chat_clone = Arc::clone(&chat);
-
-    monster_manager_guard
-        .spawn_monsters(&mut map_manager_guard, monster_factory);
-
-    collision_engine_guard
-        .update_player_vision(&mut map_manager_guard, &mut player_guard, Vec2::ZERO)
-        .await;
-
-    let mut stdout = stdout();
-    stdout
-        .queue(terminal::Clear(terminal::ClearType::All))
-        .unwrap();
-
-    terminal_guard
-        .print_terminal(&player_guard, &mut map_manager_guard, &mut chat_clone)
-        .await;
-
-    drop(terminal_guard);
-    drop(map_manager_guard);
-    drop(collision_engine_guard);
-    drop(player_guard);
-    drop(monster_manager_guard);
-
-    tokio::spawn({
-        async move {
-            let mut chat_clone = Arc::clone(&chat);
-            let collision_engine_clone = Arc::clone(&collision_engine);
-            let monster_manager_clone = Arc::clone(&monster_manager);
-            let player_clone = Arc::clone(&player);
-            let mut map_manager_guard = Arc::clone(&map_manager);
-            let terminal_clone = Arc::clone(&terminal);
-
-            update_monsters_async(
-                collision_engine_clone,
-                &mut map_manager_guard,
-                player_clone,
-                monster_manager_clone,
-                &mut chat_clone,
-                terminal_clone,
-            )
-            .await;
+        if !has_event {
+            continue;
         }
-    });
 
-    loop {
         match event::read().unwrap() {
             Event::Key(key_input) => {
                 if key_input.kind == KeyEventKind::Press {
-                    let mut player_guard = player_clone.lock().await;
-
-                    match player_guard.key_event {
-                        KeyCode::Esc => {
-                            break;
+                    let action = terminal.get_action(&key_input);
+
+                    // the bindings screen reads raw keys, so a broken binding can always be fixed there
+                    let is_binding_screen_key =
+                        action == Some(Action::KeyBindings) || terminal.is_binding_screen_open();
+                    if is_binding_screen_key && !terminal.is_console_open() {
+                        if !terminal.is_binding_screen_open() {
+                            terminal.open_binding_screen();
+                        } else if let Some(message) = terminal.handle_binding_screen_key(&key_input) {
+                            game.add_message(&message, MessageCategory::General);
                         }
-                        _ => {}
+                        terminal.print_terminal(&game);
+                        continue;
                     }
 
-                    player_guard.key_event = key_input.code;
-                    let mut terminal_guard = terminal_clone.lock().await;
-                    let mut map_manager_guard = map_manager_clone.lock().await;
-                    let mut collision_engine_guard = collision_engine_clone.lock().await;
-                    let mut monster_manager_guard = monster_manager_clone.lock().await;
-
-                    //let mut chat_guard = chat_clone.lock().await;
-
-                    let new_player_pos = collision_engine_guard
-                        .try_move_player(&mut player_guard, &mut chat_clone)
-                        .await;
-
-                    let player_move_type = collision_engine_guard
-                        .try_process_move(
-                            &mut map_manager_guard,
-                            &mut player_guard,
-                            &mut chat_clone,
-                            new_player_pos,
-                        )
-                        .await;
-                    player_guard.previous_tile_below_player = player_guard.tile_below_player;
-
-                    match player_move_type {
-                        MovementType::Normal => {
-                            collision_engine_guard
-                                .update_player_position(
-                                    &mut map_manager_guard,
-                                    &mut player_guard,
-                                    new_player_pos,
-                                )
-                                .await;
-                        }
-                        MovementType::LadderUp => {
-                            map_manager_guard.load_map("scene_ladder", MovementType::LadderUp);
-                            let ladder_entry_pos = Vec2::new(3, 2);
-                            player_guard.update_tile_below_player(LADDER_TILE_SET.floor);
-                            player_guard.previous_player_position = player_guard.position;
-                            player_guard.position = ladder_entry_pos;
-                        }
-                        MovementType::LadderDown => {
-                            map_manager_guard.load_map("scene_ladder", MovementType::LadderDown);
-                            player_guard.position = Vec2::new(3, 2);
+                    // the console swallows every key while it is open, including the hotkeys below
+                    if action == Some(Action::Console) || terminal.is_console_open() {
+                        if !terminal.is_console_open() {
+                            terminal.open_console();
+                        } else if let ConsoleAction::Submit(line) =
+                            terminal.handle_console_key(key_input.code)
+                        {
+                            step(&mut game, &mut recorder, GameEvent::Command(line));
                         }
-                        MovementType::LadderExit => {
-                            map_manager_guard.load_map("map2", MovementType::Normal);
-                            player_guard.update_tile_below_player(LADDER_TILE_SET.floor);
-                            player_guard.position = player_guard.previous_player_position;
-                            player_guard.tile_below_player =
-                                player_guard.previous_tile_below_player;
-                        }
-                        MovementType::LadderEnter => {
-                            map_manager_guard.load_map("map1", MovementType::Normal);
-                        }
-                        MovementType::Battle => {
-                            let position = Vec2::new(new_player_pos.x, new_player_pos.y);
-                            
-                            if let Some(monster) = monster_manager_guard.get_monster_at_position(position) {
-                                BattleSystem::start_battle(&mut player_guard, monster, &chat_clone).await;
-                            }
+                        terminal.print_terminal(&game);
+                        continue;
+                    }
 
-                            drop(monster_manager_guard);
+                    // the editor takes every other key while it is open, edit off in the console closes it too
+                    if game.editor().is_some() {
+                        if let Some(input) = get_editor_input(action, &key_input) {
+                            step(&mut game, &mut recorder, GameEvent::Edit(input));
                         }
+                        terminal.print_terminal(&game);
+                        continue;
+                    }
+
+                    // quitting closes whatever screen is open first
+                    let is_screen_open = terminal.is_log_open()
+                        || terminal.is_overview_open()
+                        || game.player().inventory.is_open;
+                    if action == Some(Action::Quit) && !is_screen_open {
+                        break;
+                    }
+
+                    if action == Some(Action::CycleMinimap) {
+                        let mode_name = terminal.cycle_minimap_mode();
+                        game.add_message(&format!("Minimap: {}", mode_name), MessageCategory::General);
+                    }
+                    if action == Some(Action::CyclePalette) {
+                        let palette_name = terminal.cycle_palette();
+                        game.add_message(&format!("Palette: {}", palette_name), MessageCategory::General);
+                    }
 
-                        _ => {}
+                    if action == Some(Action::DebugOverlay) {
+                        terminal.toggle_debug_overlay();
                     }
 
-                    let terrain_data = map_factory
-                        .generate_terrain(&mut map_manager_guard, new_player_pos, &mut chat_clone)
-                        .await;
+                    if action == Some(Action::Log) || terminal.is_log_open() {
+                        match (action, key_input.code) {
+                            (Some(Action::Log | Action::Quit), _) => terminal.toggle_log(),
+                            (_, KeyCode::Up) => terminal.scroll_log(1),
+                            (_, KeyCode::Down) => terminal.scroll_log(-1),
+                            (_, KeyCode::PageUp) => terminal.scroll_log(10),
+                            (_, KeyCode::PageDown) => terminal.scroll_log(-10),
+                            (_, KeyCode::Char(number)) if number.is_ascii_digit() => terminal
+                                .toggle_log_category(number.to_digit(10).unwrap_or(0) as usize),
+                            _ => {}
+                        }
+                        terminal.print_terminal(&game);
+                        continue;
+                    }
 
-                    if let Some(terrain_data) = terrain_data {
-                        map_manager_guard.update_current_map(terrain_data, &mut chat_clone);
+                    // while the overview is open the movement keys scroll it instead of moving the player
+                    if action == Some(Action::Overview) || terminal.is_overview_open() {
+                        match action {
+                            Some(Action::Overview | Action::Quit) => terminal.toggle_overview(),
+                            Some(action) => {
+                                if let Some((delta_x, delta_y)) = action.get_direction() {
+                                    terminal.scroll_overview(delta_x, delta_y);
+                                }
+                            }
+                            None => {}
+                        }
+                        terminal.print_terminal(&game);
+                        continue;
                     }
 
-                    collision_engine_guard
-                        .update_player_vision(
-                            &mut map_manager_guard,
-                            &mut player_guard,
-                            new_player_pos,
-                        )
-                        .await;
-
-                    terminal_guard
-                        .print_terminal(&player_guard, &mut map_manager_guard, &mut chat_clone)
-                        .await;
+                    // a turn that hasn't been earned yet is dropped, so there is nothing new to draw
+                    let Some(action) = action else {
+                        continue;
+                    };
+                    if step(&mut game, &mut recorder, GameEvent::Input(action)) {
+                        terminal.print_terminal(&game);
+                    }
                 }
             }
+            Event::Resize(_, _) => {
+                // the renderer picks up the new size on its own, it just needs a frame to draw
+                terminal.print_terminal(&game);
+            }
             _ => {}
         }
     }
+
+    // hand the terminal back in the state we found it
+    let _ = terminal.end_session();
+    ExitCode::SUCCESS
 }
 
-// asynchronous function to update monsters
-async fn update_monsters_async(
-    collision_engine_clone: Arc<Mutex<CollisionEngine>>,
-    map_manager_clone: &mut Arc<Mutex<MapManager>>,
-    player_clone: Arc<Mutex<Player>>,
-    monster_manager_clone: Arc<Mutex<MonsterManager>>,
-    chat_clone: &mut Arc<Mutex<Chat>>,
-    terminal_clone: Arc<Mutex<GameClient>>,
-) {
+// offers the saved games before anything else, none at all goes straight into the new game
+fn choose_game(terminal: &mut GameClient, new_game: Game, config: &GameConfig) -> Option<Game> {
+    let slots = save::list_slots();
+    if slots.is_empty() {
+        return Some(new_game);
+    }
+
+    let mut load_screen = LoadScreen::new(slots);
     loop {
-        let mut collision_engine_guard = collision_engine_clone.lock().await;
-        let player_guard = player_clone.lock().await;
-        let mut monster_manager_guard = monster_manager_clone.lock().await;
-        let mut terminal_guard = terminal_clone.lock().await;
-        let mut map_manager_guard = map_manager_clone.lock().await;
+        terminal.print_load_screen(&load_screen);
+        let key_input = match event::read().unwrap() {
+            Event::Key(key_input) if key_input.kind == KeyEventKind::Press => key_input,
+            _ => continue,
+        };
+
+        match load_screen.handle_key(key_input.code) {
+            LoadScreenAction::None => {}
+            LoadScreenAction::NewGame => return Some(new_game),
+            LoadScreenAction::Load(slot) => match Game::load(&slot, config.clone()) {
+                Ok(game) => return Some(game),
+                Err(error) => load_screen.error = Some(error.to_string()),
+            },
+            LoadScreenAction::Quit => return None,
+        }
+    }
+}
 
-        let mut monsters_to_remove= Vec::<i32>::new();
+// checks every map file in the directory, prints what it found and fails on any error
+fn validate_maps(directory: &Path) -> ExitCode {
+    let paths = match map_loader::list_map_files(directory) {
+        Ok(paths) => paths,
+        Err(error) => {
+            eprintln!("{} could not be read: {}", directory.display(), error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut error_count = 0;
+    let mut warning_count = 0;
+    for path in &paths {
+        let diagnostics = match map_loader::check(path) {
+            Ok(diagnostics) => diagnostics,
+            Err(error) => {
+                println!("{}", error);
+                error_count += 1;
+                continue;
+            }
+        };
+        for diagnostic in diagnostics {
+            match diagnostic.severity {
+                Severity::Error => error_count += 1,
+                Severity::Warning => warning_count += 1,
+            }
+            println!("{}", diagnostic);
+        }
+    }
 
-        for monster in monster_manager_guard.get_monsters_mut().values_mut() {
-            if !monster.is_alive {
-                let mut map = map_manager_guard.get_mut_current_map();
-                monsters_to_remove.push(monster.id);
+    println!(
+        "Checked {} maps: {} errors, {} warnings.",
+        paths.len(),
+        error_count,
+        warning_count
+    );
+    if error_count > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
 
-                chat_clone.lock().await.process_debug_message(&format!("mon dead at pos: {:?}", monster.position), 3);
-                map.map[monster.position.y][monster.position.x] = Space::new(DEFAULT_TILE_SET.floor);
+// every event the player causes goes through here so a recording misses nothing
+fn step(game: &mut Game, recorder: &mut Option<ReplayWriter>, event: GameEvent) -> bool {
+    let was_applied = game.step(event.clone());
+    if let (true, Some(writer)) = (was_applied, recorder.as_mut()) {
+        if let Err(error) = writer.record(game, &event) {
+            // the run itself is fine, it just can't be reproduced past this point
+            game.add_message(
+                &format!("Stopped recording the replay: {}", error),
+                MessageCategory::Warning,
+            );
+            *recorder = None;
+        }
+    }
+    was_applied
+}
+
+// none of the editor's own keys move the player in any preset, so whatever the movement keys
+// are bound to steers the cursor
+fn get_editor_input(action: Option<Action>, key_input: &KeyEvent) -> Option<EditorInput> {
+    let is_ctrl = key_input.modifiers.contains(KeyModifiers::CONTROL);
+    let input = match (key_input.code, is_ctrl) {
+        (KeyCode::Enter | KeyCode::Char(' '), false) => EditorInput::Paint,
+        (KeyCode::Char(']'), false) => EditorInput::NextBrush,
+        (KeyCode::Char('['), false) => EditorInput::PreviousBrush,
+        (KeyCode::Char('r'), false) => EditorInput::Corner,
+        (KeyCode::Char('z'), true) => EditorInput::Undo,
+        (KeyCode::Char('y'), true) => EditorInput::Redo,
+        (KeyCode::Char('s'), true) => EditorInput::Save,
+        (KeyCode::Esc, _) => EditorInput::Close,
+        _ => {
+            let (delta_x, delta_y) = action?.get_direction()?;
+            EditorInput::Move(delta_x, delta_y)
+        }
+    };
+    Some(input)
+}
+
+// plays the recorded events one after another, esc hands the game over to the player early
+// returns true when the player quit the whole game instead
+fn play_replay(
+    terminal: &mut GameClient,
+    game: &mut Game,
+    replay: &Replay,
+    is_step_by_step: bool,
+) -> bool {
+    let controls = if is_step_by_step {
+        "any key steps, esc takes over, q quits"
+    } else {
+        "esc takes over, q quits"
+    };
+    game.add_message(
+        &format!(
+            "Playing back {} events, {}.",
+            replay.entries.len(),
+            controls
+        ),
+        MessageCategory::General,
+    );
+    terminal.print_terminal(game);
+
+    for index in 0..replay.entries.len() {
+        match read_replay_key(is_step_by_step) {
+            Some(KeyCode::Esc) => {
+                game.add_message("Stopped the replay early.", MessageCategory::General);
+                return false;
             }
+            Some(KeyCode::Char('q')) => return true,
+            _ => {}
         }
 
-        for monster_id in monsters_to_remove {
-            monster_manager_guard.despawn(monster_id);
+        if let Err(divergence) = replay.play_entry(index, game) {
+            game.add_message(&divergence.to_string(), MessageCategory::Warning);
+            terminal.print_terminal(game);
+            return false;
         }
+        terminal.print_terminal(game);
+    }
+    false
+}
 
-        let mut new_monsters_pos = collision_engine_guard
-            .try_move_monsters(
-                &player_guard,
-                &mut monster_manager_guard,
-                &mut map_manager_guard,
-                chat_clone,
-            )
-            .await;
-
-        let processed_monsters_positions = collision_engine_guard
-            .process_monsters_move(
-                &mut new_monsters_pos,
-                &mut map_manager_guard,
-                &mut monster_manager_guard,
-            )
-            .await;
-
-        collision_engine_guard
-            .update_monsters_position(
-                &mut map_manager_guard,
-                &mut monster_manager_guard,
-                processed_monsters_positions,
-            )
-            .await;
-
-        collision_engine_guard
-            .update_player_vision(&mut map_manager_guard, &player_guard, Vec2::ZERO)
-            .await;
-
-        terminal_guard
-            .print_terminal(&player_guard, &mut map_manager_guard, chat_clone)
-            .await;
-
-        drop(collision_engine_guard);
-        drop(terminal_guard);
-        drop(monster_manager_guard);
-        drop(map_manager_guard);
-        drop(player_guard);
-
-        async_std::task::sleep(Duration::from_secs(1)).await;
+// step by step waits for a key press before every event, at speed a key only interrupts
+fn read_replay_key(is_step_by_step: bool) -> Option<KeyCode> {
+    if !is_step_by_step && !event::poll(REPLAY_EVENT_DELAY).unwrap() {
+        return None;
+    }
+    loop {
+        match event::read().unwrap() {
+            Event::Key(key_input) if key_input.kind == KeyEventKind::Press => {
+                return Some(key_input.code)
+            }
+            _ if is_step_by_step => {}
+            _ => return None,
+        }
     }
 }
@@ -1,29 +1,54 @@
+use crate::item::Item;
+use crate::lock::{Lock, RUSTY_LOCK_ID};
+use crate::pathfinding::MovementMode;
 use crate::player::Player;
+use crate::world_object::WorldObject;
 use crate::space::Space;
 use crate::tile_set::{TileSet, DEFAULT_TILE_SET, MONSTER_TILE_SET};
 use crate::vec2::Vec2;
 use crate::Map;
-use crossterm::{terminal, QueueableCommand};
 
-use std::io::stdout;
+use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct MapData {
+    #[serde(with = "crate::save::tile_rows")]
     pub map: Map,
-    pub str_map: String,
     pub tile_set: TileSet,
     pub width: usize,
     pub height: usize,
+    #[serde(with = "crate::save::explored_rows")]
+    pub explored: HashSet<Vec2>,
+    pub locks: HashMap<Vec2, Lock>,
+    pub key_ids: HashMap<Vec2, String>,
+    pub objects: HashMap<Vec2, WorldObject>,
+    pub item_piles: HashMap<Vec2, Vec<Item>>,
+    // overrides MapManager::movement for this map when set
+    pub movement: Option<MovementMode>,
+    // where the player arrives when the map is first played
+    #[serde(default)]
+    pub spawn: Option<Vec2>,
+    // monsters placed by hand in the map's metadata, as opposed to the random ones
+    #[serde(default)]
+    pub monster_spawns: Vec<(Vec2, char)>,
 }
 
 impl MapData {
     pub(crate) fn new() -> Self {
         MapData {
             map: Map::new(),
-            str_map: String::new(),
             tile_set: DEFAULT_TILE_SET,
             width: 0,
             height: 0,
+            explored: HashSet::new(),
+            locks: HashMap::new(),
+            key_ids: HashMap::new(),
+            objects: HashMap::new(),
+            item_piles: HashMap::new(),
+            movement: None,
+            spawn: None,
+            monster_spawns: Vec::new(),
         }
     }
 
@@ -32,6 +57,26 @@ impl MapData {
         self.map[pos.y][pos.x] = Space::new(tile_set.player);
     }
 
+    // puts back whatever the player was standing on before placing them at the destination
+    pub(crate) fn move_player_to(&mut self, player: &mut Player, destination: Vec2) {
+        let previous_position = player.position;
+        if self.get_space(previous_position).is_some() {
+            self.map[previous_position.y][previous_position.x] =
+                Space::new(player.tile_below_player);
+        }
+        player.update_tile_below_player(self.map[destination.y][destination.x].tile);
+        player.position = destination;
+        self.set_player_position(destination);
+    }
+
+    pub(crate) fn find_player_position(&self) -> Option<Vec2> {
+        self.map.iter().enumerate().find_map(|(pos_y, row)| {
+            row.iter()
+                .position(|space| space.tile == self.tile_set.player)
+                .map(|pos_x| Vec2::new(pos_x, pos_y))
+        })
+    }
+
     pub(crate) fn set_player_vision(&mut self, player: &Player, _player_pos: Vec2) {
         for y in 0..self.height {
             for x in 0..self.width {
@@ -41,7 +86,7 @@ impl MapData {
                     self.map_width,
                     self.map.len()
                 );*/
-                self.map[y][x].is_visible = player.fog_of_war;
+                self.map[y][x].is_visible = !player.fog_of_war;
 
                 /*if self.map[x][y].is_solid || self.map[x][y].tile == DEFAULT_TILE_SET.open_door {
                 } else {
@@ -73,6 +118,7 @@ impl MapData {
 
             let tile = &mut self.map[y][x];
             tile.is_visible = true; //if tile.tile == MONSTER_TILE_SET.snake { false } else { true};
+            self.explored.insert(Vec2::new(x, y));
 
             if tile.is_solid && tile.tile != DEFAULT_TILE_SET.open_door {
                 break;
@@ -80,70 +126,101 @@ impl MapData {
         }
     }
 
-    pub(crate) fn set_monster_position(&mut self, new_pos: Vec2, monster_type: char) {
-        self.map[new_pos.y][new_pos.x] = Space::new(monster_type);
+    pub(crate) fn reveal_all(&mut self) {
+        for (pos_y, row) in self.map.iter().enumerate() {
+            for pos_x in 0..row.len() {
+                self.explored.insert(Vec2::new(pos_x, pos_y));
+            }
+        }
     }
 
-    pub(crate) fn set_map_tile_set(&mut self, tile_set: TileSet) {
-        self.tile_set = tile_set;
+    pub fn is_explored(&self, pos: Vec2) -> bool {
+        self.explored.contains(&pos)
+    }
+
+    // removes the key lying at the position and returns which lock it belongs to
+    pub(crate) fn take_key_at(&mut self, pos: Vec2) -> String {
+        self.key_ids
+            .remove(&pos)
+            .unwrap_or_else(|| RUSTY_LOCK_ID.to_string())
     }
 
-    pub(crate) fn print_map(&self) {
-        let mut stdout = stdout();
-        stdout
-            .queue(terminal::Clear(terminal::ClearType::All))
-            .unwrap();
+    pub(crate) fn get_lock_mut(&mut self, pos: Vec2) -> &mut Lock {
+        let closed_tile = self.get_closed_door_tile(pos);
+        self.locks
+            .entry(pos)
+            .or_insert_with(|| Lock::rusty(closed_tile))
+    }
 
-        for tile in &self.map {
-            let tile_line: String = tile.iter().map(|space| space.tile).collect();
-            println!("{}", tile_line);
+    // the glyph a door shows when shut, a door drawn open takes its side from the wall it sits in
+    pub(crate) fn get_closed_door_tile(&self, pos: Vec2) -> char {
+        let tile = self.map[pos.y][pos.x].tile;
+        if tile != self.tile_set.open_door {
+            return tile;
+        }
+        let is_wall = |x: Option<usize>| {
+            x.and_then(|x| self.get_space(Vec2::new(x, pos.y)))
+                .is_some_and(|space| space.tile == self.tile_set.wall)
+        };
+        if is_wall(pos.x.checked_sub(1)) || is_wall(pos.x.checked_add(1)) {
+            self.tile_set.closed_door_top
+        } else {
+            self.tile_set.closed_door_side
         }
     }
 
-    fn print_map_with_module(&self, module: &[String]) {
-        let mut stdout = stdout();
-        stdout
-            .queue(terminal::Clear(terminal::ClearType::All))
-            .unwrap();
-        let mut counter = 0;
-        for tile in &self.map {
-            let tile_line: String = tile.iter().map(|space| space.tile).collect();
-            if counter <= module.len() - 1 {
-                println!("{}          {}", tile_line, module[counter]);
-                counter += 1;
-            } else {
-                println!("{}", tile_line);
-            }
+    pub fn get_space(&self, pos: Vec2) -> Option<&Space> {
+        self.map.get(pos.y).and_then(|row| row.get(pos.x))
+    }
+
+    // rows can differ in length once terrain has been generated, so use the widest one
+    pub fn get_size(&self) -> Vec2 {
+        Vec2::new(
+            self.map.iter().map(|row| row.len()).max().unwrap_or(0),
+            self.map.len(),
+        )
+    }
+
+    // ladders are the only exits, they span three tiles with the position in the middle
+    pub fn is_ladder_at(&self, pos: Vec2) -> bool {
+        if pos.x == 0 {
+            return false;
         }
-        println!("\n");
-    }
-
-    pub(crate) fn update_str_map_with_modules(&mut self, module: &[[String; 3]; 2]) {
-        let mut counter = 0;
-        self.str_map = String::new();
-        for tile in &self.map {
-            let tile_line: String = tile.iter().map(|space| space.tile).collect();
-            if counter <= module.len() {
-                self.str_map += &*format!(
-                    "{}      {}      {}",
-                    tile_line, module[0][counter], module[1][counter]
-                );
-                counter += 1;
-            } else {
-                self.str_map += &*format!("{}", tile_line);
-            }
-            self.str_map += &*format!("\n");
+        let tiles: String = (pos.x - 1..=pos.x + 1)
+            .filter_map(|x| self.get_space(Vec2::new(x, pos.y)))
+            .map(|space| space.tile)
+            .collect();
+        tiles == self.tile_set.ladder
+    }
+
+    // objects keep their own glyph, so put it back once whatever stood on top has moved off
+    pub(crate) fn restore_object_tile(&mut self, pos: Vec2) {
+        if let Some(object) = self.objects.get(&pos) {
+            self.map[pos.y][pos.x] = Space::new(object.get_tile());
         }
     }
 
-    pub(crate) fn get_current_floor_to_size(&mut self, size: usize) -> [String; 3] {
-        let mut module_pieces = [format!("FLOOR: {}", 0), String::new(), String::new()];
+    pub(crate) fn add_to_item_pile(&mut self, pos: Vec2, items: Vec<Item>) {
+        if !items.is_empty() {
+            self.item_piles.entry(pos).or_default().extend(items);
+        }
+    }
 
-        for i in 1..size {
-            module_pieces[i] = String::new();
+    // piles are drawn on top of the terrain but never over whoever is standing on them
+    pub fn get_display_tile(&self, pos: Vec2) -> char {
+        let space = self.map[pos.y][pos.x];
+        if self.item_piles.contains_key(&pos) && !space.is_monster && !space.is_player {
+            return self.tile_set.item_pile;
         }
+        space.tile
+    }
 
-        module_pieces
+    pub(crate) fn set_monster_position(&mut self, new_pos: Vec2, monster_type: char) {
+        self.map[new_pos.y][new_pos.x] = Space::new(monster_type);
+    }
+
+    pub(crate) fn set_map_tile_set(&mut self, tile_set: TileSet) {
+        self.tile_set = tile_set;
     }
 
     pub(crate) fn get_tile_at_position(&self, position: Option<(usize, usize)>) -> char {
@@ -152,4 +229,17 @@ impl MapData {
         }
         ' '
     }
+
+    // a bare map drawn with the default tiles, for the unit tests
+    #[cfg(test)]
+    pub(crate) fn from_rows(rows: &[&str]) -> Self {
+        let mut map_data = MapData::new();
+        map_data.map = rows
+            .iter()
+            .map(|row| row.chars().map(Space::new).collect())
+            .collect();
+        map_data.height = map_data.map.len();
+        map_data.width = map_data.get_size().x;
+        map_data
+    }
 }
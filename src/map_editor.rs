@@ -0,0 +1,562 @@
+// paints straight onto the current map while the game waits, opened with the edit command
+use crate::item::{Item, ITEM_NAMES};
+use crate::map_data::MapData;
+use crate::map_metadata::MapMetadata;
+use crate::monster::{Monster, MONSTER_NAMES};
+use crate::monster_manager::MonsterManager;
+use crate::space::Space;
+use crate::tile_set::TileSet;
+use crate::vec2::Vec2;
+use crate::world_object::{WorldObject, WorldObjectKind};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// older steps fall off the bottom of the undo stack past this
+const MAX_UNDO_STEPS: usize = 100;
+
+// the defaults a freshly painted object starts with, the .meta file can change them afterwards
+const TRAP_DAMAGE: i32 = 5;
+const BREAKABLE_DURABILITY: i32 = 6;
+
+// everything the editor can be asked to do, a frontend turns keys into these
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum EditorInput {
+    Move(isize, isize),
+    NextBrush,
+    PreviousBrush,
+    Paint,
+    // marks one corner of a rectangle, the second one fills it
+    Corner,
+    Undo,
+    Redo,
+    Save,
+    Close,
+}
+
+impl EditorInput {
+    // how the input is written into a replay file
+    pub fn to_text(&self) -> String {
+        match self {
+            EditorInput::Move(delta_x, delta_y) => format!("move {} {}", delta_x, delta_y),
+            EditorInput::NextBrush => "brush next".to_string(),
+            EditorInput::PreviousBrush => "brush previous".to_string(),
+            EditorInput::Paint => "paint".to_string(),
+            EditorInput::Corner => "corner".to_string(),
+            EditorInput::Undo => "undo".to_string(),
+            EditorInput::Redo => "redo".to_string(),
+            EditorInput::Save => "save".to_string(),
+            EditorInput::Close => "close".to_string(),
+        }
+    }
+
+    pub fn from_text(text: &str) -> Option<EditorInput> {
+        let parts: Vec<&str> = text.split_whitespace().collect();
+        let input = match parts.as_slice() {
+            ["move", delta_x, delta_y] => {
+                EditorInput::Move(delta_x.parse().ok()?, delta_y.parse().ok()?)
+            }
+            ["brush", "next"] => EditorInput::NextBrush,
+            ["brush", "previous"] => EditorInput::PreviousBrush,
+            ["paint"] => EditorInput::Paint,
+            ["corner"] => EditorInput::Corner,
+            ["undo"] => EditorInput::Undo,
+            ["redo"] => EditorInput::Redo,
+            ["save"] => EditorInput::Save,
+            ["close"] => EditorInput::Close,
+            _ => return None,
+        };
+        Some(input)
+    }
+}
+
+// what painting puts down, tiles come from the tile set of the map being edited
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Brush {
+    Floor,
+    Wall,
+    SideDoor,
+    TopDoor,
+    OpenDoor,
+    Key,
+    Lever,
+    PressurePlate,
+    Chest,
+    Trap,
+    BreakableWall,
+    // three tiles wide, centred on the cursor
+    Ladder,
+    // where the player arrives when the map is first played
+    Spawn,
+    Monster(char),
+    Item(&'static str),
+}
+
+impl Brush {
+    // every brush in the order they are cycled through
+    pub fn all() -> Vec<Brush> {
+        let mut brushes = vec![
+            Brush::Floor,
+            Brush::Wall,
+            Brush::SideDoor,
+            Brush::TopDoor,
+            Brush::OpenDoor,
+            Brush::Key,
+            Brush::Lever,
+            Brush::PressurePlate,
+            Brush::Chest,
+            Brush::Trap,
+            Brush::BreakableWall,
+            Brush::Ladder,
+            Brush::Spawn,
+        ];
+        brushes.extend(
+            MONSTER_NAMES
+                .iter()
+                .filter_map(|name| Monster::tile_from_name(name))
+                .map(Brush::Monster),
+        );
+        brushes.extend(ITEM_NAMES.iter().map(|name| Brush::Item(name)));
+        brushes
+    }
+
+    pub fn get_name(&self) -> String {
+        match self {
+            Brush::Floor => "floor".to_string(),
+            Brush::Wall => "wall".to_string(),
+            Brush::SideDoor => "door (side)".to_string(),
+            Brush::TopDoor => "door (top)".to_string(),
+            Brush::OpenDoor => "open door".to_string(),
+            Brush::Key => "key".to_string(),
+            Brush::Lever => "lever".to_string(),
+            Brush::PressurePlate => "pressure plate".to_string(),
+            Brush::Chest => "chest".to_string(),
+            Brush::Trap => "trap".to_string(),
+            Brush::BreakableWall => "breakable wall".to_string(),
+            Brush::Ladder => "ladder".to_string(),
+            Brush::Spawn => "spawn point".to_string(),
+            Brush::Monster(tile) => Monster::name_from_tile(*tile).to_string(),
+            Brush::Item(name) => name.replace('_', " "),
+        }
+    }
+
+    // the single tile this brush paints, none for the brushes that place something else
+    pub fn get_tile(&self, tile_set: &TileSet) -> Option<char> {
+        let tile = match self {
+            Brush::Floor => tile_set.floor,
+            Brush::Wall => tile_set.wall,
+            Brush::SideDoor => tile_set.closed_door_side,
+            Brush::TopDoor => tile_set.closed_door_top,
+            Brush::OpenDoor => tile_set.open_door,
+            Brush::Key => tile_set.key,
+            Brush::Lever => tile_set.lever,
+            Brush::PressurePlate => tile_set.pressure_plate,
+            Brush::Chest => tile_set.chest,
+            Brush::Trap => tile_set.trap,
+            Brush::BreakableWall => tile_set.breakable_wall,
+            Brush::Ladder | Brush::Spawn | Brush::Monster(_) | Brush::Item(_) => return None,
+        };
+        Some(tile)
+    }
+
+    // objects need more than their glyph, this is what a painted one starts out as
+    fn get_object(&self) -> Option<WorldObject> {
+        let kind = match self {
+            Brush::Lever => WorldObjectKind::Lever { is_on: false },
+            Brush::PressurePlate => WorldObjectKind::PressurePlate,
+            Brush::Chest => WorldObjectKind::Chest {
+                contents: Vec::new(),
+                loot_table: None,
+                is_open: false,
+            },
+            Brush::Trap => WorldObjectKind::Trap {
+                damage: TRAP_DAMAGE,
+                is_hidden: false,
+            },
+            Brush::BreakableWall => WorldObjectKind::BreakableWall {
+                durability: BREAKABLE_DURABILITY,
+            },
+            _ => return None,
+        };
+        Some(WorldObject::new(kind))
+    }
+}
+
+// the parts of the world an edit can change, kept whole for undo and redo
+#[derive(Clone)]
+struct Snapshot {
+    map_data: MapData,
+    monster_manager: MonsterManager,
+}
+
+// one undo step, the map being played and the map as its file has it change together
+struct EditStep {
+    played: Snapshot,
+    authored: Option<Snapshot>,
+}
+
+pub struct MapEditor {
+    pub cursor: Vec2,
+    brush_index: usize,
+    // the first corner of a rectangle fill, waiting for the second
+    pub corner: Option<Vec2>,
+    // the map as its file has it with every edit made since, it is what gets saved so chests
+    // emptied or keys picked up while playing stay in the file, none for the generated map
+    authored: Option<Snapshot>,
+    undo_stack: Vec<EditStep>,
+    redo_stack: Vec<EditStep>,
+}
+
+impl MapEditor {
+    pub(crate) fn new(cursor: Vec2, authored_map: Option<MapData>) -> Self {
+        let authored = authored_map.map(|mut map_data| {
+            // the file keeps the starting spot in the spawn, the player glyph is only in the way
+            let floor = map_data.tile_set.floor;
+            for space in map_data.map.iter_mut().flatten() {
+                if space.is_player {
+                    *space = Space::new(floor);
+                }
+            }
+            let mut monster_manager = MonsterManager::new();
+            monster_manager.spawn_placed_monsters(&mut map_data);
+            Snapshot {
+                map_data,
+                monster_manager,
+            }
+        });
+        MapEditor {
+            cursor,
+            brush_index: 0,
+            corner: None,
+            authored,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+        }
+    }
+
+    pub fn get_brush(&self) -> Brush {
+        Brush::all()[self.brush_index]
+    }
+
+    pub fn get_undo_count(&self) -> usize {
+        self.undo_stack.len()
+    }
+
+    pub fn get_redo_count(&self) -> usize {
+        self.redo_stack.len()
+    }
+
+    // everything but saving and closing, which need to know more than the map, returns what to
+    // tell the player if anything
+    pub(crate) fn handle(
+        &mut self,
+        input: &EditorInput,
+        map_data: &mut MapData,
+        monster_manager: &mut MonsterManager,
+    ) -> Result<Option<String>, String> {
+        match input {
+            EditorInput::Move(delta_x, delta_y) => {
+                let size = map_data.get_size();
+                let move_axis = |position: usize, delta: isize, length: usize| {
+                    position
+                        .saturating_add_signed(delta)
+                        .min(length.saturating_sub(1))
+                };
+                self.cursor = Vec2::new(
+                    move_axis(self.cursor.x, *delta_x, size.x),
+                    move_axis(self.cursor.y, *delta_y, size.y),
+                );
+                Ok(None)
+            }
+            EditorInput::NextBrush | EditorInput::PreviousBrush => {
+                let count = Brush::all().len();
+                self.brush_index = if *input == EditorInput::NextBrush {
+                    (self.brush_index + 1) % count
+                } else {
+                    (self.brush_index + count - 1) % count
+                };
+                Ok(None)
+            }
+            EditorInput::Paint => {
+                let brush = self.get_brush();
+                let cursor = self.cursor;
+                self.edit(map_data, monster_manager, |map_data, monster_manager| {
+                    MapEditor::paint(brush, cursor, map_data, monster_manager)
+                })?;
+                Ok(None)
+            }
+            EditorInput::Corner => match self.corner.take() {
+                None => {
+                    self.corner = Some(self.cursor);
+                    Ok(Some(format!(
+                        "Corner set at {},{}, set the opposite one to fill.",
+                        self.cursor.x, self.cursor.y
+                    )))
+                }
+                Some(corner) => {
+                    let brush = self.get_brush();
+                    let cursor = self.cursor;
+                    let count =
+                        self.edit(map_data, monster_manager, |map_data, monster_manager| {
+                            MapEditor::fill(brush, corner, cursor, map_data, monster_manager)
+                        })?;
+                    Ok(Some(format!(
+                        "Filled {} tiles with {}.",
+                        count,
+                        brush.get_name()
+                    )))
+                }
+            },
+            EditorInput::Undo => {
+                let step = self.undo_stack.pop().ok_or("Nothing to undo.")?;
+                let redo_step = self.swap(step, map_data, monster_manager);
+                self.redo_stack.push(redo_step);
+                Ok(None)
+            }
+            EditorInput::Redo => {
+                let step = self.redo_stack.pop().ok_or("Nothing to redo.")?;
+                let undo_step = self.swap(step, map_data, monster_manager);
+                self.undo_stack.push(undo_step);
+                Ok(None)
+            }
+            EditorInput::Save | EditorInput::Close => Ok(None),
+        }
+    }
+
+    // runs one change as a single undo step on both copies of the map, a change that fails on
+    // either leaves nothing behind
+    fn edit<T>(
+        &mut self,
+        map_data: &mut MapData,
+        monster_manager: &mut MonsterManager,
+        change: impl Fn(&mut MapData, &mut MonsterManager) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let step = EditStep {
+            played: Snapshot {
+                map_data: map_data.clone(),
+                monster_manager: monster_manager.clone(),
+            },
+            authored: self.authored.clone(),
+        };
+        let result = change(map_data, monster_manager).and_then(|result| {
+            if let Some(authored) = self.authored.as_mut() {
+                change(&mut authored.map_data, &mut authored.monster_manager)?;
+            }
+            Ok(result)
+        });
+        match result {
+            Ok(result) => {
+                self.undo_stack.push(step);
+                if self.undo_stack.len() > MAX_UNDO_STEPS {
+                    self.undo_stack.remove(0);
+                }
+                self.redo_stack.clear();
+                Ok(result)
+            }
+            Err(message) => {
+                *map_data = step.played.map_data;
+                *monster_manager = step.played.monster_manager;
+                self.authored = step.authored;
+                Err(message)
+            }
+        }
+    }
+
+    // puts the step in place and hands back what it replaced
+    fn swap(
+        &mut self,
+        step: EditStep,
+        map_data: &mut MapData,
+        monster_manager: &mut MonsterManager,
+    ) -> EditStep {
+        EditStep {
+            played: Snapshot {
+                map_data: std::mem::replace(map_data, step.played.map_data),
+                monster_manager: std::mem::replace(
+                    monster_manager,
+                    step.played.monster_manager,
+                ),
+            },
+            authored: std::mem::replace(&mut self.authored, step.authored),
+        }
+    }
+
+    // writes the map as its file has it with the edits made, never the state it was played into
+    pub(crate) fn save(&self, path: &Path) -> io::Result<()> {
+        let authored = self.authored.as_ref().ok_or(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "the map has no file to start from",
+        ))?;
+        save_map(path, &authored.map_data, &authored.monster_manager)
+    }
+
+    fn paint(
+        brush: Brush,
+        position: Vec2,
+        map_data: &mut MapData,
+        monster_manager: &mut MonsterManager,
+    ) -> Result<(), String> {
+        let space = *map_data
+            .get_space(position)
+            .ok_or("The cursor is off the map.")?;
+        if let Some(tile) = brush.get_tile(&map_data.tile_set) {
+            return MapEditor::paint_tile(brush, tile, position, map_data, monster_manager);
+        }
+
+        let is_free = space.is_traversable && !space.is_player && !space.is_monster;
+        match brush {
+            Brush::Ladder => {
+                let ladder: Vec<char> = map_data.tile_set.ladder.chars().collect();
+                let row_length = map_data.map[position.y].len();
+                if position.x == 0 || position.x + 1 >= row_length {
+                    return Err("A ladder needs a tile on either side of the cursor.".to_string());
+                }
+                for (tile, pos_x) in ladder.into_iter().zip(position.x - 1..) {
+                    let position = Vec2::new(pos_x, position.y);
+                    MapEditor::paint_tile(brush, tile, position, map_data, monster_manager)?;
+                }
+                Ok(())
+            }
+            Brush::Spawn if is_free || space.is_player => {
+                map_data.spawn = Some(position);
+                Ok(())
+            }
+            Brush::Monster(tile) if is_free => {
+                map_data.monster_spawns.push((position, tile));
+                monster_manager.spawn_monster(map_data, tile, position);
+                Ok(())
+            }
+            Brush::Item(name) => {
+                if !space.is_traversable {
+                    return Err("Items can only be placed on walkable tiles.".to_string());
+                }
+                map_data.add_to_item_pile(position, Item::from_name(name).into_iter().collect());
+                Ok(())
+            }
+            _ => Err(format!(
+                "A {} needs a free walkable tile.",
+                brush.get_name()
+            )),
+        }
+    }
+
+    // replaces whatever was at the position, including monsters, with a bare tile
+    fn paint_tile(
+        brush: Brush,
+        tile: char,
+        position: Vec2,
+        map_data: &mut MapData,
+        monster_manager: &mut MonsterManager,
+    ) -> Result<(), String> {
+        let space = *map_data
+            .get_space(position)
+            .ok_or("The cursor is off the map.")?;
+        if space.is_player {
+            return Err("The player is standing there.".to_string());
+        }
+        if let Some(monster_id) = monster_manager
+            .get_monster_at_position(position)
+            .map(|monster| monster.id)
+        {
+            monster_manager.despawn(monster_id);
+        }
+        map_data
+            .monster_spawns
+            .retain(|(spawn, _)| *spawn != position);
+        map_data.objects.remove(&position);
+        map_data.locks.remove(&position);
+        map_data.key_ids.remove(&position);
+        map_data.item_piles.remove(&position);
+
+        let mut space = Space::new(tile);
+        space.is_visible = true;
+        map_data.map[position.y][position.x] = space;
+        if let Some(object) = brush.get_object() {
+            map_data.objects.insert(position, object);
+        }
+        if map_data.spawn == Some(position) && !space.is_traversable {
+            map_data.spawn = None;
+        }
+        Ok(())
+    }
+
+    // only tile brushes fill, a rectangle of monsters or spawn points makes no sense
+    fn fill(
+        brush: Brush,
+        corner: Vec2,
+        opposite: Vec2,
+        map_data: &mut MapData,
+        monster_manager: &mut MonsterManager,
+    ) -> Result<usize, String> {
+        let tile = brush
+            .get_tile(&map_data.tile_set)
+            .ok_or(format!("A {} can't fill a rectangle.", brush.get_name()))?;
+        let mut count = 0;
+        for pos_y in corner.y.min(opposite.y)..=corner.y.max(opposite.y) {
+            for pos_x in corner.x.min(opposite.x)..=corner.x.max(opposite.x) {
+                let position = Vec2::new(pos_x, pos_y);
+                // the player can't be painted over, so the rectangle goes around them
+                if map_data
+                    .get_space(position)
+                    .is_some_and(|space| space.is_player)
+                {
+                    continue;
+                }
+                MapEditor::paint_tile(brush, tile, position, map_data, monster_manager)?;
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+}
+
+// writes a map into a map file and its .meta sidecar, with the monsters lifted off and any open
+// doors shut again
+fn save_map(path: &Path, map_data: &MapData, monster_manager: &MonsterManager) -> io::Result<()> {
+    let tile_set = &map_data.tile_set;
+    let tiles_below_monsters: HashMap<Vec2, char> = monster_manager
+        .iter()
+        .map(|monster| (monster.position, monster.tile_below))
+        .collect();
+
+    let mut contents = String::new();
+    for (pos_y, row) in map_data.map.iter().enumerate() {
+        for (pos_x, space) in row.iter().enumerate() {
+            let position = Vec2::new(pos_x, pos_y);
+            let tile = if map_data.spawn == Some(position) {
+                tile_set.player
+            } else if space.is_player {
+                tile_set.floor
+            } else if space.is_monster {
+                tiles_below_monsters
+                    .get(&position)
+                    .copied()
+                    .unwrap_or(tile_set.floor)
+            } else if space.tile == tile_set.open_door {
+                map_data
+                    .locks
+                    .get(&position)
+                    .map_or(space.tile, |lock| lock.closed_tile)
+            } else {
+                space.tile
+            };
+            contents.push(tile);
+        }
+        contents.push('\n');
+    }
+    fs::write(path, contents)?;
+
+    let metadata_path = path.with_extension("meta");
+    let metadata = MapMetadata::from_map(map_data);
+    if metadata.is_empty() {
+        return match fs::remove_file(&metadata_path) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+            _ => Ok(()),
+        };
+    }
+    fs::write(
+        metadata_path,
+        format!("# saved by the map editor\n{}", metadata.to_text()),
+    )
+}
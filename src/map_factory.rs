@@ -1,4 +1,5 @@
 use crate::chat::Chat;
+use crate::lock::Lock;
 use crate::map_data::MapData;
 
 use crate::player::Player;
@@ -8,17 +9,19 @@ use crate::tile_set::DEFAULT_TILE_SET;
 use crate::Vec2;
 
 use crate::map_manager::MapManager;
-use futures::lock::{Mutex, MutexGuard};
 use rand::rngs::StdRng;
 use rand::Rng;
 use rand::SeedableRng;
 use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashSet, VecDeque};
 use std::fs::File;
 use std::hash::{Hash, Hasher};
 use std::io;
 use std::io::BufRead;
 use std::path::Path;
-use std::sync::Arc;
+
+pub const GENERATED_MAP_SIZE: usize = 20;
+pub const GENERATED_MAP_START: Vec2 = Vec2::new(2, 1);
 
 #[derive(Clone)]
 pub struct MapFactory {}
@@ -42,11 +45,11 @@ impl MapFactory {
         seed_phrase.hash(&mut hasher);
         let seed = hasher.finish();
 
-        // create a seeded RNG using the generated seed
+        // create a seeded RNG using the generated seed, the same phrase always builds the same map
+        let mut rng = StdRng::seed_from_u64(seed);
 
         for pos_y in 0..height {
             for pos_x in 0..width {
-                let mut rng = rand::thread_rng();
                 let y: f64 = rng.gen(); // generates a float between 0 and 1
 
                 let tile =
@@ -78,22 +81,92 @@ impl MapFactory {
         player.position = pos;
         player.tile_below_player = DEFAULT_TILE_SET.floor;
         new_map.set_player_position(pos);
+        new_map.spawn = Some(pos);
 
-        new_map.width = new_map.map.len();
-        new_map.height = if new_map.width > 0 {
-            new_map.map[0].len()
-        } else {
-            0
-        };
+        new_map.height = new_map.map.len();
+        new_map.width = new_map.get_size().x;
+
+        self.place_locked_door(&mut new_map, pos, &mut rng);
 
         return new_map;
     }
 
-    pub(crate) async fn generate_terrain<'a>(
+    // turns a wall between two floor tiles into a coloured door and drops the matching key where
+    // the player can walk to without going through that door
+    fn place_locked_door(&mut self, map_data: &mut MapData, player_position: Vec2, rng: &mut StdRng) {
+        let lock_ids = ["red", "blue", "green", "yellow"];
+        let mut door_candidates = Vec::<Vec2>::new();
+
+        for (pos_y, row) in map_data.map.iter().enumerate() {
+            for (pos_x, space) in row.iter().enumerate() {
+                let position = Vec2::new(pos_x, pos_y);
+                if space.tile == DEFAULT_TILE_SET.wall && pos_x > 0 && pos_x + 1 < row.len() {
+                    let is_passage = row[pos_x - 1].tile == DEFAULT_TILE_SET.floor
+                        && row[pos_x + 1].tile == DEFAULT_TILE_SET.floor;
+                    if is_passage {
+                        door_candidates.push(position);
+                    }
+                }
+            }
+        }
+
+        if door_candidates.is_empty() {
+            return;
+        }
+        let door_position = door_candidates[rng.gen_range(0..door_candidates.len())];
+
+        let key_candidates: Vec<Vec2> = self
+            .get_reachable_floor(map_data, player_position, door_position)
+            .into_iter()
+            .filter(|position| *position != player_position)
+            .collect();
+        if key_candidates.is_empty() {
+            return;
+        }
+        let key_position = key_candidates[rng.gen_range(0..key_candidates.len())];
+        let lock_id = lock_ids[rng.gen_range(0..lock_ids.len())];
+
+        map_data.map[door_position.y][door_position.x] = Space::new(DEFAULT_TILE_SET.closed_door_side);
+        map_data
+            .locks
+            .insert(door_position, Lock::new(lock_id, DEFAULT_TILE_SET.closed_door_side));
+        map_data.map[key_position.y][key_position.x] = Space::new(DEFAULT_TILE_SET.key);
+        map_data.key_ids.insert(key_position, lock_id.to_string());
+    }
+
+    // the floor tiles reachable from the start with the door shut, in the order they were found
+    fn get_reachable_floor(
+        &self,
+        map_data: &MapData,
+        start: Vec2,
+        door_position: Vec2,
+    ) -> Vec<Vec2> {
+        let mut reached = vec![start];
+        let mut visited = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(position) = queue.pop_front() {
+            for (delta_x, delta_y) in [(0, -1), (-1, 0), (0, 1), (1, 0)] {
+                let neighbour = Vec2::new(
+                    position.x.wrapping_add_signed(delta_x),
+                    position.y.wrapping_add_signed(delta_y),
+                );
+                let is_floor = map_data
+                    .get_space(neighbour)
+                    .is_some_and(|space| space.tile == DEFAULT_TILE_SET.floor);
+                if is_floor && neighbour != door_position && visited.insert(neighbour) {
+                    reached.push(neighbour);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+        reached
+    }
+
+    pub(crate) fn generate_terrain(
         &mut self,
-        map_manager_guard: &mut MutexGuard<'a, MapManager>,
+        map_manager_guard: &mut MapManager,
         new_player_position: Vec2,
-        chat_clone: &mut Arc<Mutex<Chat>>,
+        _chat: &mut Chat,
     ) -> Option<TerrainData> {
         let mut terrain_data = TerrainData::new();
 
@@ -101,9 +174,10 @@ impl MapFactory {
         "seedphrase".hash(&mut hasher);
         let seed = hasher.finish();
         let mut rng = StdRng::seed_from_u64(seed);*/
-        let mut chat = chat_clone.lock().await;
         let map_index = map_manager_guard.current_map_index;
         let map = map_manager_guard.get_map(map_index).expect("map data");
+        // a step off the edge was refused, the map only grows under the player's feet
+        map.get_space(new_player_position)?;
         if new_player_position.x >= map.width - 1 {
             if new_player_position.x >= 20 && new_player_position.y >= 10 {
                 terrain_data.width_increase = 10;
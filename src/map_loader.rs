@@ -0,0 +1,612 @@
+use crate::lock::{is_master_key, Lock, RUSTY_LOCK_ID};
+use crate::map_data::MapData;
+use crate::map_manager;
+use crate::map_metadata::MapMetadata;
+use crate::space::Space;
+use crate::tiled;
+use crate::tile_set::{TileSet, DEFAULT_TILE_SET, LADDER_TILE_SET, MONSTER_TILE_SET};
+use crate::vec2::Vec2;
+use crate::world_object::WorldObjectKind;
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const MAPS_DIRECTORY: &str = "src/maps";
+// the file formats a map can be in, when a map has more than one the first is read
+pub const MAP_EXTENSIONS: &[&str] = &["txt", "tmx", "tmj", "json"];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Severity {
+    // the map still loads, it just may not play the way it was meant to
+    Warning,
+    // the map can't be loaded at all
+    Error,
+}
+
+// one problem with a map file, pointing at the line and column to fix it in
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MapDiagnostic {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for MapDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(
+            f,
+            "{}:{}:{}: {}: {}",
+            self.path.display(),
+            self.line,
+            self.column,
+            severity,
+            self.message
+        )
+    }
+}
+
+#[derive(Debug)]
+pub enum MapError {
+    Io(PathBuf, io::Error),
+    // every diagnostic found, the errors among them are why the map was refused
+    Invalid(Vec<MapDiagnostic>),
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MapError::Io(path, error) => {
+                write!(f, "{} could not be read: {}", path.display(), error)
+            }
+            MapError::Invalid(diagnostics) => {
+                let errors: Vec<&MapDiagnostic> = diagnostics
+                    .iter()
+                    .filter(|diagnostic| diagnostic.severity == Severity::Error)
+                    .collect();
+                match errors.first() {
+                    Some(error) if errors.len() > 1 => {
+                        write!(f, "{} (and {} more errors)", error, errors.len() - 1)
+                    }
+                    Some(error) => error.fmt(f),
+                    None => write!(f, "the map has no errors"),
+                }
+            }
+        }
+    }
+}
+
+// a map as it came out of its file, along with whatever it had to be warned about
+pub(crate) struct LoadedMap {
+    pub(crate) map_data: MapData,
+    pub(crate) warnings: Vec<MapDiagnostic>,
+}
+
+// the file a map is read from, a .txt file when there is none at all
+pub(crate) fn get_map_path(map_name: &str) -> PathBuf {
+    let directory = Path::new(MAPS_DIRECTORY);
+    MAP_EXTENSIONS
+        .iter()
+        .map(|extension| directory.join(format!("{}.{}", map_name, extension)))
+        .find(|path| path.exists())
+        .unwrap_or_else(|| directory.join(format!("{}.txt", map_name)))
+}
+
+// reads a map file, or a map drawn in Tiled, and its .meta sidecar, rows shorter than the
+// widest are padded with walls
+//
+// the spawn is where the player will be put, it is checked along with everything reachable
+// from it, without one the map's own player glyph is used if it has one
+pub(crate) fn load(path: &Path, spawn: Option<Vec2>) -> Result<LoadedMap, MapError> {
+    let contents =
+        fs::read_to_string(path).map_err(|error| MapError::Io(path.to_path_buf(), error))?;
+    let map_name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut loader = MapLoader {
+        path: path.to_path_buf(),
+        first_line: 1,
+        diagnostics: Vec::new(),
+    };
+    let tile_set = get_tile_set(&map_name);
+    let mut map_data = if tiled::is_tiled_map(path) {
+        let (map_data, errors) = tiled::import(&contents, path, tile_set);
+        for error in errors {
+            loader.error(error.position, error.message);
+        }
+        map_data
+    } else {
+        loader.parse_rows(&contents, tile_set)
+    };
+    let metadata_path = path.with_extension("meta");
+    let (metadata, metadata_errors) = MapMetadata::load(&metadata_path, map_data.get_size());
+    for error in metadata_errors {
+        loader.diagnostics.push(MapDiagnostic {
+            path: metadata_path.clone(),
+            line: error.line,
+            column: error.column,
+            severity: Severity::Error,
+            message: error.message,
+        });
+    }
+    metadata.apply_to(&mut map_data);
+    // a starting spot drawn into the map, e.g. by the editor or as a Tiled object, wins over the
+    // one it is loaded with
+    map_data.spawn = map_data
+        .find_player_position()
+        .or(map_data.spawn)
+        .or(spawn);
+
+    if !map_data.map.is_empty() {
+        match map_data.spawn {
+            Some(spawn) if loader.check_spawn(&map_data, spawn) => {
+                loader.check_reachability(&map_data, spawn);
+                loader.check_relocking_doors(&map_data, spawn);
+            }
+            Some(_) => {}
+            None => loader.warn(
+                Vec2::ZERO,
+                "the map has no starting position, so nothing was checked for being reachable"
+                    .to_string(),
+            ),
+        }
+    }
+
+    if loader
+        .diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error)
+    {
+        return Err(MapError::Invalid(loader.diagnostics));
+    }
+    Ok(LoadedMap {
+        map_data,
+        warnings: loader.diagnostics,
+    })
+}
+
+// everything wrong with a map file, as validate-maps reports it
+pub fn check(path: &Path) -> Result<Vec<MapDiagnostic>, MapError> {
+    match load(path, get_table_spawn(path)) {
+        Ok(loaded_map) => Ok(loaded_map.warnings),
+        Err(MapError::Invalid(diagnostics)) => Ok(diagnostics),
+        Err(error) => Err(error),
+    }
+}
+
+// a map file on its own, outside of any game, e.g. to see what an import turned into
+pub fn read_map(path: &Path) -> Result<MapData, MapError> {
+    load(path, get_table_spawn(path)).map(|loaded_map| loaded_map.map_data)
+}
+
+// the starting spot MAP_SPAWNS gives a map, found by its file name
+fn get_table_spawn(path: &Path) -> Option<Vec2> {
+    path.file_stem()
+        .and_then(|stem| map_manager::get_map_spawn(&stem.to_string_lossy()))
+}
+
+// every map file in a directory in name order, the .meta files are read along with their map
+pub fn list_map_files(directory: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        let extension = path.extension().map(|extension| extension.to_string_lossy());
+        if extension.is_some_and(|extension| MAP_EXTENSIONS.contains(&&*extension)) {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+fn get_tile_set(map_name: &str) -> TileSet {
+    if map_name == "scene_ladder" {
+        LADDER_TILE_SET
+    } else {
+        DEFAULT_TILE_SET
+    }
+}
+
+// every glyph a map drawn in this tile set may contain
+fn get_known_glyphs(tile_set: &TileSet) -> Vec<char> {
+    let mut glyphs = vec![
+        tile_set.player,
+        tile_set.wall,
+        tile_set.closed_door_side,
+        tile_set.closed_door_top,
+        tile_set.open_door,
+        tile_set.key,
+        tile_set.floor,
+        tile_set.lever,
+        tile_set.pressure_plate,
+        tile_set.chest,
+        tile_set.trap,
+        tile_set.breakable_wall,
+        tile_set.item_pile,
+        MONSTER_TILE_SET.snake,
+        MONSTER_TILE_SET.goblin,
+    ];
+    glyphs.extend(tile_set.ladder.chars());
+    glyphs
+}
+
+// how the reachability check treats a tile
+enum Passage {
+    Open,
+    Blocked,
+    // a closed door, passable once a key, a lockpick or a lever gets it open
+    Door,
+    // pulled by bumping into it, which opens whatever it targets
+    Lever,
+    // the middle of a ladder, climbed from the tile above or below it
+    Ladder,
+}
+
+struct MapLoader {
+    path: PathBuf,
+    // the file line of the first row, blank lines before the map don't count as rows
+    first_line: usize,
+    diagnostics: Vec<MapDiagnostic>,
+}
+
+impl MapLoader {
+    fn parse_rows(&mut self, contents: &str, tile_set: TileSet) -> MapData {
+        let mut lines: Vec<&str> = contents.lines().collect();
+        while lines.last().is_some_and(|line| line.trim().is_empty()) {
+            lines.pop();
+        }
+        let blank_line_count = lines
+            .iter()
+            .take_while(|line| line.trim().is_empty())
+            .count();
+        self.first_line = blank_line_count + 1;
+        let lines = &lines[blank_line_count..];
+
+        let mut map_data = MapData::new();
+        if lines.is_empty() {
+            self.error(Vec2::ZERO, "the map is empty".to_string());
+            return map_data;
+        }
+
+        let known_glyphs = get_known_glyphs(&tile_set);
+        let width = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        for (pos_y, line) in lines.iter().enumerate() {
+            let mut row: Vec<Space> = Vec::with_capacity(width);
+            for (pos_x, glyph) in line.chars().enumerate() {
+                if !known_glyphs.contains(&glyph) {
+                    self.error(
+                        Vec2::new(pos_x, pos_y),
+                        format!("'{}' is not a tile of the {}", glyph, tile_set.name),
+                    );
+                }
+                row.push(Space::new(glyph));
+            }
+
+            if row.len() < width {
+                self.warn(
+                    Vec2::new(row.len(), pos_y),
+                    format!(
+                        "the row is {} tiles wide, padded with walls to {}",
+                        row.len(),
+                        width
+                    ),
+                );
+                row.resize(width, Space::new(tile_set.wall));
+            }
+            map_data.map.push(row);
+        }
+
+        map_data.width = width;
+        map_data.height = map_data.map.len();
+        map_data.tile_set = tile_set;
+        map_data
+    }
+
+    fn check_spawn(&mut self, map_data: &MapData, spawn: Vec2) -> bool {
+        match map_data.get_space(spawn) {
+            None => self.error(
+                spawn,
+                format!(
+                    "the starting position {},{} is outside the map",
+                    spawn.x, spawn.y
+                ),
+            ),
+            Some(space) if !space.is_traversable && space.tile != map_data.tile_set.player => self
+                .error(
+                    spawn,
+                    format!(
+                        "the starting position {},{} is on '{}', which can't be walked on",
+                        spawn.x, spawn.y, space.tile
+                    ),
+                ),
+            Some(_) => return true,
+        }
+        false
+    }
+
+    // reports the ladders and doors the player can't get to from the spawn
+    fn check_reachability(&mut self, map_data: &MapData, spawn: Vec2) {
+        let ladders = find_ladders(map_data);
+        let Exploration {
+            reached,
+            closed_doors,
+            ..
+        } = explore(map_data, &ladders, spawn, None);
+
+        for door in closed_doors {
+            let lock = get_lock(map_data, door);
+            self.warn(
+                door,
+                format!(
+                    "the door can't be opened, no {} key can be reached and its lock can't be picked",
+                    lock.id
+                ),
+            );
+        }
+        for ladder in ladders {
+            let is_reached = reached.contains(&ladder)
+                || [ladder.y.wrapping_sub(1), ladder.y + 1]
+                    .into_iter()
+                    .any(|pos_y| reached.contains(&Vec2::new(ladder.x, pos_y)));
+            if !is_reached {
+                self.warn(
+                    ladder,
+                    format!(
+                        "the ladder can't be reached from the starting position {},{}",
+                        spawn.x, spawn.y
+                    ),
+                );
+            }
+        }
+    }
+
+    // a door that locks again behind the player takes the key that opened it, so whatever is
+    // behind it needs another way to open it again, like a plate, a lever or a second key
+    fn check_relocking_doors(&mut self, map_data: &MapData, spawn: Vec2) {
+        let ladders = find_ladders(map_data);
+        let reached = explore(map_data, &ladders, spawn, None).reached;
+        let mut locks: Vec<(&Vec2, &Lock)> = map_data.locks.iter().collect();
+        locks.sort_by_key(|(door, _)| **door);
+        for (door, lock) in locks {
+            // a lock that can be picked counts as open, like it does everywhere else
+            if !lock.relocks || lock.can_be_picked() || !reached.contains(door) {
+                continue;
+            }
+            let outside = explore(map_data, &ladders, spawn, Some(*door)).reached;
+            for neighbour in get_neighbours(map_data, *door) {
+                if outside.contains(&neighbour)
+                    || !matches!(get_passage(map_data, &ladders, neighbour), Passage::Open)
+                {
+                    continue;
+                }
+                let inside = explore(map_data, &ladders, neighbour, Some(*door));
+                let key_count = outside
+                    .iter()
+                    .chain(&inside.reached)
+                    .filter(|position| {
+                        map_data.map[position.y][position.x].tile == map_data.tile_set.key
+                    })
+                    .map(|position| get_key_id(map_data, *position))
+                    .filter(|key_id| lock.is_opened_by(key_id))
+                    .map(|key_id| if is_master_key(&key_id) { 2 } else { 1 })
+                    .sum::<usize>();
+                if key_count < 2 && !inside.opened_doors.contains(door) {
+                    self.warn(
+                        *door,
+                        format!(
+                            "the door locks again once the only {} key has been used on it, \
+                             nothing behind it opens it again",
+                            lock.id
+                        ),
+                    );
+                    break;
+                }
+            }
+        }
+    }
+
+    fn warn(&mut self, position: Vec2, message: String) {
+        self.add(position, Severity::Warning, message);
+    }
+
+    fn error(&mut self, position: Vec2, message: String) {
+        self.add(position, Severity::Error, message);
+    }
+
+    fn add(&mut self, position: Vec2, severity: Severity, message: String) {
+        self.diagnostics.push(MapDiagnostic {
+            path: self.path.clone(),
+            line: self.first_line + position.y,
+            column: position.x + 1,
+            severity,
+            message,
+        });
+    }
+}
+
+struct Exploration {
+    reached: BTreeSet<Vec2>,
+    // doors a plate or a lever along the way opens
+    opened_doors: BTreeSet<Vec2>,
+    // doors that were found but stayed shut
+    closed_doors: BTreeSet<Vec2>,
+}
+
+// everywhere the player could get to from the start, picking up keys and pulling levers along
+// the way, the held door is treated as a wall
+fn explore(
+    map_data: &MapData,
+    ladders: &BTreeSet<Vec2>,
+    start: Vec2,
+    held_door: Option<Vec2>,
+) -> Exploration {
+    let mut reached = BTreeSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+    let mut key_ids = BTreeSet::new();
+    let mut opened_doors = BTreeSet::new();
+    let mut closed_doors = BTreeSet::new();
+
+    loop {
+        while let Some(position) = queue.pop_front() {
+            let tile = map_data.map[position.y][position.x].tile;
+            if tile == map_data.tile_set.key {
+                key_ids.insert(get_key_id(map_data, position));
+            }
+            if let Some(object) = map_data.objects.get(&position) {
+                if let WorldObjectKind::PressurePlate = object.kind {
+                    opened_doors.extend(object.targets.iter().copied());
+                }
+            }
+
+            // diagonal steps can't squeeze past walls, so four ways reaches everything
+            for neighbour in get_neighbours(map_data, position) {
+                if reached.contains(&neighbour) || held_door == Some(neighbour) {
+                    continue;
+                }
+                match get_passage(map_data, ladders, neighbour) {
+                    Passage::Open => {}
+                    Passage::Door => {
+                        closed_doors.insert(neighbour);
+                        continue;
+                    }
+                    Passage::Lever => {
+                        if let Some(object) = map_data.objects.get(&neighbour) {
+                            opened_doors.extend(object.targets.iter().copied());
+                        }
+                        continue;
+                    }
+                    Passage::Blocked | Passage::Ladder => continue,
+                }
+                reached.insert(neighbour);
+                queue.push_back(neighbour);
+            }
+        }
+
+        // doors wait until everything else has been explored, a key may still turn up
+        let openable_doors: Vec<Vec2> = closed_doors
+            .iter()
+            .copied()
+            .filter(|door| opened_doors.contains(door) || can_open(map_data, *door, &key_ids))
+            .collect();
+        if openable_doors.is_empty() {
+            break;
+        }
+        for door in openable_doors {
+            closed_doors.remove(&door);
+            reached.insert(door);
+            queue.push_back(door);
+        }
+    }
+
+    Exploration {
+        reached,
+        opened_doors,
+        closed_doors,
+    }
+}
+
+// the middle tile of every ladder, where climbing it takes the player
+fn find_ladders(map_data: &MapData) -> BTreeSet<Vec2> {
+    let ladder: Vec<char> = map_data.tile_set.ladder.chars().collect();
+    let mut ladders = BTreeSet::new();
+    for (pos_y, row) in map_data.map.iter().enumerate() {
+        for (pos_x, tiles) in row.windows(ladder.len()).enumerate() {
+            if tiles
+                .iter()
+                .map(|space| space.tile)
+                .eq(ladder.iter().copied())
+            {
+                ladders.insert(Vec2::new(pos_x + ladder.len() / 2, pos_y));
+            }
+        }
+    }
+    ladders
+}
+
+fn get_passage(map_data: &MapData, ladders: &BTreeSet<Vec2>, position: Vec2) -> Passage {
+    let space = map_data.map[position.y][position.x];
+    let tile_set = &map_data.tile_set;
+    // in the ladder scene the rungs are its floor, everywhere else a ladder is an exit
+    if ladders.contains(&position) && !space.is_traversable {
+        return Passage::Ladder;
+    }
+    let is_ladder_side = ladders.contains(&Vec2::new(position.x.wrapping_sub(1), position.y))
+        || ladders.contains(&Vec2::new(position.x + 1, position.y));
+    if is_ladder_side && !ladders.contains(&position) {
+        return Passage::Blocked;
+    }
+
+    match map_data.objects.get(&position).map(|object| &object.kind) {
+        Some(WorldObjectKind::BreakableWall { .. }) => return Passage::Open,
+        Some(WorldObjectKind::Lever { .. }) => return Passage::Lever,
+        Some(WorldObjectKind::Chest { .. }) => return Passage::Blocked,
+        _ => {}
+    }
+
+    if tile_set.name == DEFAULT_TILE_SET.name
+        && (space.tile == tile_set.closed_door_side || space.tile == tile_set.closed_door_top)
+    {
+        return Passage::Door;
+    }
+    if space.is_traversable
+        || space.is_monster
+        || space.tile == tile_set.key
+        || space.tile == tile_set.item_pile
+        || space.tile == tile_set.player
+    {
+        return Passage::Open;
+    }
+    Passage::Blocked
+}
+
+fn get_neighbours(map_data: &MapData, position: Vec2) -> Vec<Vec2> {
+    let mut neighbours = Vec::new();
+    if position.x > 0 {
+        neighbours.push(Vec2::new(position.x - 1, position.y));
+    }
+    if position.y > 0 {
+        neighbours.push(Vec2::new(position.x, position.y - 1));
+    }
+    if position.x + 1 < map_data.width {
+        neighbours.push(Vec2::new(position.x + 1, position.y));
+    }
+    if position.y + 1 < map_data.height {
+        neighbours.push(Vec2::new(position.x, position.y + 1));
+    }
+    neighbours
+}
+
+fn get_key_id(map_data: &MapData, position: Vec2) -> String {
+    map_data
+        .key_ids
+        .get(&position)
+        .cloned()
+        .unwrap_or_else(|| RUSTY_LOCK_ID.to_string())
+}
+
+fn get_lock(map_data: &MapData, position: Vec2) -> Lock {
+    map_data
+        .locks
+        .get(&position)
+        .cloned()
+        .unwrap_or_else(|| Lock::rusty(map_data.map[position.y][position.x].tile))
+}
+
+// the player always starts with lockpicks, so a lock that can be picked counts as open
+fn can_open(map_data: &MapData, door: Vec2, key_ids: &BTreeSet<String>) -> bool {
+    let lock = get_lock(map_data, door);
+    lock.can_be_picked() || key_ids.iter().any(|key_id| lock.is_opened_by(key_id))
+}
@@ -1,27 +1,46 @@
 use crate::map_data::MapData;
+use crate::map_loader::{self, LoadedMap, MapDiagnostic, MapError, Severity};
+use crate::pathfinding::MovementMode;
 use crate::player::Player;
-use crate::space::Space;
-use crate::tile_set::{DEFAULT_TILE_SET, LADDER_TILE_SET};
+use crate::tile_set::DEFAULT_TILE_SET;
 use crate::{Map, MovementType};
 use crate::Vec2;
 
 use crate::chat::Chat;
 use crate::terrain_data::TerrainData;
-use futures::lock::Mutex;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io;
-use std::io::BufRead;
-use std::path::Path;
-use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+
+// maps load_map knows how to switch to
+pub const MAP_NAMES: &[&str] = &["scene_ladder", "map1", "test", "test_map", "map2", "map3"];
+// slot of the map built by MapFactory, load_map calls it "test"
+pub const GENERATED_MAP_INDEX: usize = 2;
+// where the player arrives on each map file, the generated map picks its own
+pub const MAP_SPAWNS: &[(&str, Vec2)] = &[
+    ("scene_ladder", Vec2::new(3, 2)),
+    ("map1", Vec2::new(5, 2)),
+    ("test_map", Vec2::new(10, 10)),
+    ("map2", Vec2::new(6, 2)),
+    ("map3", Vec2::new(1, 1)),
+];
+
+pub(crate) fn get_map_spawn(map_name: &str) -> Option<Vec2> {
+    MAP_SPAWNS
+        .iter()
+        .find(|(name, _)| *name == map_name)
+        .map(|(_, spawn)| *spawn)
+}
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct MapManager {
     maps: HashMap<usize, MapData>,
     pub current_map_index: usize,
     pub should_transition: bool,
     pub target_map: String,
     pub target_position: Vec2,
+    pub seed_phrase: String,
+    // the diagonals setting, maps can override it in their metadata
+    pub movement: MovementMode,
 }
 
 impl MapManager {
@@ -32,9 +51,18 @@ impl MapManager {
             should_transition: false,
             target_map: String::new(),
             target_position: Vec2::ZERO,
+            seed_phrase: "seedphrase".to_string(),
+            movement: MovementMode::FourWay,
         }
     }
 
+    // how the player and the monsters may move on the current map
+    pub(crate) fn get_movement(&self) -> MovementMode {
+        self.get_map(self.current_map_index)
+            .and_then(|map_data| map_data.movement)
+            .unwrap_or(self.movement)
+    }
+
     pub(crate) fn add_map(&mut self, map_index: usize, map: MapData) {
         self.maps.insert(map_index, map);
     }
@@ -58,7 +86,7 @@ impl MapManager {
     pub(crate) fn update_current_map(
         &mut self,
         terrain_data: TerrainData,
-        _chat_clone: &mut Arc<Mutex<Chat>>,
+        _chat_clone: &mut Chat,
     ) {
         let map_index = self.current_map_index;
         let map = self.get_map_mut(map_index).expect("map data");
@@ -67,50 +95,40 @@ impl MapManager {
         map.width += terrain_data.width_increase;
     }
 
+    // reads the map called map_name from src/maps through the map loader and returns what it
+    // warned about, the player goes to pos unless the map file has its own starting spot
     pub(crate) fn add_map_set_player_position(
         &mut self,
         player: &mut Player,
         map_name: &str,
-        pos: Vec2,
-    ) {
-        let mut map = "".to_owned();
-        let full_map_name = format!("src/maps/{}.txt", map_name);
-
-        map += "\n";
-        if let Ok(lines) = self.read_lines(&full_map_name) {
-            for line in lines {
-                if let Ok(tile) = line {
-                    map += &tile;
-                    map += "\n";
-                }
-            }
-        }
-        let map_lines: Vec<&str> = map.trim().lines().collect();
-        let mut new_map = MapData::new();
-        new_map.map = map_lines
-            .iter()
-            .map(|line| line.chars().map(Space::new).collect())
-            .collect();
-
+        pos: Option<Vec2>,
+    ) -> Result<Vec<MapDiagnostic>, MapError> {
+        let path = map_loader::get_map_path(map_name);
+        let LoadedMap {
+            map_data: mut new_map,
+            warnings,
+        } = map_loader::load(&path, pos)?;
+
+        // the loader only warns, validate-maps can do without a starting spot but a game can't
+        let Some(pos) = new_map.spawn else {
+            return Err(MapError::Invalid(vec![MapDiagnostic {
+                path,
+                line: 1,
+                column: 1,
+                severity: Severity::Error,
+                message: format!(
+                    "{} has no starting position, give it one in the map or in MAP_SPAWNS",
+                    map_name
+                ),
+            }]));
+        };
         player.position = pos;
         player.tile_below_player = DEFAULT_TILE_SET.floor;
         new_map.set_player_position(pos);
 
-        new_map.height = new_map.map.len();
-        new_map.width = if new_map.height > 0 {
-            new_map.map[0].len()
-        } else {
-            0
-        };
-
-        if map_name == "scene_ladder" {
-            new_map.tile_set = LADDER_TILE_SET;
-        } else {
-            new_map.tile_set = DEFAULT_TILE_SET;
-        }
-
         self.add_map(self.current_map_index, new_map);
         self.current_map_index += 1;
+        Ok(warnings)
     }
 
     pub(crate) fn add_generated_map(&mut self, generated_map: MapData) {
@@ -118,6 +136,10 @@ impl MapManager {
         self.current_map_index += 1;
     }
 
+    pub(crate) fn get_current_map(&self) -> &MapData {
+        self.get_map(self.current_map_index).expect("map data")
+    }
+
     pub(crate) fn get_mut_current_map(&mut self) -> &mut MapData {
         self.get_map_mut(self.current_map_index).expect("map data")
     }
@@ -127,28 +149,10 @@ impl MapManager {
         map_name: &str,
         _player_move: MovementType,
     ) -> Option<&mut MapData> {
-        if map_name == "scene_ladder" {
-            self.current_map_index = 0;
-        } else if map_name == "map1" {
-            self.current_map_index = 1;
-        } else if map_name == "test" {
-            self.current_map_index = 2;
-        } else if map_name == "test_map" {
-            self.current_map_index = 3;
-        } else if map_name == "map2" {
-            self.current_map_index = 4;
-        } else if map_name == "map3" {
-            self.current_map_index = 5;
+        if let Some(map_index) = MAP_NAMES.iter().position(|name| *name == map_name) {
+            self.current_map_index = map_index;
         }
 
         self.get_map_mut(self.current_map_index)
     }
-
-    fn read_lines<P>(&mut self, filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-    where
-        P: AsRef<Path>,
-    {
-        let file = File::open(filename)?;
-        Ok(io::BufReader::new(file).lines())
-    }
 }
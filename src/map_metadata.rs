@@ -0,0 +1,510 @@
+use crate::item::{Item, ITEM_NAMES};
+use crate::lock::{Lock, RUSTY_LOCK_ID};
+use crate::loot::LootTable;
+use crate::map_data::MapData;
+use crate::monster::{Monster, MONSTER_NAMES};
+use crate::pathfinding::MovementMode;
+use crate::world_object::{WorldObject, WorldObjectKind};
+use crate::Vec2;
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone)]
+pub struct LockMetadata {
+    pub position: Vec2,
+    pub id: String,
+    pub relocks: bool,
+    pub pick_chance: Option<f64>,
+}
+
+// optional sidecar next to each map file, e.g. src/maps/map2.meta
+//
+//   # comments start with a hash
+//   lock 5,3 red relock pick:40
+//   key 13,3 red
+//   lever 3,4 -> 10,5 11,5
+//   plate 2,2 -> 4,4
+//   chest 3,3 healing_potion scroll_of_teleport
+//   chest 4,3 loot:common_chest
+//   trap 4,4 hidden damage:5
+//   breakable 7,1 durability:6
+//   monster 6,2 goblin
+//   item 2,3 healing_potion
+//   movement eight
+//
+// doors and keys without an entry fall back to the rusty lock, maps without a movement line
+// follow the global diagonals setting
+#[derive(Clone, Default)]
+pub struct MapMetadata {
+    pub locks: Vec<LockMetadata>,
+    pub keys: Vec<(Vec2, String)>,
+    pub objects: Vec<(Vec2, WorldObject)>,
+    pub monsters: Vec<(Vec2, char)>,
+    pub items: Vec<(Vec2, String)>,
+    pub movement: Option<MovementMode>,
+}
+
+// a line of a .meta file that couldn't be used, the line and column are one based
+pub(crate) struct MetadataError {
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    pub(crate) message: String,
+}
+
+// what is wrong with a line and the column of the word it is about
+type LineError = (usize, String);
+
+const ENTRY_NAMES: &[&str] = &[
+    "lock",
+    "key",
+    "lever",
+    "plate",
+    "chest",
+    "trap",
+    "breakable",
+    "monster",
+    "item",
+    "movement",
+];
+
+impl MapMetadata {
+    pub(crate) fn new() -> Self {
+        MapMetadata::default()
+    }
+
+    // a map without a .meta file has nothing to report
+    pub(crate) fn load(path: &Path, map_size: Vec2) -> (Self, Vec<MetadataError>) {
+        match fs::read_to_string(path) {
+            Ok(contents) => MapMetadata::parse(&contents, map_size),
+            Err(_) => (MapMetadata::new(), Vec::new()),
+        }
+    }
+
+    // a line with a mistake in it is left out and reported, rather than e.g. quietly leaving a
+    // chest empty because of a misspelt item
+    pub(crate) fn parse(contents: &str, map_size: Vec2) -> (Self, Vec<MetadataError>) {
+        let mut metadata = MapMetadata::new();
+        let mut errors = Vec::new();
+
+        for (index, line) in contents.lines().enumerate() {
+            let words = split_words(line);
+            if words.first().is_none_or(|(_, word)| word.starts_with('#')) {
+                continue;
+            }
+            if let Err((column, message)) = metadata.parse_line(&words, map_size) {
+                errors.push(MetadataError {
+                    line: index + 1,
+                    column,
+                    message,
+                });
+            }
+        }
+
+        (metadata, errors)
+    }
+
+    fn parse_line(&mut self, words: &[(usize, &str)], map_size: Vec2) -> Result<(), LineError> {
+        let (name_column, name) = words[0];
+        if !ENTRY_NAMES.contains(&name) {
+            return Err((
+                name_column,
+                format!(
+                    "'{}' is not a metadata entry, use one of {}",
+                    name,
+                    ENTRY_NAMES.join(", ")
+                ),
+            ));
+        }
+        if name == "movement" {
+            self.movement = match words.get(1) {
+                Some((_, "four")) => Some(MovementMode::FourWay),
+                Some((_, "eight")) => Some(MovementMode::EightWay),
+                Some((column, word)) => {
+                    let message = format!("'{}' is not a movement, use four or eight", word);
+                    return Err((*column, message));
+                }
+                None => return Err((name_column, "movement needs four or eight".to_string())),
+            };
+            return Ok(());
+        }
+
+        let (position_column, position_word) = *words.get(1).ok_or((
+            name_column,
+            format!("{} needs a position, e.g. {} 3,4", name, name),
+        ))?;
+        let position = parse_map_position(position_column, position_word, map_size)?;
+        let arguments = &words[2..];
+
+        match name {
+            "lock" => {
+                let id = get_name_argument(arguments)
+                    .ok_or((position_column, "the lock needs an id, e.g. red".to_string()))?;
+                check_arguments(&arguments[1..], &["relock"], &["pick:"])?;
+                let pick_chance = parse_option(arguments, "pick:")?
+                    .map(|percent| (percent / 100.0).clamp(0.0, 1.0));
+                self.locks.push(LockMetadata {
+                    position,
+                    id: id.to_string(),
+                    relocks: has_flag(arguments, "relock"),
+                    pick_chance,
+                });
+            }
+            "key" => {
+                let id = get_name_argument(arguments)
+                    .ok_or((position_column, "the key needs the id of its lock".to_string()))?;
+                check_arguments(&arguments[1..], &[], &[])?;
+                self.keys.push((position, id.to_string()));
+            }
+            "lever" | "plate" => {
+                let kind = if name == "lever" {
+                    WorldObjectKind::Lever { is_on: false }
+                } else {
+                    WorldObjectKind::PressurePlate
+                };
+                let targets = parse_targets(position_column, arguments, map_size)?;
+                self.objects
+                    .push((position, WorldObject::new(kind).with_targets(targets)));
+            }
+            "chest" => {
+                let mut contents = Vec::new();
+                let mut loot_table = None;
+                for (column, word) in arguments {
+                    if let Some(table_name) = word.strip_prefix("loot:") {
+                        if LootTable::for_chest(table_name).is_none() {
+                            return Err((*column, format!("'{}' is not a loot table", table_name)));
+                        }
+                        loot_table = Some(table_name.to_string());
+                    } else if Item::from_name(word).is_some() {
+                        contents.push(word.to_string());
+                    } else {
+                        return Err((*column, get_unknown_item_message(word)));
+                    }
+                }
+                let object = WorldObject::new(WorldObjectKind::Chest {
+                    contents,
+                    loot_table,
+                    is_open: false,
+                });
+                self.objects.push((position, object));
+            }
+            "trap" => {
+                check_arguments(arguments, &["hidden"], &["damage:"])?;
+                let object = WorldObject::new(WorldObjectKind::Trap {
+                    damage: parse_option(arguments, "damage:")?.unwrap_or(5.0) as i32,
+                    is_hidden: has_flag(arguments, "hidden"),
+                });
+                self.objects.push((position, object));
+            }
+            "breakable" => {
+                check_arguments(arguments, &[], &["durability:"])?;
+                let object = WorldObject::new(WorldObjectKind::BreakableWall {
+                    durability: parse_option(arguments, "durability:")?.unwrap_or(6.0) as i32,
+                });
+                self.objects.push((position, object));
+            }
+            "monster" => {
+                let (column, monster_name) = *arguments.first().ok_or((
+                    position_column,
+                    format!("the monster needs a name, one of {}", MONSTER_NAMES.join(", ")),
+                ))?;
+                let tile = Monster::tile_from_name(monster_name).ok_or((
+                    column,
+                    format!(
+                        "'{}' is not a monster, use one of {}",
+                        monster_name,
+                        MONSTER_NAMES.join(", ")
+                    ),
+                ))?;
+                check_arguments(&arguments[1..], &[], &[])?;
+                self.monsters.push((position, tile));
+            }
+            _ => {
+                let (column, item_name) = *arguments
+                    .first()
+                    .ok_or((position_column, "the item needs a name, e.g. ration".to_string()))?;
+                if Item::from_name(item_name).is_none() {
+                    return Err((column, get_unknown_item_message(item_name)));
+                }
+                check_arguments(&arguments[1..], &[], &[])?;
+                self.items.push((position, item_name.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn apply_to(&self, map_data: &mut MapData) {
+        map_data.movement = self.movement;
+
+        for lock_metadata in &self.locks {
+            let position = lock_metadata.position;
+            if map_data.get_space(position).is_none() {
+                continue;
+            }
+            let closed_tile = map_data.get_closed_door_tile(position);
+            let mut lock = Lock::new(&lock_metadata.id, closed_tile);
+            lock.relocks = lock_metadata.relocks;
+            if let Some(pick_chance) = lock_metadata.pick_chance {
+                lock.pick_chance = pick_chance;
+            }
+            map_data.locks.insert(position, lock);
+        }
+
+        for (position, id) in &self.keys {
+            map_data.key_ids.insert(*position, id.clone());
+        }
+
+        for (position, object) in &self.objects {
+            if map_data.get_space(*position).is_none() {
+                continue;
+            }
+            map_data.objects.insert(*position, object.clone());
+            map_data.restore_object_tile(*position);
+        }
+
+        for (position, name) in &self.items {
+            if map_data.get_space(*position).is_some() {
+                map_data.add_to_item_pile(*position, Item::from_name(name).into_iter().collect());
+            }
+        }
+        map_data.monster_spawns.extend(self.monsters.iter().copied());
+    }
+
+    // everything about a map that its tiles can't show, the reverse of apply_to
+    pub(crate) fn from_map(map_data: &MapData) -> Self {
+        let mut metadata = MapMetadata::new();
+        metadata.movement = map_data.movement;
+
+        let default_pick_chance = Lock::rusty(' ').pick_chance;
+        for (position, lock) in &map_data.locks {
+            // a lock only shows up here once the door is used, a plain rusty one needs no line
+            let plain_lock = Lock {
+                is_locked: lock.is_locked,
+                ..Lock::rusty(lock.closed_tile)
+            };
+            if *lock == plain_lock {
+                continue;
+            }
+            let has_own_pick_chance = (lock.pick_chance - default_pick_chance).abs() > f64::EPSILON;
+            metadata.locks.push(LockMetadata {
+                position: *position,
+                id: lock.id.clone(),
+                relocks: lock.relocks,
+                pick_chance: has_own_pick_chance.then_some(lock.pick_chance),
+            });
+        }
+        for (position, id) in &map_data.key_ids {
+            if id != RUSTY_LOCK_ID {
+                metadata.keys.push((*position, id.clone()));
+            }
+        }
+        for (position, object) in &map_data.objects {
+            metadata.objects.push((*position, object.clone()));
+        }
+        for (position, items) in &map_data.item_piles {
+            for item in items {
+                if let Some(name) = item.get_metadata_name() {
+                    metadata.items.push((*position, name.to_string()));
+                }
+            }
+        }
+        metadata.monsters = map_data.monster_spawns.clone();
+
+        // the maps behind these are unordered, sorting keeps saving twice from shuffling lines
+        let reading_order = |position: &Vec2| (position.y, position.x);
+        metadata.locks.sort_by_key(|lock| reading_order(&lock.position));
+        metadata.keys.sort_by_key(|(position, _)| reading_order(position));
+        metadata.objects.sort_by_key(|(position, _)| reading_order(position));
+        metadata.items.sort_by_key(|(position, _)| reading_order(position));
+        metadata.monsters.sort_by_key(|(position, _)| reading_order(position));
+        metadata
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.locks.is_empty()
+            && self.keys.is_empty()
+            && self.objects.is_empty()
+            && self.monsters.is_empty()
+            && self.items.is_empty()
+            && self.movement.is_none()
+    }
+
+    // the same format parse reads
+    pub(crate) fn to_text(&self) -> String {
+        let position_text = |position: &Vec2| format!("{},{}", position.x, position.y);
+        let mut lines = Vec::new();
+
+        for lock in &self.locks {
+            let mut line = format!("lock {} {}", position_text(&lock.position), lock.id);
+            if lock.relocks {
+                line += " relock";
+            }
+            if let Some(pick_chance) = lock.pick_chance {
+                line += &format!(" pick:{}", (pick_chance * 100.0).round());
+            }
+            lines.push(line);
+        }
+        for (position, id) in &self.keys {
+            lines.push(format!("key {} {}", position_text(position), id));
+        }
+        for (position, object) in &self.objects {
+            let arguments = match &object.kind {
+                WorldObjectKind::Lever { .. } => "lever".to_string(),
+                WorldObjectKind::PressurePlate => "plate".to_string(),
+                WorldObjectKind::Chest {
+                    contents,
+                    loot_table,
+                    ..
+                } => {
+                    let mut words = vec!["chest".to_string()];
+                    words.extend(contents.iter().cloned());
+                    words.extend(loot_table.iter().map(|table| format!("loot:{}", table)));
+                    words.join(" ")
+                }
+                WorldObjectKind::Trap { damage, is_hidden } => {
+                    let hidden = if *is_hidden { " hidden" } else { "" };
+                    format!("trap{} damage:{}", hidden, damage)
+                }
+                WorldObjectKind::BreakableWall { durability } => {
+                    format!("breakable durability:{}", durability)
+                }
+            };
+            // the kind goes first and the position second, so split the kind back off
+            let (kind, rest) = arguments.split_once(' ').unwrap_or((&arguments, ""));
+            let mut line = format!("{} {}", kind, position_text(position));
+            if !rest.is_empty() {
+                line += &format!(" {}", rest);
+            }
+            if !object.targets.is_empty() {
+                let targets: Vec<String> = object.targets.iter().map(position_text).collect();
+                line += &format!(" -> {}", targets.join(" "));
+            }
+            lines.push(line);
+        }
+        for (position, tile) in &self.monsters {
+            let name = Monster::name_from_tile(*tile);
+            lines.push(format!("monster {} {}", position_text(position), name));
+        }
+        for (position, name) in &self.items {
+            lines.push(format!("item {} {}", position_text(position), name));
+        }
+        match self.movement {
+            Some(MovementMode::FourWay) => lines.push("movement four".to_string()),
+            Some(MovementMode::EightWay) => lines.push("movement eight".to_string()),
+            None => {}
+        }
+
+        lines.join("\n") + "\n"
+    }
+}
+
+pub(crate) fn parse_position(part: &str) -> Option<Vec2> {
+    let (x, y) = part.split_once(',')?;
+    Some(Vec2::new(x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
+
+// the words of a line along with the column each starts at
+fn split_words(line: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (index, character) in line.char_indices().chain([(line.len(), ' ')]) {
+        match (character.is_whitespace(), start) {
+            (false, None) => start = Some(index),
+            (true, Some(word_start)) => {
+                words.push((line[..word_start].chars().count() + 1, &line[word_start..index]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+fn parse_map_position(column: usize, word: &str, map_size: Vec2) -> Result<Vec2, LineError> {
+    let position = parse_position(word).ok_or((
+        column,
+        format!("'{}' is not a position, write it as x,y", word),
+    ))?;
+    if position.x >= map_size.x || position.y >= map_size.y {
+        return Err((
+            column,
+            format!("{} is outside the {}x{} map", word, map_size.x, map_size.y),
+        ));
+    }
+    Ok(position)
+}
+
+fn get_unknown_item_message(name: &str) -> String {
+    format!("'{}' is not an item, use one of {}", name, ITEM_NAMES.join(", "))
+}
+
+// the word naming what a lock or key belongs to, it comes first and isn't a flag or an option
+fn get_name_argument<'a>(arguments: &[(usize, &'a str)]) -> Option<&'a str> {
+    arguments
+        .first()
+        .map(|(_, word)| *word)
+        .filter(|word| *word != "relock" && !word.contains(':'))
+}
+
+fn has_flag(arguments: &[(usize, &str)], flag: &str) -> bool {
+    arguments.iter().any(|(_, word)| *word == flag)
+}
+
+// every word left over has to be one of the flags or start with one of the option prefixes
+fn check_arguments(
+    arguments: &[(usize, &str)],
+    flags: &[&str],
+    prefixes: &[&str],
+) -> Result<(), LineError> {
+    for (column, word) in arguments {
+        let is_known =
+            flags.contains(word) || prefixes.iter().any(|prefix| word.starts_with(prefix));
+        if !is_known {
+            let mut known: Vec<String> = flags.iter().map(|flag| flag.to_string()).collect();
+            known.extend(prefixes.iter().map(|prefix| format!("{}<number>", prefix)));
+            let message = if known.is_empty() {
+                format!("'{}' is one word too many", word)
+            } else {
+                format!("'{}' is not an option here, use {}", word, known.join(" or "))
+            };
+            return Err((*column, message));
+        }
+    }
+    Ok(())
+}
+
+// reads a "name:value" style option such as pick:40 or damage:5
+fn parse_option(arguments: &[(usize, &str)], prefix: &str) -> Result<Option<f64>, LineError> {
+    for (column, word) in arguments {
+        if let Some(value) = word.strip_prefix(prefix) {
+            return value
+                .parse::<f64>()
+                .map(Some)
+                .map_err(|_| (*column, format!("'{}' is not a number", value)));
+        }
+    }
+    Ok(None)
+}
+
+// the positions listed after "->" are the tiles an object activates
+fn parse_targets(
+    position_column: usize,
+    arguments: &[(usize, &str)],
+    map_size: Vec2,
+) -> Result<Vec<Vec2>, LineError> {
+    match arguments.first() {
+        Some((_, "->")) => {}
+        Some((column, word)) => {
+            return Err((*column, format!("expected -> before the targets, found '{}'", word)))
+        }
+        None => {
+            return Err((
+                position_column,
+                "nothing to open, list the targets after ->, e.g. -> 4,4".to_string(),
+            ))
+        }
+    }
+    arguments[1..]
+        .iter()
+        .map(|(column, word)| parse_map_position(*column, word, map_size))
+        .collect()
+}
@@ -0,0 +1,193 @@
+use crate::camera::Camera;
+use crate::hud::Rect;
+use crate::map_data::MapData;
+use crate::vec2::Vec2;
+
+// the panel crops around the player once the downscaled map gets bigger than this
+const MAX_WIDTH: usize = 24;
+const MAX_HEIGHT: usize = 8;
+
+const BRAILLE_BLANK: u32 = 0x2800;
+// dot bits of a braille character, indexed by [y][x] inside its 2x4 block of tiles
+const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum MinimapMode {
+    // one character per scale x scale tiles
+    Blocks(usize),
+    // one braille character per 2x4 tiles with a dot for every explored wall
+    Braille,
+}
+
+impl MinimapMode {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            MinimapMode::Blocks(_) => "blocks",
+            MinimapMode::Braille => "braille",
+        }
+    }
+
+    pub(crate) fn next(&self) -> MinimapMode {
+        match self {
+            MinimapMode::Blocks(_) => MinimapMode::Braille,
+            MinimapMode::Braille => MinimapMode::Blocks(3),
+        }
+    }
+
+    fn get_cell_size(&self) -> Vec2 {
+        match self {
+            MinimapMode::Blocks(scale) => Vec2::new((*scale).max(1), (*scale).max(1)),
+            MinimapMode::Braille => Vec2::new(2, 4),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Minimap {
+    pub mode: MinimapMode,
+}
+
+impl Minimap {
+    pub(crate) fn new(mode: MinimapMode) -> Self {
+        Minimap { mode }
+    }
+
+    // the viewport is the part of the map currently on screen, in map coordinates
+    pub(crate) fn get_lines(
+        &self,
+        map: &MapData,
+        player_position: Vec2,
+        viewport: Option<Rect>,
+    ) -> Vec<String> {
+        let cell_size = self.mode.get_cell_size();
+        let map_size = map.get_size();
+        let grid_size = Vec2::new(
+            map_size.x.div_ceil(cell_size.x),
+            map_size.y.div_ceil(cell_size.y),
+        );
+        let view_size = Vec2::new(grid_size.x.min(MAX_WIDTH), grid_size.y.min(MAX_HEIGHT));
+
+        let mut window = Camera::new(Vec2::ZERO);
+        window.follow(
+            Vec2::new(
+                player_position.x / cell_size.x,
+                player_position.y / cell_size.y,
+            ),
+            grid_size,
+            view_size,
+        );
+
+        (0..view_size.y)
+            .map(|view_y| {
+                (0..view_size.x)
+                    .map(|view_x| {
+                        let cell = window.to_map_position(view_x, view_y);
+                        let area = Rect::new(
+                            cell.x * cell_size.x,
+                            cell.y * cell_size.y,
+                            cell_size.x,
+                            cell_size.y,
+                        );
+                        self.get_cell(map, area, player_position, viewport)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn get_cell(
+        &self,
+        map: &MapData,
+        area: Rect,
+        player_position: Vec2,
+        viewport: Option<Rect>,
+    ) -> char {
+        let positions: Vec<Vec2> = (area.y..area.y + area.height)
+            .flat_map(|y| (area.x..area.x + area.width).map(move |x| Vec2::new(x, y)))
+            .collect();
+
+        if positions.contains(&player_position) {
+            return map.tile_set.player;
+        }
+        if positions
+            .iter()
+            .any(|position| map.is_explored(*position) && map.is_ladder_at(*position))
+        {
+            return '>';
+        }
+        if let Some(corner) = viewport.and_then(|viewport| Minimap::get_corner(viewport, area)) {
+            return corner;
+        }
+
+        let is_wall = |position: &Vec2| {
+            map.is_explored(*position)
+                && map
+                    .get_space(*position)
+                    .is_some_and(|space| !space.is_traversable && !space.is_monster)
+        };
+        let terrain = match self.mode {
+            MinimapMode::Blocks(_) => {
+                if positions.iter().any(is_wall) {
+                    map.tile_set.wall
+                } else if positions.iter().any(|position| map.is_explored(*position)) {
+                    map.tile_set.floor
+                } else {
+                    ' '
+                }
+            }
+            MinimapMode::Braille => {
+                let dots = positions
+                    .iter()
+                    .filter(|position| is_wall(position))
+                    .fold(0, |dots, position| {
+                        dots | BRAILLE_DOTS[position.y - area.y][position.x - area.x]
+                    });
+                if dots == 0 {
+                    ' '
+                } else {
+                    char::from_u32(BRAILLE_BLANK + dots).unwrap_or(' ')
+                }
+            }
+        };
+
+        // the viewport edges only show through where nothing has been explored yet
+        if terrain == ' ' && viewport.is_some_and(|viewport| Minimap::touches_edge(viewport, area))
+        {
+            return '·';
+        }
+        terrain
+    }
+
+    fn get_corner(viewport: Rect, area: Rect) -> Option<char> {
+        let right = viewport.x + viewport.width.saturating_sub(1);
+        let bottom = viewport.y + viewport.height.saturating_sub(1);
+        let contains = |x: usize, y: usize| {
+            (area.x..area.x + area.width).contains(&x)
+                && (area.y..area.y + area.height).contains(&y)
+        };
+
+        if contains(viewport.x, viewport.y) {
+            Some('┌')
+        } else if contains(right, viewport.y) {
+            Some('┐')
+        } else if contains(viewport.x, bottom) {
+            Some('└')
+        } else if contains(right, bottom) {
+            Some('┘')
+        } else {
+            None
+        }
+    }
+
+    fn touches_edge(viewport: Rect, area: Rect) -> bool {
+        let right = viewport.x + viewport.width.saturating_sub(1);
+        let bottom = viewport.y + viewport.height.saturating_sub(1);
+        let overlaps_x = area.x <= right && viewport.x < area.x + area.width;
+        let overlaps_y = area.y <= bottom && viewport.y < area.y + area.height;
+        let spans_x = |x: usize| (area.x..area.x + area.width).contains(&x);
+        let spans_y = |y: usize| (area.y..area.y + area.height).contains(&y);
+
+        (overlaps_y && (spans_x(viewport.x) || spans_x(right)))
+            || (overlaps_x && (spans_y(viewport.y) || spans_y(bottom)))
+    }
+}
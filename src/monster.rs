@@ -1,7 +1,12 @@
+use crate::scheduler::{Energy, NORMAL_SPEED};
 use crate::status::Status;
+use crate::tile_set::MONSTER_TILE_SET;
 use crate::Vec2;
+use serde::{Deserialize, Serialize};
 
-#[derive(Copy, Clone)]
+pub const MONSTER_NAMES: &[&str] = &["goblin", "snake"];
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
 pub struct Monster {
     pub id: i32,
     pub tile: char,
@@ -9,7 +14,8 @@ pub struct Monster {
     pub position: Vec2,
     pub tile_below: char,
     pub in_battle: bool,
-    pub is_alive: bool
+    pub is_alive: bool,
+    pub energy: Energy,
 }
 
 impl Monster {
@@ -21,7 +27,17 @@ impl Monster {
             position,
             tile_below: ' ',
             in_battle: false,
-            is_alive: true
+            is_alive: true,
+            energy: Energy::new_empty(Monster::get_speed(tile)),
+        }
+    }
+
+    // snakes only get a move in every other turn
+    fn get_speed(tile: char) -> i32 {
+        if tile == MONSTER_TILE_SET.snake {
+            NORMAL_SPEED / 2
+        } else {
+            NORMAL_SPEED
         }
     }
 
@@ -29,6 +45,28 @@ impl Monster {
         id
     }
 
+    pub fn get_name(&self) -> &'static str {
+        Monster::name_from_tile(self.tile)
+    }
+
+    pub(crate) fn name_from_tile(tile: char) -> &'static str {
+        if tile == MONSTER_TILE_SET.snake {
+            "snake"
+        } else if tile == MONSTER_TILE_SET.goblin {
+            "goblin"
+        } else {
+            "monster"
+        }
+    }
+
+    pub(crate) fn tile_from_name(name: &str) -> Option<char> {
+        match name {
+            "goblin" => Some(MONSTER_TILE_SET.goblin),
+            "snake" => Some(MONSTER_TILE_SET.snake),
+            _ => None,
+        }
+    }
+
     pub(crate) fn update_tile_below_monster(&mut self, tile: char) {
         self.tile_below = tile;
     }
@@ -1,5 +1,4 @@
-use std::collections::HashMap;
-use futures::lock::MutexGuard;
+use std::collections::BTreeMap;
 
 use crate::monster::Monster;
 use crate::monster_generator::MonsterFactory;
@@ -7,37 +6,60 @@ use crate::monster_generator::MonsterFactory;
 use crate::tile_set::{DEFAULT_TILE_SET, MONSTER_TILE_SET};
 use crate::Vec2;
 
+use crate::map_data::MapData;
 use crate::map_manager::MapManager;
 use rand::Rng;
 
 use crate::space::Space;
+use serde::{Deserialize, Serialize};
 
-type Monsters = HashMap<i32, Monster>;
+// kept in id order so monsters act in the same order every run, which a loaded game relies on
+type Monsters = BTreeMap<i32, Monster>;
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct MonsterManager {
+    // the monsters on the map being played, the only ones that move
     monsters: Monsters,
+    // which map that is, none until the manager first follows one
+    #[serde(default)]
+    map_index: Option<usize>,
+    // the monsters of every other map, waiting where they stood until the player comes back
+    #[serde(default)]
+    resting_monsters: BTreeMap<usize, Monsters>,
 }
 
 impl MonsterManager {
     pub(crate) fn new() -> Self {
 
         MonsterManager {
-            monsters: HashMap::<i32, Monster>::new()
+            monsters: BTreeMap::<i32, Monster>::new(),
+            map_index: None,
+            resting_monsters: BTreeMap::new(),
         }
     }
 
+    // swaps in the monsters of the given map when the player has changed maps, the ones left
+    // behind keep their places on their own map
+    pub(crate) fn follow_map(&mut self, map_index: usize) {
+        if let Some(current_index) = self.map_index.filter(|index| *index != map_index) {
+            let monsters = self.resting_monsters.remove(&map_index).unwrap_or_default();
+            let left_behind = std::mem::replace(&mut self.monsters, monsters);
+            self.resting_monsters.insert(current_index, left_behind);
+        }
+        self.map_index = Some(map_index);
+    }
+
     pub(crate) fn spawn_monsters(
         &mut self,
-        map_manager_clone: &mut MutexGuard<MapManager>,
+        map_manager_clone: &mut MapManager,
         mut monster_factory: MonsterFactory,
+        rng: &mut impl Rng,
     ) {
         let map_index = map_manager_clone.current_map_index;
         let map_data = map_manager_clone.get_map_mut(map_index).expect("map data");
         let map_height = map_data.height;
         let map_width = map_data.width;
 
-        let mut rng = rand::thread_rng();
         let mut spawn_one = false;
         let mut loop_limit = 0;
 
@@ -70,10 +92,26 @@ impl MonsterManager {
         }
     }
 
+    // the monsters a map's metadata places, skipped where something already stands
+    pub(crate) fn spawn_placed_monsters(&mut self, map_data: &mut MapData) {
+        for (position, monster_type) in map_data.monster_spawns.clone() {
+            if map_data
+                .get_space(position)
+                .is_some_and(|space| space.is_traversable)
+            {
+                self.spawn_monster(map_data, monster_type, position);
+            }
+        }
+    }
+
     pub(crate) fn get_monsters(self) -> Monsters {
         self.monsters
     }
 
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &Monster> {
+        self.monsters.values()
+    }
+
     pub(crate) fn get_monsters_mut(&mut self) -> &mut Monsters {
         &mut self.monsters
     }
@@ -95,6 +133,16 @@ impl MonsterManager {
         None
     }
     
+    // ids of despawned monsters are never handed out again
+    pub(crate) fn spawn_monster(&mut self, map_data: &mut MapData, monster_type: char, position: Vec2) -> i32 {
+        let id = self.monsters.keys().max().map_or(0, |id| id + 1);
+        let mut monster = Monster::new(monster_type, position, id);
+        monster.tile_below = map_data.map[position.y][position.x].tile;
+        map_data.map[position.y][position.x] = Space::new(monster.tile);
+        self.monsters.insert(id, monster);
+        id
+    }
+
     pub(crate) fn despawn(&mut self, monster_id: i32) {
         self.monsters.remove(&monster_id);
     }
@@ -0,0 +1,274 @@
+use crate::chat::{MessageCategory, Severity};
+use crate::tile_set::{TileSet, MONSTER_TILE_SET};
+use crossterm::style::Color;
+
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct CellStyle {
+    pub foreground: Option<Color>,
+    pub background: Option<Color>,
+    pub is_bold: bool,
+    pub is_dim: bool,
+    pub is_reversed: bool,
+}
+
+impl CellStyle {
+    pub const PLAIN: CellStyle = CellStyle {
+        foreground: None,
+        background: None,
+        is_bold: false,
+        is_dim: false,
+        is_reversed: false,
+    };
+
+    pub(crate) fn bold(mut self) -> Self {
+        self.is_bold = true;
+        self
+    }
+
+    // remembered but not currently visible tiles are drawn faded
+    pub(crate) fn dimmed(mut self) -> Self {
+        self.is_dim = true;
+        self.is_bold = false;
+        self
+    }
+
+    // swaps the colors, so it stands out in every palette including the one without colors
+    pub(crate) fn reversed(mut self) -> Self {
+        self.is_reversed = true;
+        self
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PaletteKind {
+    Default,
+    ColorblindSafe,
+    NoColor,
+}
+
+impl PaletteKind {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            PaletteKind::Default => "default",
+            PaletteKind::ColorblindSafe => "colorblind safe",
+            PaletteKind::NoColor => "no color",
+        }
+    }
+
+    pub(crate) fn next(&self) -> PaletteKind {
+        match self {
+            PaletteKind::Default => PaletteKind::ColorblindSafe,
+            PaletteKind::ColorblindSafe => PaletteKind::NoColor,
+            PaletteKind::NoColor => PaletteKind::Default,
+        }
+    }
+}
+
+// the handful of colors every palette has to fill in, tiles and messages pick from these roles
+//
+// only walls and traps get a background, walls so rooms read as solid blocks and traps so they
+// stand out from the floor around them, everything else keeps the terminal's own background
+#[derive(Copy, Clone, PartialEq, Debug)]
+struct PaletteColors {
+    wall: Option<Color>,
+    wall_background: Option<Color>,
+    floor: Option<Color>,
+    door: Option<Color>,
+    key: Option<Color>,
+    player: Option<Color>,
+    goblin: Option<Color>,
+    snake: Option<Color>,
+    danger: Option<Color>,
+    danger_background: Option<Color>,
+    object: Option<Color>,
+    item: Option<Color>,
+    combat: Option<Color>,
+    loot: Option<Color>,
+    warning: Option<Color>,
+    debug: Option<Color>,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Palette {
+    pub kind: PaletteKind,
+    colors: PaletteColors,
+}
+
+impl Palette {
+    pub(crate) fn new(kind: PaletteKind) -> Self {
+        let colors = match kind {
+            PaletteKind::Default => PaletteColors {
+                wall: Some(Color::Grey),
+                wall_background: Some(Color::DarkGrey),
+                floor: Some(Color::DarkGrey),
+                door: Some(Color::DarkYellow),
+                key: Some(Color::Yellow),
+                player: Some(Color::White),
+                goblin: Some(Color::Green),
+                snake: Some(Color::Magenta),
+                danger: Some(Color::Red),
+                danger_background: Some(Color::DarkRed),
+                object: Some(Color::Cyan),
+                item: Some(Color::Yellow),
+                combat: Some(Color::Red),
+                loot: Some(Color::Yellow),
+                warning: Some(Color::DarkYellow),
+                debug: Some(Color::DarkGrey),
+            },
+            // built from the Okabe-Ito set so no two roles rely on telling red from green
+            PaletteKind::ColorblindSafe => PaletteColors {
+                wall: Some(Color::Grey),
+                wall_background: Some(Color::DarkGrey),
+                floor: Some(Color::DarkGrey),
+                door: Some(Color::Rgb {
+                    r: 230,
+                    g: 159,
+                    b: 0,
+                }),
+                key: Some(Color::Rgb {
+                    r: 240,
+                    g: 228,
+                    b: 66,
+                }),
+                player: Some(Color::White),
+                goblin: Some(Color::Rgb {
+                    r: 86,
+                    g: 180,
+                    b: 233,
+                }),
+                snake: Some(Color::Rgb {
+                    r: 204,
+                    g: 121,
+                    b: 167,
+                }),
+                danger: Some(Color::Rgb {
+                    r: 213,
+                    g: 94,
+                    b: 0,
+                }),
+                danger_background: Some(Color::Rgb {
+                    r: 213,
+                    g: 94,
+                    b: 0,
+                }),
+                object: Some(Color::Rgb {
+                    r: 0,
+                    g: 114,
+                    b: 178,
+                }),
+                item: Some(Color::Rgb {
+                    r: 240,
+                    g: 228,
+                    b: 66,
+                }),
+                combat: Some(Color::Rgb {
+                    r: 213,
+                    g: 94,
+                    b: 0,
+                }),
+                loot: Some(Color::Rgb {
+                    r: 240,
+                    g: 228,
+                    b: 66,
+                }),
+                warning: Some(Color::Rgb {
+                    r: 230,
+                    g: 159,
+                    b: 0,
+                }),
+                debug: Some(Color::DarkGrey),
+            },
+            PaletteKind::NoColor => PaletteColors {
+                wall: None,
+                wall_background: None,
+                floor: None,
+                door: None,
+                key: None,
+                player: None,
+                goblin: None,
+                snake: None,
+                danger: None,
+                danger_background: None,
+                object: None,
+                item: None,
+                combat: None,
+                loot: None,
+                warning: None,
+                debug: None,
+            },
+        };
+
+        Palette { kind, colors }
+    }
+
+    fn style(color: Option<Color>) -> CellStyle {
+        CellStyle {
+            foreground: color,
+            ..CellStyle::PLAIN
+        }
+    }
+
+    fn style_on(foreground: Option<Color>, background: Option<Color>) -> CellStyle {
+        CellStyle {
+            background,
+            ..Palette::style(foreground)
+        }
+    }
+
+    pub(crate) fn monster_style(&self, monster: char) -> Option<CellStyle> {
+        if monster == MONSTER_TILE_SET.goblin {
+            Some(Palette::style(self.colors.goblin).bold())
+        } else if monster == MONSTER_TILE_SET.snake {
+            Some(Palette::style(self.colors.snake).bold())
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn tile_style(&self, tile: char, tile_set: &TileSet) -> CellStyle {
+        if let Some(style) = self.monster_style(tile) {
+            return style;
+        }
+
+        let colors = &self.colors;
+        if tile == tile_set.player {
+            Palette::style(colors.player).bold()
+        } else if tile == tile_set.wall || tile == tile_set.breakable_wall {
+            Palette::style_on(colors.wall, colors.wall_background)
+        } else if tile == tile_set.floor || tile == tile_set.pressure_plate {
+            Palette::style(colors.floor)
+        } else if tile == tile_set.closed_door_side
+            || tile == tile_set.closed_door_top
+            || tile == tile_set.open_door
+        {
+            Palette::style(colors.door)
+        } else if tile == tile_set.key {
+            Palette::style(colors.key).bold()
+        } else if tile == tile_set.trap {
+            Palette::style_on(colors.player, colors.danger_background).bold()
+        } else if tile == tile_set.lever || tile == tile_set.chest {
+            Palette::style(colors.object)
+        } else if tile == tile_set.item_pile {
+            Palette::style(colors.item).bold()
+        } else {
+            CellStyle::PLAIN
+        }
+    }
+
+    // the category picks the color, severity decides how loud it is
+    pub(crate) fn message_style(&self, category: MessageCategory, severity: Severity) -> CellStyle {
+        let style = match category {
+            MessageCategory::General => CellStyle::PLAIN,
+            MessageCategory::Combat => Palette::style(self.colors.combat),
+            MessageCategory::Loot => Palette::style(self.colors.loot),
+            MessageCategory::Warning => Palette::style(self.colors.warning),
+            MessageCategory::Debug => Palette::style(self.colors.debug).dimmed(),
+        };
+
+        match severity {
+            Severity::Info => style,
+            Severity::Notice => style.bold(),
+            Severity::Danger => Palette::style(self.colors.danger).bold(),
+        }
+    }
+}
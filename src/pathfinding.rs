@@ -3,6 +3,76 @@ use crate::vec2::Vec2;
 use rand::Rng;
 use std::collections::{BinaryHeap, HashMap, HashSet};
 use crate::Map;
+use serde::{Deserialize, Serialize};
+
+// costs are in tenths of a step so a diagonal can cost roughly the square root of two
+const STRAIGHT_COST: usize = 10;
+const DIAGONAL_COST: usize = 14;
+
+const STRAIGHT_DIRECTIONS: [(i32, i32); 4] = [(0, -1), (-1, 0), (0, 1), (1, 0)];
+const ALL_DIRECTIONS: [(i32, i32); 8] = [
+    (0, -1),
+    (-1, 0),
+    (0, 1),
+    (1, 0),
+    (-1, -1),
+    (1, -1),
+    (-1, 1),
+    (1, 1),
+];
+
+// whether diagonal steps are allowed, for the player and the monsters alike
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum MovementMode {
+    FourWay,
+    EightWay,
+}
+
+impl MovementMode {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            MovementMode::FourWay => "four-way",
+            MovementMode::EightWay => "eight-way",
+        }
+    }
+
+    pub(crate) fn get_directions(&self) -> &'static [(i32, i32)] {
+        match self {
+            MovementMode::FourWay => &STRAIGHT_DIRECTIONS,
+            MovementMode::EightWay => &ALL_DIRECTIONS,
+        }
+    }
+
+    // a diagonal step may round a single corner but not slip through a gap between two walls
+    pub(crate) fn can_step(&self, map: &Map, from: Vec2, dx: i32, dy: i32) -> bool {
+        if dx == 0 || dy == 0 {
+            return true;
+        }
+        if *self == MovementMode::FourWay {
+            return false;
+        }
+
+        let is_wall = |x: i32, y: i32| {
+            map.get(y as usize)
+                .and_then(|row| row.get(x as usize))
+                .is_none_or(|space| space.is_solid && !space.is_monster && !space.is_player)
+        };
+        let (x, y) = (from.x as i32, from.y as i32);
+        !(is_wall(x + dx, y) && is_wall(x, y + dy))
+    }
+
+    // octile distance for eight-way, manhattan for four-way, in the same tenths as the step costs
+    fn get_heuristic(&self, from: Vec2, to: Vec2) -> usize {
+        let dx = from.x.abs_diff(to.x);
+        let dy = from.y.abs_diff(to.y);
+        match self {
+            MovementMode::FourWay => STRAIGHT_COST * (dx + dy),
+            MovementMode::EightWay => {
+                STRAIGHT_COST * dx.max(dy) + (DIAGONAL_COST - STRAIGHT_COST) * dx.min(dy)
+            }
+        }
+    }
+}
 
 pub(crate) struct CostMapData {
     cost_map: HashMap<Vec2, usize>,
@@ -32,13 +102,18 @@ impl Pathfinding {
         }
     }
 
-    pub(crate) fn wander(current_monster_position: Vec2, map: &Map) -> Vec2 {
-        // define the directions (up, down, left, right)
-        let directions = [(0, -1), (-1, 0), (0, 1), (1, 0)];
+    pub(crate) fn wander(
+        current_monster_position: Vec2,
+        map: &Map,
+        movement: MovementMode,
+        rng: &mut impl Rng,
+    ) -> Vec2 {
+        // define the directions (up, down, left, right and the diagonals if allowed)
+        let directions = movement.get_directions();
 
         loop {
-            // generate a random number between 0 and 3 (inclusive)
-            let random_index = rand::thread_rng().gen_range(0..5);
+            // generate a random number between 0 and the number of directions (inclusive)
+            let random_index = rng.gen_range(0..directions.len() + 1);
 
             // make it possible to not move
             if random_index == 0 {
@@ -47,21 +122,31 @@ impl Pathfinding {
 
             // otherwise, pick a random direction to wander
             let (dx, dy) = directions[random_index-1]; // Subtract 1 to match the array index
-            let new_x = current_monster_position.x as i32 + dx;
-            let new_y = current_monster_position.y as i32 + dy;
-            let potential_position = Vec2::new(new_x as usize, new_y as usize);
-
-            if map[potential_position.y][potential_position.x].is_traversable {
+            if !movement.can_step(map, current_monster_position, dx, dy) {
+                continue;
+            }
+            let potential_position = Vec2::new(
+                current_monster_position.x.wrapping_add_signed(dx as isize),
+                current_monster_position.y.wrapping_add_signed(dy as isize),
+            );
+
+            // the generated map has no wall around it, so the edge of the map has to stop them too
+            let is_traversable = map
+                .get(potential_position.y)
+                .and_then(|row| row.get(potential_position.x))
+                .is_some_and(|space| space.is_traversable);
+            if is_traversable {
                 return potential_position;
             }
         }
     }
 
-    pub(crate) async fn find_shortest_path(
+    pub(crate) fn find_shortest_path(
         map: &Map,
         monster_start_position: Vec2,
         player_start_position: Vec2,
         radius: usize,
+        movement: MovementMode,
     ) -> Vec2 {
         // we build the cost map based on the type of tiles we want to accept as traversable, toggle bool for monsters
         let mut cost_map_data = Pathfinding::build_cost_map(
@@ -70,8 +155,8 @@ impl Pathfinding {
             player_start_position,
             false,
             radius,
-        )
-        .await;
+            movement,
+        );
         if cost_map_data.found_player {
             // found player whilst not ignoring other monster's looking for the player
             return Pathfinding::reconstruct_path(
@@ -80,8 +165,8 @@ impl Pathfinding {
                 &cost_map_data.cost_map,
                 map,
                 false,
-            )
-            .await;
+                movement,
+            );
         }
 
         // if we're here, than the monster couldn't find a traversable path to the player
@@ -92,24 +177,25 @@ impl Pathfinding {
             player_start_position,
             true,
             radius,
-        )
-        .await;
+            movement,
+        );
         Pathfinding::reconstruct_path(
             monster_start_position,
             player_start_position,
             &new_cost_map.cost_map,
             map,
             true,
+            movement,
         )
-        .await
     }
 
-    async fn build_cost_map(
+    fn build_cost_map(
         map: &Map,
         monster_start_position: Vec2,
         player_start_position: Vec2,
         ignore_monsters: bool,
         radius: usize,
+        movement: MovementMode,
     ) -> CostMapData {
         // create a map to store the cost of reaching each position
         let mut cost_map = HashMap::new();
@@ -148,8 +234,8 @@ impl Pathfinding {
                     player_start_position,
                     monster_start_position,
                     ignore_monsters,
+                    movement,
                 )
-                .await
             } else {
                 neighbours = Pathfinding::get_traversable_neighbours(
                     current_monster.position,
@@ -157,14 +243,17 @@ impl Pathfinding {
                     player_start_position,
                     monster_start_position,
                     ignore_monsters,
+                    movement,
                 )
-                .await
             }
 
             for neighbour_position in neighbours {
                 if !closed_set.contains(&neighbour_position) {
                     let tile = map[neighbour_position.y][neighbour_position.x];
-                    let movement_cost = tile.travel_cost;
+                    let is_diagonal = neighbour_position.x != current_monster.position.x
+                        && neighbour_position.y != current_monster.position.y;
+                    let step_cost = if is_diagonal { DIAGONAL_COST } else { STRAIGHT_COST };
+                    let movement_cost = tile.travel_cost * step_cost;
                     let tentative_cost = cost_map[&current_monster.position] + movement_cost;
 
                     // if this is the first time visiting the neighbour or the new cost is lower, update the cost map
@@ -173,7 +262,7 @@ impl Pathfinding {
                     {
                         // legit neighbour entry, with a lower value than previous so count towards the radius of the monster searching for the player
                         // calculate the distance between the current position and the player's position
-                        let distance_to_player = Pathfinding::calculate_distance(current_monster.position, player_start_position);
+                        let distance_to_player = Pathfinding::calculate_distance(current_monster.position, player_start_position, movement);
 
                         // check if the distance exceeds the desired radius
                         if distance_to_player > radius {
@@ -184,8 +273,8 @@ impl Pathfinding {
                         let heuristic_cost = Pathfinding::calculate_heuristic(
                             neighbour_position,
                             player_start_position,
-                        )
-                        .await;
+                            movement,
+                        );
 
                         let priority = tentative_cost + heuristic_cost;
 
@@ -203,29 +292,30 @@ impl Pathfinding {
         return CostMapData::new(HashMap::<Vec2, usize>::new(), false);
     }
 
-    fn calculate_distance(monster_position: Vec2, player_position: Vec2) -> usize {
-        // calculate the Manhattan distance (L1 distance) between the two positions
+    fn calculate_distance(monster_position: Vec2, player_position: Vec2, movement: MovementMode) -> usize {
+        // calculate the number of steps between the two positions, ignoring walls
         let dx = (player_position.x as isize - monster_position.x as isize).abs() as usize;
         let dy = (player_position.y as isize - monster_position.y as isize).abs() as usize;
-        let manhattan_distance = dx + dy;
 
-        return manhattan_distance;
+        match movement {
+            // manhattan distance (L1 distance)
+            MovementMode::FourWay => dx + dy,
+            // chebyshev distance, a diagonal step covers both axes at once
+            MovementMode::EightWay => dx.max(dy),
+        }
     }
 
-    async fn calculate_heuristic(node_position: Vec2, player_position: Vec2) -> usize {
-        // calculate the Manhattan distance (L1 distance) between the two positions
-        let dx = (player_position.x as isize - node_position.x as isize).abs() as usize;
-        let dy = (player_position.y as isize - node_position.y as isize).abs() as usize;
-        let manhattan_distance = dx + dy;
-        manhattan_distance
+    fn calculate_heuristic(node_position: Vec2, player_position: Vec2, movement: MovementMode) -> usize {
+        movement.get_heuristic(node_position, player_position)
     }
 
-    async fn reconstruct_path(
+    fn reconstruct_path(
         monster_position: Vec2,
         player_position: Vec2,
         cost_map: &HashMap<Vec2, usize>,
         map: &Map,
         ignore_monsters: bool,
+        movement: MovementMode,
     ) -> Vec2 {
         let mut current_position = player_position;
         let mut path = Vec::new();
@@ -242,8 +332,8 @@ impl Pathfinding {
                     player_position,
                     monster_position,
                     ignore_monsters,
-                )
-                .await;
+                    movement,
+                );
             } else {
                 neighbours = Pathfinding::get_traversable_neighbours(
                     current_position,
@@ -251,8 +341,8 @@ impl Pathfinding {
                     player_position,
                     monster_position,
                     ignore_monsters,
-                )
-                .await;
+                    movement,
+                );
             }
 
             // initialise min_cost with a high value
@@ -290,23 +380,30 @@ impl Pathfinding {
         }
     }
 
-    async fn get_traversable_neighbours(
+    fn get_traversable_neighbours(
         current_node_position: Vec2,
         map: &Map,
         player_pos: Vec2,
         monster_pos: Vec2,
         ignore_monsters: bool,
+        movement: MovementMode,
     ) -> Vec<Vec2> {
         let mut neighbours = Vec::new();
 
-        let directions = [(0, -1), (-1, 0), (0, 1), (1, 0)];
+        for (dx, dy) in movement.get_directions() {
+            if !movement.can_step(map, current_node_position, *dx, *dy) {
+                continue;
+            }
 
-        for (dx, dy) in directions.iter() {
             let new_x = current_node_position.x as i32 + dx;
             let new_y = current_node_position.y as i32 + dy;
 
-            if new_x >= 0 && new_x < map[0].len() as i32 && new_y >= 0 && new_y < map.len() as i32 {
-                let tile = &map[new_y as usize][new_x as usize];
+            // rows can differ in length once terrain has been generated, so check the row itself
+            let tile = usize::try_from(new_y)
+                .ok()
+                .zip(usize::try_from(new_x).ok())
+                .and_then(|(y, x)| map.get(y).and_then(|row| row.get(x)));
+            if let Some(tile) = tile {
                 let tile_pos = Vec2::new(new_x as usize, new_y as usize);
 
                 if tile.is_traversable {
@@ -333,4 +430,65 @@ impl Pathfinding {
 
         neighbours
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map_data::MapData;
+
+    fn can_step(movement: MovementMode, rows: &[&str], dx: i32, dy: i32) -> bool {
+        let map = MapData::from_rows(rows).map;
+        movement.can_step(&map, Vec2::new(0, 0), dx, dy)
+    }
+
+    #[test]
+    fn the_heuristic_counts_diagonals_at_their_own_cost() {
+        let origin = Vec2::new(2, 2);
+        let eight_way = |to| MovementMode::EightWay.get_heuristic(origin, to);
+        let four_way = |to| MovementMode::FourWay.get_heuristic(origin, to);
+
+        assert_eq!(eight_way(origin), 0);
+        assert_eq!(eight_way(Vec2::new(5, 2)), 30);
+        assert_eq!(eight_way(Vec2::new(3, 3)), 14);
+        assert_eq!(eight_way(Vec2::new(5, 3)), 34);
+        assert_eq!(eight_way(Vec2::new(0, 6)), 48);
+        assert_eq!(four_way(Vec2::new(5, 3)), 40);
+        assert_eq!(four_way(Vec2::new(0, 6)), 60);
+    }
+
+    #[test]
+    fn a_diagonal_rounds_one_corner_but_not_two() {
+        let eight_way = MovementMode::EightWay;
+        assert!(can_step(eight_way, &["..", ".."], 1, 1));
+        assert!(can_step(eight_way, &[".#", ".."], 1, 1));
+        assert!(can_step(eight_way, &["..", "#."], 1, 1));
+        assert!(!can_step(eight_way, &[".#", "#."], 1, 1));
+        // whoever stands in the way is not a wall, they move off again
+        assert!(can_step(eight_way, &[".g", "#."], 1, 1));
+        // nor are straight steps ever refused here
+        assert!(can_step(eight_way, &[".#", "#."], 1, 0));
+    }
+
+    #[test]
+    fn the_edge_of_the_map_counts_as_a_wall() {
+        let eight_way = MovementMode::EightWay;
+        assert!(!can_step(eight_way, &["..", ".."], -1, -1));
+        // only one side is off the map, whether the tile itself can be entered is up to the caller
+        assert!(can_step(eight_way, &["..", ".."], -1, 1));
+        assert!(!can_step(eight_way, &[".", "#"], 1, 1));
+    }
+
+    #[test]
+    fn four_way_movement_never_steps_diagonally() {
+        assert!(!can_step(MovementMode::FourWay, &["..", ".."], 1, 1));
+        assert!(can_step(MovementMode::FourWay, &["..", ".."], 0, 1));
+
+        let map = MapData::from_rows(&["g....", ".....", ".....", "...@."]).map;
+        let start = Vec2::new(0, 0);
+        let player = Vec2::new(3, 3);
+        let step = Pathfinding::find_shortest_path(&map, start, player, 10, MovementMode::FourWay);
+        assert_eq!(start.x.abs_diff(step.x) + start.y.abs_diff(step.y), 1);
+        let step = Pathfinding::find_shortest_path(&map, start, player, 10, MovementMode::EightWay);
+        assert_eq!(step, Vec2::new(1, 1));
+    }
+}
@@ -1,15 +1,21 @@
-use crossterm::event::KeyCode;
 
+use crate::equipment::{EquipError, Equipment, EquipmentSlot};
+use crate::action::Action;
 use crate::inventory::Inventory;
+use crate::item::Item;
+use crate::scheduler::{Energy, NORMAL_SPEED};
 use crate::status::Status;
 use crate::vec2::Vec2;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Player {
-    pub key_event: KeyCode,
-    pub previous_key_event: KeyCode,
+    // only ever the action being handled right now, so there is nothing to save
+    #[serde(skip)]
+    pub action: Option<Action>,
     pub key_state: bool,
     pub inventory: Inventory,
+    pub equipment: Equipment,
     pub status: Status,
     pub position: Vec2,
     pub previous_player_position: Vec2,
@@ -18,16 +24,18 @@ pub struct Player {
     pub multi_tile_below_player: bool,
     pub current_floor: usize,
     pub fog_of_war: bool,
+    pub is_god_mode: bool,
+    pub energy: Energy,
     pub is_alive: bool
 }
 
 impl Player {
     pub(crate) fn new() -> Self {
         Player {
-            key_event: KeyCode::Enter,
-            previous_key_event: KeyCode::Null,
+            action: None,
             key_state: false,
-            inventory: Inventory::new(),
+            inventory: Player::starting_inventory(),
+            equipment: Equipment::new(),
             status: Status::new(),
             position: Vec2::ZERO,
             previous_player_position: Vec2::ZERO,
@@ -35,11 +43,58 @@ impl Player {
             previous_tile_below_player: '.',
             multi_tile_below_player: false,
             current_floor: 0,
-            fog_of_war: true,
+            fog_of_war: false,
+            is_god_mode: false,
+            energy: Energy::new(NORMAL_SPEED),
             is_alive: true
         }
     }
 
+    // the rest of the gear is found in chests, or given with the console's give command
+    //
+    // the lockpicks are kept, the map loader counts doors that can be picked as open
+    fn starting_inventory() -> Inventory {
+        let mut inventory = Inventory::new();
+        inventory.add_item(Item::rusty_dagger());
+        inventory.add_item(Item::healing_potion());
+        inventory.add_item(Item::ration());
+        inventory.add_item(Item::lockpick());
+        inventory.add_item(Item::lockpick());
+        inventory
+    }
+
+    // base status with every equipped item's modifiers applied
+    pub fn get_effective_status(&self) -> Status {
+        self.status.with_modifiers(self.equipment.total_modifiers())
+    }
+
+    pub(crate) fn equip_item(&mut self, inventory_index: usize) -> Result<String, EquipError> {
+        let item = self
+            .inventory
+            .items
+            .get(inventory_index)
+            .ok_or(EquipError::NoSuchItem)?;
+        self.equipment.can_equip(item)?;
+
+        let item = self.inventory.take_item(inventory_index).expect("item");
+        let item_name = item.name.clone();
+        let previous_item = self.equipment.equip(item)?;
+
+        if let Some(previous_item) = previous_item {
+            let message = format!("You swap the {} for the {}.", previous_item.name, item_name);
+            self.inventory.add_item(previous_item);
+            return Ok(message);
+        }
+        Ok(format!("You equip the {}.", item_name))
+    }
+
+    pub(crate) fn unequip_item(&mut self, slot: EquipmentSlot) -> Result<String, EquipError> {
+        let item = self.equipment.unequip(slot)?;
+        let message = format!("You take off the {}.", item.name);
+        self.inventory.add_item(item);
+        Ok(message)
+    }
+
     pub(crate) fn update_tile_below_player(&mut self, tile: char) {
         self.tile_below_player = tile;
     }
@@ -0,0 +1,307 @@
+use crate::palette::CellStyle;
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::style::{
+    Attribute, Print, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor,
+};
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use crossterm::QueueableCommand;
+use std::io::{self, stdout, Write};
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Cell {
+    pub tile: char,
+    pub style: CellStyle,
+}
+
+impl Cell {
+    pub const EMPTY: Cell = Cell {
+        tile: ' ',
+        style: CellStyle::PLAIN,
+    };
+
+    pub(crate) fn new(tile: char, style: CellStyle) -> Self {
+        Cell { tile, style }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct FrameBuffer {
+    pub width: usize,
+    pub height: usize,
+    cells: Vec<Cell>,
+}
+
+impl FrameBuffer {
+    pub(crate) fn new(width: usize, height: usize) -> Self {
+        FrameBuffer {
+            width,
+            height,
+            cells: vec![Cell::EMPTY; width * height],
+        }
+    }
+
+    pub(crate) fn get(&self, x: usize, y: usize) -> Cell {
+        self.cells[y * self.width + x]
+    }
+
+    pub(crate) fn set(&mut self, x: usize, y: usize, cell: Cell) {
+        if x < self.width && y < self.height {
+            self.cells[y * self.width + x] = cell;
+        }
+    }
+
+    // writes the text starting at the position, anything past the right edge is clipped
+    pub(crate) fn put_str(&mut self, x: usize, y: usize, text: &str, style: CellStyle) {
+        for (offset, tile) in text.chars().enumerate() {
+            self.set(x + offset, y, Cell::new(tile, style));
+        }
+    }
+
+    // the characters of the frame without their styles, one line per row with the padding trimmed
+    pub fn get_text(&self) -> String {
+        let mut text = String::new();
+        for y in 0..self.height {
+            let line: String = (0..self.width).map(|x| self.get(x, y).tile).collect();
+            text += line.trim_end();
+            text += "\n";
+        }
+        text
+    }
+
+    // the runs of cells that differ from the previous frame, each as its start and its cells
+    pub(crate) fn get_changed_runs(
+        &self,
+        previous_frame: &FrameBuffer,
+    ) -> Vec<(usize, usize, Vec<Cell>)> {
+        let mut runs = Vec::new();
+        for y in 0..self.height {
+            let mut x = 0;
+            while x < self.width {
+                if self.get(x, y) == previous_frame.get(x, y) {
+                    x += 1;
+                    continue;
+                }
+
+                let start = x;
+                let mut cells = Vec::new();
+                while x < self.width && self.get(x, y) != previous_frame.get(x, y) {
+                    cells.push(self.get(x, y));
+                    x += 1;
+                }
+                runs.push((start, y, cells));
+            }
+        }
+        runs
+    }
+}
+
+// where finished frames end up, the game client draws the same way into any of them
+pub trait RenderTarget {
+    fn get_size(&self) -> (usize, usize);
+
+    fn present(&mut self, frame: FrameBuffer) -> io::Result<()>;
+
+    // the next frame is drawn in full, e.g. after something else has written to the screen
+    fn invalidate(&mut self) {}
+
+    fn enter(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn leave(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// keeps the last presented frame so only the cells that changed get written to the terminal
+pub struct TerminalTarget {
+    previous_frame: Option<FrameBuffer>,
+}
+
+impl TerminalTarget {
+    pub(crate) fn new() -> Self {
+        TerminalTarget {
+            previous_frame: None,
+        }
+    }
+
+    fn queue_style(stdout: &mut impl Write, style: CellStyle) -> io::Result<()> {
+        // start from a clean slate so attributes from the previous run never leak into this one
+        stdout.queue(SetAttribute(Attribute::Reset))?;
+        stdout.queue(ResetColor)?;
+        if let Some(foreground) = style.foreground {
+            stdout.queue(SetForegroundColor(foreground))?;
+        }
+        if let Some(background) = style.background {
+            stdout.queue(SetBackgroundColor(background))?;
+        }
+        if style.is_bold {
+            stdout.queue(SetAttribute(Attribute::Bold))?;
+        }
+        if style.is_dim {
+            stdout.queue(SetAttribute(Attribute::Dim))?;
+        }
+        if style.is_reversed {
+            stdout.queue(SetAttribute(Attribute::Reverse))?;
+        }
+        Ok(())
+    }
+}
+
+impl RenderTarget for TerminalTarget {
+    fn get_size(&self) -> (usize, usize) {
+        let (width, height) = crossterm::terminal::size().unwrap_or((80, 24));
+        (width as usize, height as usize)
+    }
+
+    fn enter(&mut self) -> io::Result<()> {
+        enable_raw_mode()?;
+        let mut stdout = stdout();
+        stdout.queue(EnterAlternateScreen)?;
+        stdout.queue(Hide)?;
+        stdout.flush()?;
+        self.invalidate();
+        Ok(())
+    }
+
+    fn leave(&mut self) -> io::Result<()> {
+        let mut stdout = stdout();
+        stdout.queue(Show)?;
+        stdout.queue(LeaveAlternateScreen)?;
+        stdout.flush()?;
+        disable_raw_mode()
+    }
+
+    fn invalidate(&mut self) {
+        self.previous_frame = None;
+    }
+
+    fn present(&mut self, frame: FrameBuffer) -> io::Result<()> {
+        let mut stdout = stdout();
+
+        let previous_frame = match self.previous_frame.take() {
+            Some(previous_frame)
+                if previous_frame.width == frame.width && previous_frame.height == frame.height =>
+            {
+                previous_frame
+            }
+            _ => {
+                stdout.queue(Clear(ClearType::All))?;
+                FrameBuffer::new(frame.width, frame.height)
+            }
+        };
+
+        // each run of changed cells costs a single cursor move,
+        // the style is only re-sent where it differs from the cell before it
+        for (x, y, cells) in frame.get_changed_runs(&previous_frame) {
+            stdout.queue(MoveTo(x as u16, y as u16))?;
+            let mut run = String::new();
+            let mut run_style = None;
+            for cell in cells {
+                if run_style != Some(cell.style) {
+                    if !run.is_empty() {
+                        stdout.queue(Print(std::mem::take(&mut run)))?;
+                    }
+                    TerminalTarget::queue_style(&mut stdout, cell.style)?;
+                    run_style = Some(cell.style);
+                }
+                run.push(cell.tile);
+            }
+            stdout.queue(Print(run))?;
+        }
+
+        stdout.queue(SetAttribute(Attribute::Reset))?;
+        stdout.queue(ResetColor)?;
+        stdout.flush()?;
+        self.previous_frame = Some(frame);
+        Ok(())
+    }
+}
+
+// a terminal of a fixed size that only keeps the last frame, for tests and anything else headless
+pub struct MemoryTarget {
+    pub width: usize,
+    pub height: usize,
+    pub last_frame: Option<FrameBuffer>,
+}
+
+impl MemoryTarget {
+    pub fn new(width: usize, height: usize) -> Self {
+        MemoryTarget {
+            width,
+            height,
+            last_frame: None,
+        }
+    }
+}
+
+impl RenderTarget for MemoryTarget {
+    fn get_size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn present(&mut self, frame: FrameBuffer) -> io::Result<()> {
+        self.last_frame = Some(frame);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(rows: &[&str]) -> FrameBuffer {
+        let mut frame = FrameBuffer::new(rows[0].len(), rows.len());
+        for (y, row) in rows.iter().enumerate() {
+            frame.put_str(0, y, row, CellStyle::PLAIN);
+        }
+        frame
+    }
+
+    fn get_runs(frame: &FrameBuffer, previous_frame: &FrameBuffer) -> Vec<(usize, usize, String)> {
+        frame
+            .get_changed_runs(previous_frame)
+            .into_iter()
+            .map(|(x, y, cells)| (x, y, cells.iter().map(|cell| cell.tile).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn an_unchanged_frame_writes_nothing() {
+        let previous_frame = frame(&["#####", "#.@.#", "#####"]);
+        assert!(get_runs(&previous_frame.clone(), &previous_frame).is_empty());
+    }
+
+    #[test]
+    fn only_the_changed_cells_are_written_one_run_at_a_time() {
+        let previous_frame = frame(&["#####", "#.@.#", "#####"]);
+        let next_frame = frame(&["#####", "#@.g#", "##.##"]);
+        assert_eq!(
+            get_runs(&next_frame, &previous_frame),
+            [(1, 1, "@.g".to_string()), (2, 2, ".".to_string())]
+        );
+
+        // runs stop at the end of the row instead of running on into the next one
+        let next_frame = frame(&["####.", ".!@.#", "#####"]);
+        assert_eq!(
+            get_runs(&next_frame, &previous_frame),
+            [(4, 0, ".".to_string()), (0, 1, ".!".to_string())]
+        );
+    }
+
+    #[test]
+    fn a_change_of_style_alone_is_written() {
+        let previous_frame = frame(&["abc"]);
+        let mut next_frame = previous_frame.clone();
+        let style = CellStyle {
+            is_bold: true,
+            ..CellStyle::PLAIN
+        };
+        next_frame.set(1, 0, Cell::new('b', style));
+
+        let runs = next_frame.get_changed_runs(&previous_frame);
+        assert_eq!(runs, [(1, 0, vec![Cell::new('b', style)])]);
+    }
+}
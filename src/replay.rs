@@ -0,0 +1,317 @@
+use crate::action::Action;
+use crate::game::{Game, GameConfig, GameEvent, StartError};
+use crate::map_editor::EditorInput;
+use crate::scheduler::TimingMode;
+use crate::vec2::Vec2;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+// bumped whenever the replay lines change meaning
+pub const REPLAY_VERSION: u64 = 1;
+
+// one event as it was applied, with the turn it happened on and a hash of the state it left behind
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ReplayEntry {
+    pub turn: u64,
+    pub event: GameEvent,
+    pub state_hash: u64,
+}
+
+// a whole run as the events that drove it, enough to rebuild it from a new game
+//
+//   # comments start with a hash
+//   version = 1
+//   seed = seedphrase
+//   timing = turn
+//   map = map2
+//   spawn = 4,2
+//   fog = off
+//   1 3f29a0c4d1e2b7a8 input move_right
+//   1 9be0417c55aa6d03 command give potion 2
+//   1 0c4e7d12f09b8a65 tick
+//
+// spawn is only there when the run didn't start on the map's own starting spot, and a run that
+// loads a save in between only plays back while that save is still the same
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Replay {
+    pub seed_phrase: String,
+    pub timing: TimingMode,
+    pub start_map: String,
+    pub spawn: Option<Vec2>,
+    pub fog_of_war: bool,
+    pub entries: Vec<ReplayEntry>,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(PathBuf, io::Error),
+    Parse { line: usize, message: String },
+    Incompatible(u64),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Io(path, error) => {
+                write!(f, "Replay {} could not be read: {}", path.display(), error)
+            }
+            ReplayError::Parse { line, message } => write!(f, "Replay line {}: {}", line, message),
+            ReplayError::Incompatible(version) => write!(
+                f,
+                "Replay is in format {}, this version plays format {}.",
+                version, REPLAY_VERSION
+            ),
+        }
+    }
+}
+
+// the first event that didn't leave the game the way it was recorded
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Divergence {
+    pub index: usize,
+    pub turn: u64,
+    pub event: GameEvent,
+    pub expected_hash: u64,
+    pub found_hash: u64,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Event {} ({}) on turn {} diverged, expected state {:016x} but got {:016x}.",
+            self.index + 1,
+            event_to_text(&self.event),
+            self.turn,
+            self.expected_hash,
+            self.found_hash
+        )
+    }
+}
+
+#[derive(Debug)]
+pub enum VerifyError {
+    // the game the replay starts from can't be set up any more, say a map file went missing
+    Start(StartError),
+    Diverged(Divergence),
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VerifyError::Start(error) => write!(f, "Replay could not start: {}", error),
+            VerifyError::Diverged(divergence) => divergence.fmt(f),
+        }
+    }
+}
+
+impl Replay {
+    pub fn read(path: &Path) -> Result<Self, ReplayError> {
+        let contents =
+            fs::read_to_string(path).map_err(|error| ReplayError::Io(path.to_path_buf(), error))?;
+        Replay::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, ReplayError> {
+        let default_config = GameConfig::default();
+        let mut replay = Replay {
+            seed_phrase: default_config.seed_phrase,
+            timing: default_config.timing,
+            start_map: default_config.start_map,
+            spawn: default_config.spawn,
+            fog_of_war: default_config.fog_of_war,
+            entries: Vec::new(),
+        };
+        let mut version = None;
+
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = |message: String| ReplayError::Parse {
+                line: index + 1,
+                message,
+            };
+
+            // event lines start with their turn, everything before them is the header
+            if line.starts_with(|character: char| character.is_ascii_digit()) {
+                if version.is_none() {
+                    return Err(error(
+                        "the version has to come before the events".to_string(),
+                    ));
+                }
+                replay.entries.push(parse_entry(line).map_err(error)?);
+                continue;
+            }
+
+            let (name, value) = match line.split_once('=') {
+                Some((name, value)) => (name.trim(), value.trim()),
+                None => return Err(error("expected name = value or an event".to_string())),
+            };
+            match name {
+                "version" => {
+                    let number = value
+                        .parse()
+                        .map_err(|_| error(format!("'{}' is not a version", value)))?;
+                    if number != REPLAY_VERSION {
+                        return Err(ReplayError::Incompatible(number));
+                    }
+                    version = Some(number);
+                }
+                "seed" => replay.seed_phrase = value.to_string(),
+                "map" => replay.start_map = value.to_string(),
+                "spawn" => {
+                    replay.spawn = Some(
+                        Vec2::from_text(value)
+                            .ok_or_else(|| error(format!("'{}' is not a position", value)))?,
+                    )
+                }
+                "fog" => {
+                    replay.fog_of_war = match value {
+                        "on" => true,
+                        "off" => false,
+                        _ => return Err(error(format!("fog is on or off, not '{}'", value))),
+                    }
+                }
+                "timing" => {
+                    replay.timing = TimingMode::from_name(value)
+                        .ok_or_else(|| error(format!("unknown timing '{}'", value)))?
+                }
+                _ => return Err(error(format!("unknown setting '{}'", name))),
+            }
+        }
+
+        if version.is_none() {
+            return Err(ReplayError::Parse {
+                line: contents.lines().count(),
+                message: "the replay has no version".to_string(),
+            });
+        }
+        Ok(replay)
+    }
+
+    // the new game every replay starts from
+    pub fn get_config(&self) -> GameConfig {
+        GameConfig {
+            seed_phrase: self.seed_phrase.clone(),
+            timing: self.timing,
+            start_map: self.start_map.clone(),
+            spawn: self.spawn,
+            fog_of_war: self.fog_of_war,
+            autosave: false,
+        }
+    }
+
+    // applies one recorded event and checks the game ended up where it did at recording time
+    pub fn play_entry(&self, index: usize, game: &mut Game) -> Result<(), Divergence> {
+        let entry = &self.entries[index];
+        game.step(entry.event.clone());
+
+        let found_hash = game.state_hash();
+        if game.turn() != entry.turn || found_hash != entry.state_hash {
+            return Err(Divergence {
+                index,
+                turn: entry.turn,
+                event: entry.event.clone(),
+                expected_hash: entry.state_hash,
+                found_hash,
+            });
+        }
+        Ok(())
+    }
+
+    // plays the whole run from a new game, stopping at the first event that turns out differently
+    pub fn verify(&self) -> Result<Game, VerifyError> {
+        let mut game = Game::new(self.get_config()).map_err(VerifyError::Start)?;
+        for index in 0..self.entries.len() {
+            self.play_entry(index, &mut game)
+                .map_err(VerifyError::Diverged)?;
+        }
+        Ok(game)
+    }
+}
+
+// writes each event the moment it happens, so a crash still leaves everything up to it behind
+pub struct ReplayWriter {
+    file: File,
+}
+
+impl ReplayWriter {
+    pub fn create(path: &Path, config: &GameConfig) -> io::Result<Self> {
+        let mut file = File::create(path)?;
+        writeln!(
+            file,
+            "# project aether replay, each event is: turn, state hash after it, event"
+        )?;
+        writeln!(file, "version = {}", REPLAY_VERSION)?;
+        writeln!(file, "seed = {}", config.seed_phrase)?;
+        writeln!(file, "timing = {}", config.timing.name())?;
+        writeln!(file, "map = {}", config.start_map)?;
+        if let Some(spawn) = config.spawn {
+            writeln!(file, "spawn = {},{}", spawn.x, spawn.y)?;
+        }
+        writeln!(
+            file,
+            "fog = {}",
+            if config.fog_of_war { "on" } else { "off" }
+        )?;
+        Ok(ReplayWriter { file })
+    }
+
+    // call after the game has taken the event, ignored events changed nothing and are left out
+    pub fn record(&mut self, game: &Game, event: &GameEvent) -> io::Result<()> {
+        writeln!(
+            self.file,
+            "{} {:016x} {}",
+            game.turn(),
+            game.state_hash(),
+            event_to_text(event)
+        )?;
+        self.file.flush()
+    }
+}
+
+fn event_to_text(event: &GameEvent) -> String {
+    match event {
+        GameEvent::Input(action) => format!("input {}", action.name()),
+        GameEvent::Command(line) => format!("command {}", line),
+        GameEvent::Tick => "tick".to_string(),
+        GameEvent::Edit(input) => format!("edit {}", input.to_text()),
+    }
+}
+
+fn parse_entry(line: &str) -> Result<ReplayEntry, String> {
+    let mut parts = line.splitn(4, ' ');
+    let turn = parts.next().unwrap_or("");
+    let turn = turn
+        .parse()
+        .map_err(|_| format!("'{}' is not a turn", turn))?;
+    let state_hash = parts.next().ok_or("the event has no state hash")?;
+    let state_hash = u64::from_str_radix(state_hash, 16)
+        .map_err(|_| format!("'{}' is not a state hash", state_hash))?;
+
+    let kind = parts.next().ok_or("the event has no kind")?;
+    let rest = parts.next().unwrap_or("");
+    let event = match kind {
+        "input" => {
+            GameEvent::Input(Action::from_name(rest).ok_or(format!("unknown action '{}'", rest))?)
+        }
+        "command" if !rest.is_empty() => GameEvent::Command(rest.to_string()),
+        "command" => return Err("the command is empty".to_string()),
+        "tick" => GameEvent::Tick,
+        "edit" => GameEvent::Edit(
+            EditorInput::from_text(rest).ok_or(format!("unknown editor input '{}'", rest))?,
+        ),
+        _ => return Err(format!("unknown event '{}'", kind)),
+    };
+
+    Ok(ReplayEntry {
+        turn,
+        event,
+        state_hash,
+    })
+}
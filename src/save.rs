@@ -0,0 +1,285 @@
+use crate::map_manager::MapManager;
+use crate::monster_manager::MonsterManager;
+use crate::player::Player;
+use crate::scheduler::TimingMode;
+use crate::vec2::Vec2;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const SAVE_DIRECTORY: &str = "saves";
+pub const AUTOSAVE_SLOT: &str = "autosave";
+pub const QUICKSAVE_SLOT: &str = "quicksave";
+
+// bumped whenever the layout of SaveData changes, together with a migration in MIGRATIONS
+// and a fixture of the old format in tests/fixtures/saves
+pub const SAVE_VERSION: u64 = 2;
+
+// upgrades a save in place from one format to the next
+type Migration = fn(&mut Value) -> Result<(), String>;
+
+// MIGRATIONS[n] turns a format n + 1 save into format n + 2, so any old save walks up the chain
+const MIGRATIONS: &[Migration] = &[migrate_explored_to_rows];
+
+#[derive(Debug)]
+pub enum SaveError {
+    InvalidSlot(String),
+    NotFound(String),
+    Io(String, io::Error),
+    Corrupted(String, String),
+    Incompatible(String, u64),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::InvalidSlot(slot) => write!(
+                f,
+                "'{}' can't be a save slot, use letters, digits, '-' and '_'.",
+                slot
+            ),
+            SaveError::NotFound(slot) => write!(f, "There is no save called '{}'.", slot),
+            SaveError::Io(slot, error) => write!(f, "Save '{}' could not be accessed: {}", slot, error),
+            SaveError::Corrupted(slot, reason) => write!(f, "Save '{}' is corrupted: {}", slot, reason),
+            SaveError::Incompatible(slot, version) if *version > SAVE_VERSION => write!(
+                f,
+                "Save '{}' is from a newer version of the game (format {}, this one reads {}).",
+                slot, version, SAVE_VERSION
+            ),
+            SaveError::Incompatible(slot, version) => write!(
+                f,
+                "Save '{}' is in an old format this version can't read (format {}, this one reads {}).",
+                slot, version, SAVE_VERSION
+            ),
+        }
+    }
+}
+
+// everything a game needs to carry on exactly where it stopped, the message log starts afresh
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SaveData {
+    pub(crate) version: u64,
+    pub(crate) turn: u64,
+    pub(crate) timing: TimingMode,
+    pub(crate) player: Player,
+    pub(crate) map_manager: MapManager,
+    pub(crate) monster_manager: MonsterManager,
+    pub(crate) rng: ChaCha8Rng,
+}
+
+// slots become file names, so they are kept to characters that are safe everywhere
+pub fn get_slot_path(slot: &str) -> Result<PathBuf, SaveError> {
+    let is_valid = !slot.is_empty()
+        && slot.chars().all(|character| {
+            character.is_ascii_alphanumeric() || character == '-' || character == '_'
+        });
+    if !is_valid {
+        return Err(SaveError::InvalidSlot(slot.to_string()));
+    }
+    Ok(PathBuf::from(SAVE_DIRECTORY).join(format!("{}.json", slot)))
+}
+
+pub fn list_slots() -> Vec<String> {
+    let entries = match fs::read_dir(SAVE_DIRECTORY) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut slots: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+        .collect();
+    slots.sort();
+    slots
+}
+
+pub(crate) fn write(slot: &str, data: &SaveData) -> Result<PathBuf, SaveError> {
+    let path = get_slot_path(slot)?;
+    let io_error = |error| SaveError::Io(slot.to_string(), error);
+
+    let text = serde_json::to_string_pretty(data)
+        .map_err(|error| SaveError::Io(slot.to_string(), io::Error::other(error)))?;
+    fs::create_dir_all(SAVE_DIRECTORY).map_err(io_error)?;
+
+    // write next to the real file first so a crash halfway through can't ruin the old save
+    let temporary_path = path.with_extension("json.tmp");
+    fs::write(&temporary_path, text).map_err(io_error)?;
+    fs::rename(&temporary_path, &path).map_err(io_error)?;
+    Ok(path)
+}
+
+pub(crate) fn read(slot: &str) -> Result<SaveData, SaveError> {
+    read_path(slot, &get_slot_path(slot)?)
+}
+
+pub(crate) fn read_file(path: &Path) -> Result<SaveData, SaveError> {
+    read_path(&path.display().to_string(), path)
+}
+
+fn read_path(slot: &str, path: &Path) -> Result<SaveData, SaveError> {
+    let text = fs::read_to_string(path).map_err(|error| match error.kind() {
+        io::ErrorKind::NotFound => SaveError::NotFound(slot.to_string()),
+        _ => SaveError::Io(slot.to_string(), error),
+    })?;
+    let corrupted = |reason: String| SaveError::Corrupted(slot.to_string(), reason);
+
+    // the version is checked on its own first so an old or newer save gets a clear message
+    // instead of whatever field happened to fail to parse
+    let mut value: Value =
+        serde_json::from_str(&text).map_err(|error| corrupted(error.to_string()))?;
+    let version = value
+        .get("version")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| corrupted("it has no format version".to_string()))?;
+    if version == 0 || version > SAVE_VERSION {
+        return Err(SaveError::Incompatible(slot.to_string(), version));
+    }
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version as usize - 1) {
+        let from_version = index as u64 + 1;
+        migration(&mut value).map_err(|reason| {
+            corrupted(format!(
+                "upgrading it from format {} failed, {}",
+                from_version, reason
+            ))
+        })?;
+        value["version"] = Value::from(from_version + 1);
+    }
+
+    let data: SaveData =
+        serde_json::from_value(value).map_err(|error| corrupted(error.to_string()))?;
+
+    let map = data
+        .map_manager
+        .get_map(data.map_manager.current_map_index)
+        .ok_or_else(|| corrupted("the current map is missing".to_string()))?;
+    if map.get_space(data.player.position).is_none() {
+        return Err(corrupted("the player is outside the map".to_string()));
+    }
+    Ok(data)
+}
+
+// fnv-1a over the save as json, written out here so the value stays the same across rust versions
+// and replays recorded by one build can be checked by the next
+pub(crate) fn hash(data: &SaveData) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    // going through a json value sorts every map by key, so the order things were inserted doesn't matter
+    let text = serde_json::to_value(data)
+        .map(|value| value.to_string())
+        .unwrap_or_default();
+    text.bytes().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
+
+// format 1 listed every explored tile as "x,y", format 2 draws them as rows like the map
+fn migrate_explored_to_rows(value: &mut Value) -> Result<(), String> {
+    let maps = value
+        .pointer_mut("/map_manager/maps")
+        .and_then(Value::as_object_mut)
+        .ok_or("it has no maps")?;
+
+    for (index, map) in maps.iter_mut() {
+        let explored = map
+            .get_mut("explored")
+            .ok_or(format!("map {} has no explored tiles", index))?;
+        let positions: HashSet<Vec2> = serde_json::from_value(explored.take())
+            .map_err(|error| format!("map {} has bad explored tiles: {}", index, error))?;
+        *explored = Value::from(explored_rows::to_rows(&positions));
+    }
+    Ok(())
+}
+
+// a map is saved as one string per row, the flags of each space follow from its tile
+pub(crate) mod tile_rows {
+    use crate::space::Space;
+    use crate::Map;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(map: &Map, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            map.iter()
+                .map(|row| row.iter().map(|space| space.tile).collect::<String>()),
+        )
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Map, D::Error> {
+        let rows = Vec::<String>::deserialize(deserializer)?;
+        Ok(rows
+            .iter()
+            .map(|row| row.chars().map(Space::new).collect())
+            .collect())
+    }
+}
+
+// explored tiles are saved as rows with an 'x' for each explored tile, which keeps saves small
+// and the same from one save to the next
+pub(crate) mod explored_rows {
+    use crate::vec2::Vec2;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::collections::HashSet;
+
+    const EXPLORED: char = 'x';
+    const UNEXPLORED: char = '.';
+
+    pub(crate) fn to_rows(explored: &HashSet<Vec2>) -> Vec<String> {
+        let width = explored
+            .iter()
+            .map(|position| position.x + 1)
+            .max()
+            .unwrap_or(0);
+        let height = explored
+            .iter()
+            .map(|position| position.y + 1)
+            .max()
+            .unwrap_or(0);
+        (0..height)
+            .map(|pos_y| {
+                (0..width)
+                    .map(|pos_x| {
+                        if explored.contains(&Vec2::new(pos_x, pos_y)) {
+                            EXPLORED
+                        } else {
+                            UNEXPLORED
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    pub(crate) fn serialize<S: Serializer>(
+        explored: &HashSet<Vec2>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(to_rows(explored))
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashSet<Vec2>, D::Error> {
+        let rows = Vec::<String>::deserialize(deserializer)?;
+        Ok(rows
+            .iter()
+            .enumerate()
+            .flat_map(|(pos_y, row)| {
+                row.chars()
+                    .enumerate()
+                    .filter(|(_, character)| *character == EXPLORED)
+                    .map(move |(pos_x, _)| Vec2::new(pos_x, pos_y))
+            })
+            .collect())
+    }
+}
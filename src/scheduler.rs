@@ -0,0 +1,168 @@
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+
+// every action costs the same, an actor at normal speed earns one action per turn
+pub const ACTION_COST: i32 = 100;
+pub const NORMAL_SPEED: i32 = 100;
+
+// real time splits each turn into a few ticks so fast and slow actors interleave smoothly
+const TICKS_PER_TURN: i32 = 5;
+const TICK_LENGTH: Duration = Duration::from_millis(100);
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Energy {
+    pub speed: i32,
+    pub amount: i32,
+}
+
+impl Energy {
+    // the player starts out able to act straight away
+    pub(crate) fn new(speed: i32) -> Self {
+        Energy {
+            speed,
+            amount: ACTION_COST,
+        }
+    }
+
+    // monsters earn their first move once the player has made theirs, a full bar on top of the
+    // first turn's gain would hand them two moves before the player's second
+    pub(crate) fn new_empty(speed: i32) -> Self {
+        Energy { speed, amount: 0 }
+    }
+
+    pub(crate) fn is_ready(&self) -> bool {
+        self.amount >= ACTION_COST
+    }
+
+    pub(crate) fn spend(&mut self) {
+        self.amount -= ACTION_COST;
+    }
+
+    pub(crate) fn gain(&mut self, amount: i32) {
+        self.amount += amount;
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum TimingMode {
+    // the world only moves once the player has acted
+    TurnBased,
+    // the world moves on fixed ticks whether the player acts or not
+    RealTime,
+}
+
+impl TimingMode {
+    pub const NAMES: &'static [&'static str] = &["turn", "realtime"];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TimingMode::TurnBased => "turn",
+            TimingMode::RealTime => "realtime",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<TimingMode> {
+        match name {
+            "turn" => Some(TimingMode::TurnBased),
+            "realtime" => Some(TimingMode::RealTime),
+            _ => None,
+        }
+    }
+}
+
+// decides when the world advances, the game loop asks it how long it may wait for input
+#[derive(Copy, Clone, Debug)]
+pub struct Scheduler {
+    pub mode: TimingMode,
+    next_tick: Instant,
+}
+
+impl Scheduler {
+    pub(crate) fn new(mode: TimingMode) -> Self {
+        Scheduler {
+            mode,
+            next_tick: Instant::now() + TICK_LENGTH,
+        }
+    }
+
+    pub(crate) fn set_mode(&mut self, mode: TimingMode) {
+        *self = Scheduler::new(mode);
+    }
+
+    // none means the loop can block until the next key press
+    pub(crate) fn get_wait_time(&self) -> Option<Duration> {
+        match self.mode {
+            TimingMode::TurnBased => None,
+            TimingMode::RealTime => Some(self.next_tick.saturating_duration_since(Instant::now())),
+        }
+    }
+
+    // true once per tick that has come due, a loop that fell far behind skips ahead rather than
+    // replaying every missed tick at once
+    pub(crate) fn take_tick(&mut self) -> bool {
+        let now = Instant::now();
+        if self.mode != TimingMode::RealTime || now < self.next_tick {
+            return false;
+        }
+
+        self.next_tick += TICK_LENGTH;
+        if self.next_tick < now {
+            self.next_tick = now + TICK_LENGTH;
+        }
+        true
+    }
+
+    // a turn-based step is a whole turn, a real-time tick is a slice of one
+    pub(crate) fn get_energy_gain(&self, speed: i32) -> i32 {
+        match self.mode {
+            TimingMode::TurnBased => speed,
+            TimingMode::RealTime => speed / TICKS_PER_TURN,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_tick_is_a_slice_of_a_turn() {
+        let turn_based = Scheduler::new(TimingMode::TurnBased);
+        let real_time = Scheduler::new(TimingMode::RealTime);
+        assert_eq!(turn_based.get_energy_gain(NORMAL_SPEED), ACTION_COST);
+        assert_eq!(
+            real_time.get_energy_gain(NORMAL_SPEED) * TICKS_PER_TURN,
+            ACTION_COST
+        );
+        assert_eq!(real_time.get_energy_gain(NORMAL_SPEED / 2), 10);
+    }
+
+    #[test]
+    fn only_real_time_waits_for_ticks() {
+        let mut turn_based = Scheduler::new(TimingMode::TurnBased);
+        assert_eq!(turn_based.get_wait_time(), None);
+        assert!(!turn_based.take_tick());
+
+        let mut real_time = Scheduler::new(TimingMode::RealTime);
+        assert!(real_time.get_wait_time().is_some_and(|wait| wait <= TICK_LENGTH));
+        // the first tick only comes due a tick from now
+        assert!(!real_time.take_tick());
+
+        for name in TimingMode::NAMES {
+            let mode = TimingMode::from_name(name).expect("a timing mode");
+            assert_eq!(mode.name(), *name);
+        }
+    }
+
+    #[test]
+    fn energy_is_spent_one_action_at_a_time() {
+        let mut energy = Energy::new(NORMAL_SPEED);
+        assert!(energy.is_ready());
+        energy.spend();
+        assert!(!energy.is_ready());
+        energy.gain(NORMAL_SPEED * 2);
+        energy.spend();
+        assert!(energy.is_ready());
+        assert!(!Energy::new_empty(NORMAL_SPEED).is_ready());
+    }
+}
@@ -23,11 +23,16 @@ impl Space {
             is_solid: tile == DEFAULT_TILE_SET.wall
                 || tile == DEFAULT_TILE_SET.closed_door_side
                 || tile == DEFAULT_TILE_SET.closed_door_top
+                || tile == DEFAULT_TILE_SET.lever
+                || tile == DEFAULT_TILE_SET.chest
+                || tile == DEFAULT_TILE_SET.breakable_wall
                 || tile == MONSTER_TILE_SET.snake
                 || tile == MONSTER_TILE_SET.goblin
                 || tile == DEFAULT_TILE_SET.player,
             is_traversable: tile == DEFAULT_TILE_SET.floor
                 || tile == DEFAULT_TILE_SET.open_door
+                || tile == DEFAULT_TILE_SET.pressure_plate
+                || tile == DEFAULT_TILE_SET.trap
                 || tile == LADDER_TILE_SET.floor,
             is_monster: tile == MONSTER_TILE_SET.snake || tile == MONSTER_TILE_SET.goblin,
             is_player: tile == MONSTER_TILE_SET.player,
@@ -1,6 +1,10 @@
-#[derive(Copy, Clone)]
+use crate::item::StatModifiers;
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
 pub struct Status {
     pub health: i32,
+    pub max_health: i32,
     pub str: i32,
     pub def: i32,
 }
@@ -9,24 +13,34 @@ impl Status {
     pub(crate) fn new() -> Self {
         Status {
             health: 100,
+            max_health: 100,
             str: 3,
             def: 1,
         }
     }
 
     pub(crate) fn new_monster(health: i32, str: i32, def: i32) -> Self {
-        Status { health, str, def }
+        Status {
+            health,
+            max_health: health,
+            str,
+            def,
+        }
     }
 
-    fn print_status(&mut self) {
-        println!("HP: {}", self.health);
-        println!("STR: {}", self.str);
-        println!("DEF: {}", self.def);
+    // base values stay untouched, equipment and other modifiers are layered on top
+    pub(crate) fn with_modifiers(&self, modifiers: StatModifiers) -> Status {
+        Status {
+            health: self.health,
+            max_health: self.max_health,
+            str: self.str + modifiers.str,
+            def: self.def + modifiers.def,
+        }
     }
 
-    pub(crate) fn get_status(&mut self) -> [String; 3] {
-        [
-            format!("HP: {}", self.health),
+    pub fn get_status(&self) -> Vec<String> {
+        vec![
+            format!("HP: {}/{}", self.health, self.max_health),
             format!("STR: {}", self.str),
             format!("DEF: {}", self.def),
         ]
@@ -1,3 +1,5 @@
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
 #[derive(Clone)]
 pub struct TileSet {
     pub player: char,
@@ -8,6 +10,12 @@ pub struct TileSet {
     pub key: char,
     pub floor: char,
     pub ladder: &'static str,
+    pub lever: char,
+    pub pressure_plate: char,
+    pub chest: char,
+    pub trap: char,
+    pub breakable_wall: char,
+    pub item_pile: char,
     pub previous_tile: char,
     pub name: &'static str,
 }
@@ -26,6 +34,12 @@ impl TileSet {
         self.key = other.key;
         self.floor = other.floor;
         self.ladder = other.ladder;
+        self.lever = other.lever;
+        self.pressure_plate = other.pressure_plate;
+        self.chest = other.chest;
+        self.trap = other.trap;
+        self.breakable_wall = other.breakable_wall;
+        self.item_pile = other.item_pile;
         self.previous_tile = other.previous_tile;
         self.name = other.name;
     }
@@ -40,6 +54,12 @@ pub const DEFAULT_TILE_SET: TileSet = TileSet {
     key: 'k',
     floor: '.',
     ladder: &"|-|",
+    lever: '!',
+    pressure_plate: '_',
+    chest: '=',
+    trap: '^',
+    breakable_wall: '%',
+    item_pile: '*',
     previous_tile: '.',
     name: &"Default Tile Set",
 };
@@ -53,10 +73,33 @@ pub const LADDER_TILE_SET: TileSet = TileSet {
     key: ' ',
     floor: '-',
     ladder: &"|-|",
+    lever: '#',
+    pressure_plate: '#',
+    chest: '#',
+    trap: '#',
+    breakable_wall: '#',
+    item_pile: '*',
     previous_tile: ' ',
     name: &"Ladder Tile Set",
 };
 
+// a map only refers to its tile set by name, the glyphs themselves live in the constants above
+impl Serialize for TileSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name)
+    }
+}
+
+impl<'de> Deserialize<'de> for TileSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        [DEFAULT_TILE_SET, LADDER_TILE_SET]
+            .into_iter()
+            .find(|tile_set| tile_set.name == name)
+            .ok_or_else(|| de::Error::custom(format!("unknown tile set '{}'", name)))
+    }
+}
+
 pub struct MonsterTileSet {
     pub player: char,
     pub snake: char,
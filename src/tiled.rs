@@ -0,0 +1,928 @@
+// imports maps drawn in the Tiled editor, both the .tmx and the .tmj/.json formats
+//
+// tiles are matched to ours by the class (type in older versions of Tiled) given to them in the
+// tileset, e.g. floor, wall or door_side, see TILE_CLASSES for all of them. where several tile
+// layers overlap the topmost tile wins
+//
+// objects are matched by class too:
+//
+//   spawn      where the player arrives
+//   exit       a ladder, centred on the object
+//   monster    named after the monster, e.g. goblin
+//   item       named after the item, e.g. healing_potion
+//
+// the errors point at the row and column of the tile, only a file that can't be read at all
+// points at the line and column in the file
+use crate::item::{Item, ITEM_NAMES};
+use crate::map_data::MapData;
+use crate::monster::{Monster, MONSTER_NAMES};
+use crate::space::Space;
+use crate::tile_set::TileSet;
+use crate::vec2::Vec2;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+// every tile class an imported tileset may use
+pub const TILE_CLASSES: &[&str] = &[
+    "floor",
+    "wall",
+    "door_side",
+    "door_top",
+    "open_door",
+    "key",
+    "lever",
+    "plate",
+    "chest",
+    "trap",
+    "breakable",
+    "ladder_left",
+    "ladder_middle",
+    "ladder_right",
+];
+
+const OBJECT_CLASSES: &[&str] = &["spawn", "exit", "monster", "item"];
+
+// the top bits of a tile id say how it is flipped, which doesn't matter for a grid of glyphs
+const FLIP_FLAGS: u32 = 0xF000_0000;
+
+// what went wrong and where, the position is zero based like a tile's
+pub(crate) struct TiledError {
+    pub(crate) position: Vec2,
+    pub(crate) message: String,
+}
+
+impl TiledError {
+    fn new(position: Vec2, message: String) -> Self {
+        TiledError { position, message }
+    }
+
+    // for problems with the file as a whole rather than any one tile
+    fn at_start(message: String) -> Self {
+        TiledError::new(Vec2::ZERO, message)
+    }
+}
+
+pub(crate) fn is_tiled_map(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| ["tmx", "tmj", "json"].contains(&&*extension.to_string_lossy()))
+}
+
+// the map as far as Tiled is concerned, both file formats are read into this
+struct TiledMap {
+    orientation: String,
+    is_infinite: bool,
+    width: usize,
+    height: usize,
+    tile_width: f64,
+    tile_height: f64,
+    tilesets: Vec<Tileset>,
+    tile_layers: Vec<TileLayer>,
+    objects: Vec<MapObject>,
+}
+
+struct Tileset {
+    first_gid: u32,
+    name: String,
+    // the class of each tile by its id within the tileset
+    classes: HashMap<u32, String>,
+}
+
+struct TileLayer {
+    name: String,
+    gids: Vec<u32>,
+}
+
+struct MapObject {
+    id: u32,
+    class: String,
+    name: String,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    // tile objects hang up from their position rather than down
+    is_tile: bool,
+}
+
+// reads a Tiled map into the tiles of the given tile set, what couldn't be matched comes back as
+// errors and is filled with walls so the rest of the map can still be checked
+pub(crate) fn import(contents: &str, path: &Path, tile_set: TileSet) -> (MapData, Vec<TiledError>) {
+    let directory = path.parent().unwrap_or(Path::new(""));
+    let is_tmx = path.extension().is_some_and(|extension| extension == "tmx");
+    let tiled_map = if is_tmx {
+        read_tmx(contents, directory)
+    } else {
+        read_json(contents, directory)
+    };
+
+    let mut map_data = MapData::new();
+    map_data.tile_set = tile_set;
+    match tiled_map {
+        Ok(tiled_map) => {
+            let errors = build(&tiled_map, &mut map_data);
+            (map_data, errors)
+        }
+        Err(error) => (map_data, vec![error]),
+    }
+}
+
+fn build(tiled_map: &TiledMap, map_data: &mut MapData) -> Vec<TiledError> {
+    if tiled_map.orientation != "orthogonal" {
+        return vec![TiledError::at_start(format!(
+            "the map is {}, only orthogonal maps can be imported",
+            tiled_map.orientation
+        ))];
+    }
+    if tiled_map.is_infinite {
+        return vec![TiledError::at_start(
+            "the map is infinite, give it a fixed size in the map properties".to_string(),
+        )];
+    }
+    if tiled_map.width == 0 || tiled_map.height == 0 || tiled_map.tile_layers.is_empty() {
+        return vec![TiledError::at_start("the map has no tiles".to_string())];
+    }
+
+    let mut errors = Vec::new();
+    let cell_count = tiled_map.width * tiled_map.height;
+    for layer in &tiled_map.tile_layers {
+        if layer.gids.len() != cell_count {
+            errors.push(TiledError::at_start(format!(
+                "layer '{}' has {} tiles, a {}x{} map needs {}",
+                layer.name,
+                layer.gids.len(),
+                tiled_map.width,
+                tiled_map.height,
+                cell_count
+            )));
+        }
+    }
+    if !errors.is_empty() {
+        return errors;
+    }
+
+    // the same bad tile tends to be painted all over, so each problem is reported once where it
+    // first shows up along with how often it does
+    let mut problems: Vec<(String, Vec2, usize)> = Vec::new();
+    let tile_set = map_data.tile_set.clone();
+    for pos_y in 0..tiled_map.height {
+        let mut row = Vec::with_capacity(tiled_map.width);
+        for pos_x in 0..tiled_map.width {
+            let index = pos_y * tiled_map.width + pos_x;
+            let gid = tiled_map
+                .tile_layers
+                .iter()
+                .rev()
+                .map(|layer| layer.gids[index] & !FLIP_FLAGS)
+                .find(|gid| *gid != 0);
+            let tile = match gid {
+                Some(gid) => get_tile(tiled_map, gid, &tile_set),
+                None => Err("nothing is drawn here on any tile layer".to_string()),
+            };
+            let tile = tile.unwrap_or_else(|message| {
+                match problems
+                    .iter_mut()
+                    .find(|(problem, ..)| *problem == message)
+                {
+                    Some((_, _, count)) => *count += 1,
+                    None => problems.push((message, Vec2::new(pos_x, pos_y), 1)),
+                }
+                tile_set.wall
+            });
+            row.push(Space::new(tile));
+        }
+        map_data.map.push(row);
+    }
+    map_data.width = tiled_map.width;
+    map_data.height = tiled_map.height;
+
+    for (message, position, count) in problems {
+        let message = if count > 1 {
+            format!("{} (on {} tiles)", message, count)
+        } else {
+            message
+        };
+        errors.push(TiledError::new(position, message));
+    }
+    for object in &tiled_map.objects {
+        if let Err(error) = place_object(tiled_map, object, map_data) {
+            errors.push(error);
+        }
+    }
+    errors
+}
+
+fn get_tile(tiled_map: &TiledMap, gid: u32, tile_set: &TileSet) -> Result<char, String> {
+    let tileset = tiled_map
+        .tilesets
+        .iter()
+        .filter(|tileset| tileset.first_gid <= gid)
+        .max_by_key(|tileset| tileset.first_gid)
+        .ok_or(format!("tile {} is in none of the map's tilesets", gid))?;
+    let id = gid - tileset.first_gid;
+    let class = match tileset.classes.get(&id) {
+        Some(class) if !class.is_empty() => class,
+        _ => {
+            return Err(format!(
+                "tile {} of tileset '{}' has no class, give it one of {}",
+                id,
+                tileset.name,
+                TILE_CLASSES.join(", ")
+            ))
+        }
+    };
+
+    let ladder: Vec<char> = tile_set.ladder.chars().collect();
+    let tile = match class.as_str() {
+        "floor" => tile_set.floor,
+        "wall" => tile_set.wall,
+        "door_side" => tile_set.closed_door_side,
+        "door_top" => tile_set.closed_door_top,
+        "open_door" => tile_set.open_door,
+        "key" => tile_set.key,
+        "lever" => tile_set.lever,
+        "plate" => tile_set.pressure_plate,
+        "chest" => tile_set.chest,
+        "trap" => tile_set.trap,
+        "breakable" => tile_set.breakable_wall,
+        "ladder_left" => ladder[0],
+        "ladder_middle" => ladder[1],
+        "ladder_right" => ladder[2],
+        _ => {
+            return Err(format!(
+                "tile {} of tileset '{}' has the class '{}', which is not one of {}",
+                id,
+                tileset.name,
+                class,
+                TILE_CLASSES.join(", ")
+            ))
+        }
+    };
+    Ok(tile)
+}
+
+fn place_object(
+    tiled_map: &TiledMap,
+    object: &MapObject,
+    map_data: &mut MapData,
+) -> Result<(), TiledError> {
+    let label = if object.name.is_empty() {
+        format!("object {}", object.id)
+    } else {
+        format!("object {} '{}'", object.id, object.name)
+    };
+
+    // the tile under the middle of the object, so a shape drawn a little off still lands right
+    let centre_y = if object.is_tile {
+        object.y - object.height / 2.0
+    } else {
+        object.y + object.height / 2.0
+    };
+    let pos_x = ((object.x + object.width / 2.0) / tiled_map.tile_width).floor();
+    let pos_y = (centre_y / tiled_map.tile_height).floor();
+    if pos_x < 0.0
+        || pos_y < 0.0
+        || pos_x as usize >= tiled_map.width
+        || pos_y as usize >= tiled_map.height
+    {
+        return Err(TiledError::at_start(format!(
+            "{} at {},{} is outside the map",
+            label, object.x, object.y
+        )));
+    }
+    let position = Vec2::new(pos_x as usize, pos_y as usize);
+    let error = |message: String| Err(TiledError::new(position, message));
+
+    match object.class.as_str() {
+        "spawn" => match map_data.spawn {
+            Some(spawn) => error(format!(
+                "{} is a second spawn point, the first one is at {},{}",
+                label, spawn.x, spawn.y
+            )),
+            None => {
+                map_data.spawn = Some(position);
+                Ok(())
+            }
+        },
+        "exit" => {
+            let ladder: Vec<char> = map_data.tile_set.ladder.chars().collect();
+            if position.x == 0 || position.x + 1 >= tiled_map.width {
+                return error(format!(
+                    "{} needs a tile on either side for its ladder",
+                    label
+                ));
+            }
+            for (tile, pos_x) in ladder.into_iter().zip(position.x - 1..) {
+                map_data.map[position.y][pos_x] = Space::new(tile);
+            }
+            Ok(())
+        }
+        "monster" => match Monster::tile_from_name(&object.name) {
+            Some(tile) => {
+                map_data.monster_spawns.push((position, tile));
+                Ok(())
+            }
+            None => error(format!(
+                "{} is not a monster, name it one of {}",
+                label,
+                MONSTER_NAMES.join(", ")
+            )),
+        },
+        "item" => match Item::from_name(&object.name) {
+            Some(item) => {
+                map_data.add_to_item_pile(position, vec![item]);
+                Ok(())
+            }
+            None => error(format!(
+                "{} is not an item, name it one of {}",
+                label,
+                ITEM_NAMES.join(", ")
+            )),
+        },
+        "" => error(format!(
+            "{} has no class, give it one of {}",
+            label,
+            OBJECT_CLASSES.join(", ")
+        )),
+        class => error(format!(
+            "{} has the class '{}', which is not one of {}",
+            label,
+            class,
+            OBJECT_CLASSES.join(", ")
+        )),
+    }
+}
+
+// a tileset kept in a file of its own, relative to the map
+fn read_external_tileset(
+    directory: &Path,
+    source: &str,
+    first_gid: u32,
+) -> Result<Tileset, TiledError> {
+    let path = directory.join(source);
+    let contents = fs::read_to_string(&path).map_err(|error| {
+        TiledError::at_start(format!(
+            "the tileset {} could not be read: {}",
+            path.display(),
+            error
+        ))
+    })?;
+    let in_tileset = |error: TiledError| {
+        TiledError::at_start(format!("in the tileset {}: {}", source, error.message))
+    };
+
+    if path.extension().is_some_and(|extension| extension == "tsx") {
+        let root = parse_xml(&contents).map_err(in_tileset)?;
+        Ok(read_tmx_tileset(&root, first_gid))
+    } else {
+        let tileset: JsonTileset =
+            serde_json::from_str(&contents).map_err(|error| in_tileset(json_error(error)))?;
+        Ok(tileset.into_tileset(first_gid))
+    }
+}
+
+// the .tmj format, also written as plain .json
+
+#[derive(Deserialize)]
+struct JsonMap {
+    #[serde(default = "get_orthogonal")]
+    orientation: String,
+    #[serde(default)]
+    infinite: bool,
+    width: usize,
+    height: usize,
+    tilewidth: f64,
+    tileheight: f64,
+    #[serde(default)]
+    layers: Vec<JsonLayer>,
+    #[serde(default)]
+    tilesets: Vec<JsonTileset>,
+}
+
+#[derive(Deserialize)]
+struct JsonLayer {
+    #[serde(default)]
+    name: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    data: Value,
+    #[serde(default)]
+    objects: Vec<JsonObject>,
+    // the layers of a group
+    #[serde(default)]
+    layers: Vec<JsonLayer>,
+}
+
+#[derive(Deserialize)]
+struct JsonTileset {
+    #[serde(default)]
+    firstgid: u32,
+    source: Option<String>,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    tiles: Vec<JsonTile>,
+}
+
+#[derive(Deserialize)]
+struct JsonTile {
+    id: u32,
+    #[serde(default, rename = "type", alias = "class")]
+    class: String,
+}
+
+#[derive(Deserialize)]
+struct JsonObject {
+    #[serde(default)]
+    id: u32,
+    #[serde(default)]
+    name: String,
+    #[serde(default, rename = "type", alias = "class")]
+    class: String,
+    x: f64,
+    y: f64,
+    #[serde(default)]
+    width: f64,
+    #[serde(default)]
+    height: f64,
+    gid: Option<u32>,
+}
+
+fn get_orthogonal() -> String {
+    "orthogonal".to_string()
+}
+
+impl JsonTileset {
+    fn into_tileset(self, first_gid: u32) -> Tileset {
+        Tileset {
+            first_gid,
+            name: self.name,
+            classes: self
+                .tiles
+                .into_iter()
+                .map(|tile| (tile.id, tile.class))
+                .collect(),
+        }
+    }
+}
+
+fn json_error(error: serde_json::Error) -> TiledError {
+    TiledError::new(
+        Vec2::new(
+            error.column().saturating_sub(1),
+            error.line().saturating_sub(1),
+        ),
+        format!("the file is not a Tiled map: {}", error),
+    )
+}
+
+fn read_json(contents: &str, directory: &Path) -> Result<TiledMap, TiledError> {
+    let json_map: JsonMap = serde_json::from_str(contents).map_err(json_error)?;
+    let mut tiled_map = TiledMap {
+        orientation: json_map.orientation,
+        is_infinite: json_map.infinite,
+        width: json_map.width,
+        height: json_map.height,
+        tile_width: json_map.tilewidth,
+        tile_height: json_map.tileheight,
+        tilesets: Vec::new(),
+        tile_layers: Vec::new(),
+        objects: Vec::new(),
+    };
+
+    for tileset in json_map.tilesets {
+        let first_gid = tileset.firstgid;
+        tiled_map.tilesets.push(match &tileset.source {
+            Some(source) => read_external_tileset(directory, source, first_gid)?,
+            None => tileset.into_tileset(first_gid),
+        });
+    }
+    read_json_layers(json_map.layers, &mut tiled_map)?;
+    Ok(tiled_map)
+}
+
+fn read_json_layers(layers: Vec<JsonLayer>, tiled_map: &mut TiledMap) -> Result<(), TiledError> {
+    for layer in layers {
+        match layer.kind.as_str() {
+            "tilelayer" => {
+                let gids = match layer.data {
+                    Value::Array(values) => values
+                        .iter()
+                        .map(|value| value.as_u64().unwrap_or(0) as u32)
+                        .collect(),
+                    _ => {
+                        return Err(TiledError::at_start(format!(
+                            "layer '{}' is compressed or base64 encoded, save it with the CSV tile layer format",
+                            layer.name
+                        )))
+                    }
+                };
+                tiled_map.tile_layers.push(TileLayer {
+                    name: layer.name,
+                    gids,
+                });
+            }
+            "objectgroup" => {
+                tiled_map
+                    .objects
+                    .extend(layer.objects.into_iter().map(|object| MapObject {
+                        id: object.id,
+                        class: object.class,
+                        name: object.name,
+                        x: object.x,
+                        y: object.y,
+                        width: object.width,
+                        height: object.height,
+                        is_tile: object.gid.is_some(),
+                    }));
+            }
+            "group" => read_json_layers(layer.layers, tiled_map)?,
+            // image layers are only decoration
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+// the .tmx format
+
+fn read_tmx(contents: &str, directory: &Path) -> Result<TiledMap, TiledError> {
+    let root = parse_xml(contents)?;
+    if root.name != "map" {
+        return Err(TiledError::new(
+            root.position,
+            format!(
+                "the file is not a Tiled map, it starts with <{}>",
+                root.name
+            ),
+        ));
+    }
+
+    let number = |element: &XmlElement, name: &str| -> Result<f64, TiledError> {
+        element
+            .get(name)
+            .and_then(|value| value.parse().ok())
+            .ok_or(TiledError::new(
+                element.position,
+                format!("<{}> needs a number for {}", element.name, name),
+            ))
+    };
+    let mut tiled_map = TiledMap {
+        orientation: root.get("orientation").unwrap_or("orthogonal").to_string(),
+        is_infinite: root.get("infinite") == Some("1"),
+        width: number(&root, "width")? as usize,
+        height: number(&root, "height")? as usize,
+        tile_width: number(&root, "tilewidth")?,
+        tile_height: number(&root, "tileheight")?,
+        tilesets: Vec::new(),
+        tile_layers: Vec::new(),
+        objects: Vec::new(),
+    };
+
+    for tileset in root.get_children("tileset") {
+        let first_gid = number(tileset, "firstgid")? as u32;
+        tiled_map.tilesets.push(match tileset.get("source") {
+            Some(source) => read_external_tileset(directory, source, first_gid)?,
+            None => read_tmx_tileset(tileset, first_gid),
+        });
+    }
+    read_tmx_layers(&root, &mut tiled_map, &number)?;
+    Ok(tiled_map)
+}
+
+fn read_tmx_tileset(tileset: &XmlElement, first_gid: u32) -> Tileset {
+    Tileset {
+        first_gid,
+        name: tileset.get("name").unwrap_or_default().to_string(),
+        classes: tileset
+            .get_children("tile")
+            .filter_map(|tile| {
+                let id = tile.get("id")?.parse().ok()?;
+                let class = tile.get("class").or(tile.get("type")).unwrap_or_default();
+                Some((id, class.to_string()))
+            })
+            .collect(),
+    }
+}
+
+fn read_tmx_layers(
+    parent: &XmlElement,
+    tiled_map: &mut TiledMap,
+    number: &impl Fn(&XmlElement, &str) -> Result<f64, TiledError>,
+) -> Result<(), TiledError> {
+    for layer in &parent.children {
+        match layer.name.as_str() {
+            "layer" => {
+                let name = layer.get("name").unwrap_or_default().to_string();
+                let data = layer.get_children("data").next().ok_or(TiledError::new(
+                    layer.position,
+                    format!("layer '{}' has no data", name),
+                ))?;
+                let gids = match (data.get("encoding"), data.get("compression")) {
+                    (Some("csv"), None) => data
+                        .text
+                        .split(',')
+                        .map(|gid| gid.trim().parse().unwrap_or(0))
+                        .collect(),
+                    // without an encoding every tile is an element of its own
+                    (None, None) => data
+                        .get_children("tile")
+                        .map(|tile| tile.get("gid").and_then(|gid| gid.parse().ok()).unwrap_or(0))
+                        .collect(),
+                    _ => {
+                        return Err(TiledError::new(
+                            data.position,
+                            format!(
+                                "layer '{}' is compressed or base64 encoded, save it with the CSV tile layer format",
+                                name
+                            ),
+                        ))
+                    }
+                };
+                tiled_map.tile_layers.push(TileLayer { name, gids });
+            }
+            "objectgroup" => {
+                for object in layer.get_children("object") {
+                    tiled_map.objects.push(MapObject {
+                        id: number(object, "id").unwrap_or(0.0) as u32,
+                        class: object
+                            .get("class")
+                            .or(object.get("type"))
+                            .unwrap_or_default()
+                            .to_string(),
+                        name: object.get("name").unwrap_or_default().to_string(),
+                        x: number(object, "x")?,
+                        y: number(object, "y")?,
+                        width: number(object, "width").unwrap_or(0.0),
+                        height: number(object, "height").unwrap_or(0.0),
+                        is_tile: object.get("gid").is_some(),
+                    });
+                }
+            }
+            "group" => read_tmx_layers(layer, tiled_map, number)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+// just enough xml for Tiled files, no namespaces or doctypes with declarations in them
+
+struct XmlElement {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<XmlElement>,
+    text: String,
+    // of the start tag
+    position: Vec2,
+}
+
+impl XmlElement {
+    fn get(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn get_children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlElement> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+}
+
+struct XmlReader {
+    chars: Vec<char>,
+    index: usize,
+    // zero based, TiledError positions are too
+    line: usize,
+    column: usize,
+}
+
+fn parse_xml(contents: &str) -> Result<XmlElement, TiledError> {
+    let mut reader = XmlReader {
+        chars: contents.chars().collect(),
+        index: 0,
+        line: 0,
+        column: 0,
+    };
+    reader.skip_prolog()?;
+    reader.read_element()
+}
+
+impl XmlReader {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let next = self.peek()?;
+        self.index += 1;
+        if next == '\n' {
+            self.line += 1;
+            self.column = 0;
+        } else {
+            self.column += 1;
+        }
+        Some(next)
+    }
+
+    fn starts_with(&self, text: &str) -> bool {
+        text.chars()
+            .enumerate()
+            .all(|(offset, char)| self.chars.get(self.index + offset) == Some(&char))
+    }
+
+    fn get_position(&self) -> Vec2 {
+        Vec2::new(self.column, self.line)
+    }
+
+    fn error(&self, message: String) -> TiledError {
+        TiledError::new(self.get_position(), message)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.next();
+        }
+    }
+
+    // reads up to and past the end marker, returning what came before it
+    fn read_until(&mut self, end: &str) -> Result<String, TiledError> {
+        let mut text = String::new();
+        while !self.starts_with(end) {
+            text.push(
+                self.next()
+                    .ok_or(self.error(format!("the file ends before {}", end)))?,
+            );
+        }
+        for _ in end.chars() {
+            self.next();
+        }
+        Ok(text)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), TiledError> {
+        match self.next() {
+            Some(next) if next == expected => Ok(()),
+            Some(next) => Err(self.error(format!("expected '{}' but found '{}'", expected, next))),
+            None => Err(self.error(format!("expected '{}' but the file ends", expected))),
+        }
+    }
+
+    fn read_name(&mut self) -> Result<String, TiledError> {
+        let mut name = String::new();
+        while let Some(next) = self.peek() {
+            if !(next.is_alphanumeric() || "_-.:".contains(next)) {
+                break;
+            }
+            name.push(next);
+            self.next();
+        }
+        if name.is_empty() {
+            return Err(self.error("expected a name".to_string()));
+        }
+        Ok(name)
+    }
+
+    // the xml declaration, comments and a doctype can all come before the root element
+    fn skip_prolog(&mut self) -> Result<(), TiledError> {
+        loop {
+            self.skip_whitespace();
+            if self.starts_with("<?") {
+                self.read_until("?>")?;
+            } else if self.starts_with("<!--") {
+                self.read_until("-->")?;
+            } else if self.starts_with("<!") {
+                self.read_until(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn read_element(&mut self) -> Result<XmlElement, TiledError> {
+        let position = self.get_position();
+        self.expect('<')?;
+        let mut element = XmlElement {
+            name: self.read_name()?,
+            attributes: Vec::new(),
+            children: Vec::new(),
+            text: String::new(),
+            position,
+        };
+
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('/') => {
+                    self.next();
+                    self.expect('>')?;
+                    return Ok(element);
+                }
+                Some('>') => {
+                    self.next();
+                    break;
+                }
+                Some(_) => {
+                    let key = self.read_name()?;
+                    self.skip_whitespace();
+                    self.expect('=')?;
+                    self.skip_whitespace();
+                    let quote = match self.next() {
+                        Some(quote @ ('"' | '\'')) => quote,
+                        _ => return Err(self.error(format!("the value of {} needs quotes", key))),
+                    };
+                    let value = self.read_until(&quote.to_string())?;
+                    element.attributes.push((key, decode_entities(&value)));
+                }
+                None => return Err(self.error(format!("the file ends inside <{}>", element.name))),
+            }
+        }
+
+        loop {
+            if self.starts_with("</") {
+                self.next();
+                self.next();
+                let name = self.read_name()?;
+                if name != element.name {
+                    return Err(self.error(format!(
+                        "</{}> closes <{}> from line {}",
+                        name,
+                        element.name,
+                        element.position.y + 1
+                    )));
+                }
+                self.skip_whitespace();
+                self.expect('>')?;
+                return Ok(element);
+            } else if self.starts_with("<!--") {
+                self.read_until("-->")?;
+            } else if self.starts_with("<![CDATA[") {
+                self.read_until("<![CDATA[")?;
+                let text = self.read_until("]]>")?;
+                element.text.push_str(&text);
+            } else if self.peek() == Some('<') {
+                element.children.push(self.read_element()?);
+            } else {
+                let text = self.read_text(&element.name)?;
+                element.text.push_str(&decode_entities(&text));
+            }
+        }
+    }
+
+    fn read_text(&mut self, element_name: &str) -> Result<String, TiledError> {
+        let mut text = String::new();
+        while self.peek() != Some('<') {
+            text.push(
+                self.next()
+                    .ok_or(self.error(format!("the file ends before </{}>", element_name)))?,
+            );
+        }
+        Ok(text)
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..end];
+        let char = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| {
+                    entity
+                        .strip_prefix('#')
+                        .and_then(|decimal| decimal.parse().ok())
+                })
+                .and_then(char::from_u32),
+        };
+        match char {
+            Some(char) => {
+                decoded.push(char);
+                rest = &rest[end + 1..];
+            }
+            // not an entity after all, keep the ampersand as it is
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
@@ -1,3 +1,5 @@
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
 #[derive(Clone, Copy, Eq, Hash, PartialEq, PartialOrd, Ord, Debug)]
 pub struct Vec2 {
     pub x: usize,
@@ -10,4 +12,25 @@ impl Vec2 {
     pub const fn new(x: usize, y: usize) -> Self {
         Self { x, y }
     }
+
+    // reads "x,y", the way positions are written in saves, replays and on the command line
+    pub fn from_text(text: &str) -> Option<Self> {
+        let (x, y) = text.split_once(',')?;
+        Some(Vec2::new(x.trim().parse().ok()?, y.trim().parse().ok()?))
+    }
+}
+
+// saved as "x,y" so positions can also be the keys of a json object
+impl Serialize for Vec2 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{},{}", self.x, self.y))
+    }
+}
+
+impl<'de> Deserialize<'de> for Vec2 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Vec2::from_text(&text)
+            .ok_or_else(|| de::Error::custom(format!("'{}' is not a position", text)))
+    }
 }
@@ -0,0 +1,58 @@
+use crate::tile_set::DEFAULT_TILE_SET;
+use crate::Vec2;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum WorldObjectKind {
+    Lever {
+        is_on: bool,
+    },
+    PressurePlate,
+    Chest {
+        contents: Vec<String>,
+        loot_table: Option<String>,
+        is_open: bool,
+    },
+    Trap {
+        damage: i32,
+        is_hidden: bool,
+    },
+    BreakableWall {
+        durability: i32,
+    },
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct WorldObject {
+    pub kind: WorldObjectKind,
+    // tiles this object activates, e.g. the doors a lever opens or the traps a plate fires
+    pub targets: Vec<Vec2>,
+}
+
+impl WorldObject {
+    pub(crate) fn new(kind: WorldObjectKind) -> Self {
+        WorldObject {
+            kind,
+            targets: Vec::new(),
+        }
+    }
+
+    pub(crate) fn with_targets(mut self, targets: Vec<Vec2>) -> Self {
+        self.targets = targets;
+        self
+    }
+
+    // the glyph to draw for the object, hidden traps look like plain floor
+    pub(crate) fn get_tile(&self) -> char {
+        match self.kind {
+            WorldObjectKind::Lever { .. } => DEFAULT_TILE_SET.lever,
+            WorldObjectKind::PressurePlate => DEFAULT_TILE_SET.pressure_plate,
+            WorldObjectKind::Chest { .. } => DEFAULT_TILE_SET.chest,
+            WorldObjectKind::Trap {
+                is_hidden: true, ..
+            } => DEFAULT_TILE_SET.floor,
+            WorldObjectKind::Trap { .. } => DEFAULT_TILE_SET.trap,
+            WorldObjectKind::BreakableWall { .. } => DEFAULT_TILE_SET.breakable_wall,
+        }
+    }
+}
@@ -0,0 +1,9 @@
+<?xml version="1.0" encoding="UTF-8"?>
+<tileset version="1.10" tiledversion="1.10.2" name="dungeon" tilewidth="16" tileheight="16" tilecount="6" columns="6">
+ <image source="dungeon.png" width="96" height="16"/>
+ <tile id="0" class="floor"/>
+ <tile id="1" class="wall"/>
+ <tile id="2" class="door_side"/>
+ <tile id="3" type="key"/>
+ <tile id="5" class="lava"/>
+</tileset>
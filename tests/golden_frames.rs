@@ -0,0 +1,169 @@
+// scripted runs on the maps in src/maps, checked against tests/golden or against the game state
+mod harness;
+
+use harness::Harness;
+use project_aether::action::Action;
+use project_aether::chat::MessageCategory;
+use project_aether::game::{Game, GameConfig, StartError, MAP_NAMES};
+use project_aether::vec2::Vec2;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+const FIXTURE_MAPS: &[&str] = &["scene_ladder", "map1", "test_map", "map2", "map3"];
+
+#[test]
+fn every_map_starts_as_drawn() {
+    for map_name in FIXTURE_MAPS {
+        Harness::on_map(map_name).assert_frame(&format!("{}_start", map_name));
+    }
+}
+
+#[test]
+fn picking_up_the_key_on_map2() {
+    Harness::on_map("map2")
+        .run(
+            "
+            move_down
+            move_right*3
+            ",
+        )
+        .assert_frame("map2_key");
+}
+
+#[test]
+fn the_backpack_opens_over_the_sidebar() {
+    Harness::on_map("map2")
+        .run("inventory move_down")
+        .assert_frame("map2_backpack");
+}
+
+#[test]
+fn console_commands_show_up_on_screen() {
+    Harness::on_map("map3")
+        .run(
+            "
+            > reveal
+            > give potion 3
+            ",
+        )
+        .assert_frame("map3_revealed");
+}
+
+#[test]
+fn walls_stop_the_player() {
+    let mut harness = Harness::on_map("test_map");
+    harness.run("move_down*10");
+    assert_eq!(harness.game.player().position, Vec2::new(10, 15));
+
+    harness.run("move_right*80");
+    assert_eq!(harness.game.player().position, Vec2::new(84, 15));
+}
+
+#[test]
+fn every_action_that_moves_takes_a_turn() {
+    let mut harness = Harness::on_map("test_map");
+    harness.run("move_right*4 move_down*2 wait");
+    assert_eq!(harness.game.turn(), 7);
+    assert!(harness.game.player().is_alive);
+}
+
+#[test]
+fn the_player_can_start_anywhere_walkable() {
+    let start = |spawn| {
+        Game::new(GameConfig {
+            start_map: "test_map".to_string(),
+            spawn: Some(spawn),
+            ..GameConfig::default()
+        })
+    };
+    let game = start(Vec2::new(12, 11)).expect("a walkable spawn");
+    assert_eq!(game.player().position, Vec2::new(12, 11));
+
+    assert!(matches!(
+        start(Vec2::new(0, 0)),
+        Err(StartError::SpawnBlocked(_))
+    ));
+    assert!(matches!(
+        start(Vec2::new(500, 2)),
+        Err(StartError::SpawnOutside(_))
+    ));
+}
+
+#[test]
+fn unknown_maps_are_refused() {
+    let config = GameConfig {
+        start_map: "map9".to_string(),
+        ..GameConfig::default()
+    };
+    assert!(matches!(Game::new(config), Err(StartError::UnknownMap(_))));
+}
+
+// the monsters only get to move once the player does, so walk around for a while on every map
+#[test]
+fn every_map_survives_a_random_walk() {
+    const MOVES: [Action; 5] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Wait,
+    ];
+    for map_name in MAP_NAMES {
+        for seed in 0..4 {
+            let mut harness = Harness::on_map(map_name);
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            for _ in 0..40 {
+                harness.game.apply(MOVES[rng.gen_range(0..MOVES.len())]);
+            }
+            assert!(harness.game.player().is_alive, "{} with seed {}", map_name, seed);
+        }
+    }
+}
+
+#[test]
+fn the_ladder_leads_down_to_map2_from_the_start() {
+    let mut harness = Harness::on_map("scene_ladder");
+    harness.run("move_down");
+    // nobody climbed up from map2, so its starting spot is where the player steps off
+    let position = harness.game.player().position;
+    assert_eq!(position, Vec2::new(6, 2));
+    assert_eq!(harness.game.map().get_space(position).map(|space| space.tile), Some('@'));
+    harness.run("move_right wait");
+    assert_eq!(harness.game.player().position, Vec2::new(7, 2));
+}
+
+// the monsters stay on the map they were spawned on while the player is somewhere else
+#[test]
+fn every_map_loads_from_the_console() {
+    for start_map in MAP_NAMES {
+        for map_name in MAP_NAMES {
+            let mut harness = Harness::on_map(start_map);
+            harness.run(&format!("> loadmap {}\nwait*3", map_name));
+            let game = &harness.game;
+            for monster in game.monsters() {
+                assert!(
+                    game.map().get_space(monster.position).is_some_and(|space| space.is_monster),
+                    "a monster from {} is loose on {}",
+                    start_map,
+                    map_name
+                );
+            }
+        }
+    }
+}
+
+// the monsters' debug lines come between every turn's messages, they mustn't split the repeats
+#[test]
+fn repeated_messages_collapse_around_debug_lines() {
+    let mut harness = Harness::on_map("map3");
+    harness.run("wait*50");
+    let chat = harness.game.messages();
+    let waits = chat.get_chat_lines(1)[0];
+    assert_eq!(waits.get_text(), "You wait. x50");
+    let gameplay_count = chat
+        .messages
+        .iter()
+        .filter(|message| message.category != MessageCategory::Debug)
+        .count();
+    assert!(gameplay_count < 10, "{} gameplay messages", gameplay_count);
+}
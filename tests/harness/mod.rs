@@ -0,0 +1,133 @@
+// plays a game from a script without a terminal and compares what it draws against golden files
+//
+//   # comments start with a hash
+//   move_right*3 move_down
+//   > give potion 2
+//
+// words are action names, *n repeats one, and a line starting with > goes to the console
+use project_aether::action::Action;
+use project_aether::game::{Game, GameConfig, MAP_NAMES};
+use project_aether::game_client::GameClient;
+use project_aether::input::{KeyBindings, Preset};
+use project_aether::renderer::MemoryTarget;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+pub const FRAME_WIDTH: usize = 90;
+pub const FRAME_HEIGHT: usize = 30;
+
+const GOLDEN_DIRECTORY: &str = "tests/golden";
+// set to rewrite the golden files from whatever gets drawn now, then review the diff
+const UPDATE_VARIABLE: &str = "UPDATE_GOLDEN";
+
+pub struct Harness {
+    pub game: Game,
+    client: GameClient<MemoryTarget>,
+}
+
+impl Harness {
+    // a new game on one of the maps in src/maps, with everything else at its defaults
+    pub fn on_map(map_name: &str) -> Self {
+        assert!(
+            MAP_NAMES.contains(&map_name),
+            "{} is not one of {:?}",
+            map_name,
+            MAP_NAMES
+        );
+        let config = GameConfig {
+            start_map: map_name.to_string(),
+            ..GameConfig::default()
+        };
+        Harness {
+            game: Game::new(config).expect("a new game"),
+            client: GameClient::with_target(
+                KeyBindings::new(Preset::Arrows),
+                MemoryTarget::new(FRAME_WIDTH, FRAME_HEIGHT),
+            ),
+        }
+    }
+
+    pub fn run(&mut self, script: &str) -> &mut Self {
+        for line in script.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(command) = line.strip_prefix('>') {
+                self.game.run_command(command.trim());
+                continue;
+            }
+
+            for word in line.split_whitespace() {
+                let (name, count) = match word.split_once('*') {
+                    Some((name, count)) => (
+                        name,
+                        count
+                            .parse()
+                            .unwrap_or_else(|_| panic!("'{}' has a bad repeat count", word)),
+                    ),
+                    None => (word, 1),
+                };
+                let action = Action::from_name(name)
+                    .unwrap_or_else(|| panic!("'{}' is not an action", name));
+                for _ in 0..count {
+                    self.game.apply(action);
+                }
+            }
+        }
+        self
+    }
+
+    // draws the game the way the terminal would and returns the characters of the frame
+    pub fn frame(&mut self) -> String {
+        self.client.print_terminal(&self.game);
+        self.client
+            .get_target()
+            .last_frame
+            .as_ref()
+            .expect("the client presented a frame")
+            .get_text()
+    }
+
+    pub fn assert_frame(&mut self, name: &str) {
+        let frame = self.frame();
+        let path = PathBuf::from(GOLDEN_DIRECTORY).join(format!("{}.txt", name));
+
+        if env::var_os(UPDATE_VARIABLE).is_some() {
+            fs::create_dir_all(GOLDEN_DIRECTORY).expect("golden directory");
+            fs::write(&path, &frame).expect("golden file");
+            return;
+        }
+
+        let golden = fs::read_to_string(&path).unwrap_or_else(|_| {
+            panic!(
+                "{} is missing, run with {}=1 to create it",
+                path.display(),
+                UPDATE_VARIABLE
+            )
+        });
+        if frame == golden {
+            return;
+        }
+
+        // the first differing line is usually all it takes to see what moved
+        let row_count = golden.lines().count().max(frame.lines().count());
+        let (row, (expected, found)) = golden
+            .lines()
+            .chain(std::iter::repeat(""))
+            .zip(frame.lines().chain(std::iter::repeat("")))
+            .take(row_count)
+            .enumerate()
+            .find(|(_, (expected, found))| expected != found)
+            .unwrap_or((row_count, ("", "")));
+        panic!(
+            "frame {} differs from {} at row {}\nexpected: {}\n   found: {}\n\n{}",
+            name,
+            path.display(),
+            row,
+            expected,
+            found,
+            frame
+        );
+    }
+}
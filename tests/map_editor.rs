@@ -0,0 +1,195 @@
+// the editor paints onto the current map, undoes it step by step and writes it back out in the
+// map file format
+use project_aether::action::Action;
+use project_aether::game::{Game, GameConfig, GameEvent};
+use project_aether::map_editor::{Brush, EditorInput};
+use project_aether::map_loader;
+use project_aether::replay::{Replay, ReplayWriter};
+use project_aether::vec2::Vec2;
+use std::fs;
+
+// map3 starts the player in the top left room, the bottom left one is free of monsters
+fn editing_map3() -> Game {
+    let config = GameConfig {
+        start_map: "map3".to_string(),
+        ..GameConfig::default()
+    };
+    let mut game = Game::new(config).expect("a new game");
+    game.run_command("edit on");
+    assert!(game.editor().is_some());
+    game
+}
+
+fn edit(game: &mut Game, inputs: &[EditorInput]) {
+    for input in inputs {
+        assert!(game.step(GameEvent::Edit(input.clone())), "{:?}", input);
+    }
+}
+
+fn select_brush(game: &mut Game, brush: Brush) {
+    while game.editor().expect("the editor").get_brush() != brush {
+        edit(game, &[EditorInput::NextBrush]);
+    }
+}
+
+fn tile_at(game: &Game, x: usize, y: usize) -> char {
+    game.map().get_space(Vec2::new(x, y)).expect("a tile").tile
+}
+
+#[test]
+fn painting_can_be_undone_and_redone() {
+    let mut game = editing_map3();
+    select_brush(&mut game, Brush::Wall);
+    edit(&mut game, &[EditorInput::Move(0, 4), EditorInput::Paint]);
+    assert_eq!(tile_at(&game, 1, 5), '#');
+
+    edit(&mut game, &[EditorInput::Undo]);
+    assert_eq!(tile_at(&game, 1, 5), '.');
+    edit(&mut game, &[EditorInput::Redo]);
+    assert_eq!(tile_at(&game, 1, 5), '#');
+    assert!(!game.step(GameEvent::Edit(EditorInput::Redo)));
+}
+
+#[test]
+fn a_rectangle_fills_as_one_step() {
+    let mut game = editing_map3();
+    select_brush(&mut game, Brush::Wall);
+    edit(
+        &mut game,
+        &[
+            EditorInput::Move(0, 4),
+            EditorInput::Corner,
+            EditorInput::Move(1, 1),
+            EditorInput::Corner,
+        ],
+    );
+    for (x, y) in [(1, 5), (2, 5), (1, 6), (2, 6)] {
+        assert_eq!(tile_at(&game, x, y), '#');
+    }
+    assert_eq!(tile_at(&game, 3, 5), '.');
+
+    edit(&mut game, &[EditorInput::Undo]);
+    for (x, y) in [(1, 5), (2, 5), (1, 6), (2, 6)] {
+        assert_eq!(tile_at(&game, x, y), '.');
+    }
+}
+
+#[test]
+fn the_world_waits_while_editing() {
+    let mut game = editing_map3();
+    let position = game.player().position;
+    assert!(!game.step(GameEvent::Input(Action::MoveRight)));
+    assert_eq!(game.player().position, position);
+
+    edit(&mut game, &[EditorInput::Close]);
+    assert!(game.editor().is_none());
+    assert!(game.step(GameEvent::Input(Action::Wait)));
+}
+
+#[test]
+fn a_saved_map_loads_back_cleanly() {
+    let mut game = editing_map3();
+    edit(&mut game, &[EditorInput::Move(2, 4)]);
+    select_brush(&mut game, Brush::Spawn);
+    edit(&mut game, &[EditorInput::Paint, EditorInput::Move(1, 0)]);
+    select_brush(&mut game, Brush::Monster('g'));
+    edit(&mut game, &[EditorInput::Paint, EditorInput::Move(1, 0)]);
+    select_brush(&mut game, Brush::Item("healing_potion"));
+    edit(&mut game, &[EditorInput::Paint]);
+
+    let path = std::env::temp_dir().join("project_aether_edited_map.txt");
+    game.save_map_to(&path).expect("the map is written");
+    let contents = fs::read_to_string(&path).expect("the saved map");
+    let rows: Vec<&str> = contents.lines().collect();
+    // the player is lifted off the map and the new starting spot drawn in
+    assert_eq!(&rows[1][..2], "#.");
+    assert_eq!(&rows[5][..4], "#..@");
+    assert!(!contents.contains(['g', 's']), "{}", contents);
+
+    let metadata = fs::read_to_string(path.with_extension("meta")).expect("the saved metadata");
+    assert!(metadata.contains("monster 4,5 goblin"), "{}", metadata);
+    assert!(metadata.contains("item 5,5 healing_potion"), "{}", metadata);
+
+    let diagnostics = map_loader::check(&path).unwrap_or_else(|error| panic!("{}", error));
+    assert!(diagnostics.is_empty(), "{}", diagnostics[0]);
+}
+
+#[test]
+fn edits_play_back_from_a_replay() {
+    let config = GameConfig {
+        start_map: "map3".to_string(),
+        ..GameConfig::default()
+    };
+    let events = [
+        GameEvent::Command("edit on".to_string()),
+        GameEvent::Edit(EditorInput::Move(0, 4)),
+        GameEvent::Edit(EditorInput::NextBrush),
+        GameEvent::Edit(EditorInput::Paint),
+        GameEvent::Edit(EditorInput::Undo),
+        GameEvent::Edit(EditorInput::Redo),
+        GameEvent::Edit(EditorInput::Close),
+        GameEvent::Input(Action::Wait),
+    ];
+
+    let path = std::env::temp_dir().join("project_aether_editor.replay");
+    let mut game = Game::new(config.clone()).expect("a new game");
+    let mut writer = ReplayWriter::create(&path, &config).expect("replay file");
+    for event in &events {
+        assert!(game.step(event.clone()));
+        writer.record(&game, event).expect("replay line");
+    }
+
+    let contents = fs::read_to_string(&path).expect("recorded replay");
+    let replay = Replay::parse(&contents).expect("a valid replay");
+    assert_eq!(
+        replay.entries[1].event,
+        GameEvent::Edit(EditorInput::Move(0, 4))
+    );
+    let replayed = replay.verify().unwrap_or_else(|error| panic!("{}", error));
+    assert_eq!(replayed.state_hash(), game.state_hash());
+}
+
+// plays map2 for a while, then opens the editor and saves the map with its metadata
+fn save_map2(name: &str, play: impl FnOnce(&mut Game)) -> (String, String) {
+    let config = GameConfig {
+        start_map: "map2".to_string(),
+        ..GameConfig::default()
+    };
+    let mut game = Game::new(config).expect("a new game");
+    play(&mut game);
+    game.run_command("edit on");
+
+    let path = std::env::temp_dir().join(format!("project_aether_{}.txt", name));
+    game.save_map_to(&path).expect("the map is written");
+    (
+        fs::read_to_string(&path).expect("the saved map"),
+        fs::read_to_string(path.with_extension("meta")).expect("the saved metadata"),
+    )
+}
+
+#[test]
+fn saving_writes_the_map_as_drawn_not_as_played() {
+    let played = save_map2("played_map", |game| {
+        // picks up the key below the start, then searches next to the hidden trap until it shows
+        game.apply(Action::MoveDown);
+        assert_eq!(tile_at(game, 6, 3), '.');
+        game.run_command("teleport 7 3");
+        while tile_at(game, 8, 3) != '^' {
+            game.apply(Action::Search);
+        }
+    });
+    let fresh = save_map2("fresh_map", |_| {});
+    assert_eq!(played, fresh);
+    assert!(fresh.1.contains("trap 8,3 hidden damage:4"), "{}", fresh.1);
+}
+
+#[test]
+fn only_the_map_in_the_editor_can_be_saved() {
+    let config = GameConfig {
+        start_map: "map2".to_string(),
+        ..GameConfig::default()
+    };
+    let game = Game::new(config).expect("a new game");
+    let path = std::env::temp_dir().join("project_aether_unsaved_map.txt");
+    assert!(game.save_map_to(&path).is_err());
+}
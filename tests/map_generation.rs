@@ -0,0 +1,57 @@
+// the generated map is built from the seed phrase, whatever the phrase it has to stay playable
+use project_aether::game::{Game, GameConfig};
+use project_aether::map_data::MapData;
+use project_aether::tile_set::DEFAULT_TILE_SET;
+use project_aether::vec2::Vec2;
+use std::collections::{HashSet, VecDeque};
+
+fn find_tile(map_data: &MapData, tile: char) -> Option<Vec2> {
+    map_data.map.iter().enumerate().find_map(|(pos_y, row)| {
+        row.iter()
+            .position(|space| space.tile == tile)
+            .map(|pos_x| Vec2::new(pos_x, pos_y))
+    })
+}
+
+// everywhere the player can walk to from the start without going through the door
+fn get_reachable(map_data: &MapData, start: Vec2, door: Vec2) -> HashSet<Vec2> {
+    let mut reached = HashSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(position) = queue.pop_front() {
+        for (delta_x, delta_y) in [(0, -1), (-1, 0), (0, 1), (1, 0)] {
+            let neighbour = Vec2::new(
+                position.x.wrapping_add_signed(delta_x),
+                position.y.wrapping_add_signed(delta_y),
+            );
+            let is_open = map_data.get_space(neighbour).is_some_and(|space| {
+                space.is_traversable || space.is_monster || space.tile == DEFAULT_TILE_SET.key
+            });
+            if is_open && neighbour != door && reached.insert(neighbour) {
+                queue.push_back(neighbour);
+            }
+        }
+    }
+    reached
+}
+
+// seed 43 used to drop the key in the room the door shuts off
+#[test]
+fn the_key_is_never_behind_its_own_door() {
+    for seed in 0..60 {
+        let config = GameConfig {
+            seed_phrase: format!("seed {}", seed),
+            start_map: "test".to_string(),
+            ..GameConfig::default()
+        };
+        let game = Game::new(config).expect("a new game");
+        let map_data = game.map();
+        let (Some(door), Some(key)) = (
+            find_tile(map_data, DEFAULT_TILE_SET.closed_door_side),
+            find_tile(map_data, DEFAULT_TILE_SET.key),
+        ) else {
+            continue;
+        };
+        let reachable = get_reachable(map_data, game.player().position, door);
+        assert!(reachable.contains(&key), "seed {}: key at {:?}", seed, key);
+    }
+}
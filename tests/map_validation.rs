@@ -0,0 +1,83 @@
+// every map in src/maps has to load cleanly, and each broken map in tests/fixtures/maps has to
+// be reported where it is broken
+use project_aether::map_loader::{self, MapError, Severity, MAPS_DIRECTORY};
+use std::path::Path;
+
+const FIXTURE_DIRECTORY: &str = "tests/fixtures/maps";
+
+// the line, column and severity of everything found in a fixture
+fn check_fixture(name: &str) -> Vec<(usize, usize, Severity)> {
+    let path = Path::new(FIXTURE_DIRECTORY).join(format!("{}.txt", name));
+    map_loader::check(&path)
+        .unwrap_or_else(|error| panic!("{}", error))
+        .into_iter()
+        .map(|diagnostic| (diagnostic.line, diagnostic.column, diagnostic.severity))
+        .collect()
+}
+
+#[test]
+fn the_shipped_maps_are_clean() {
+    let paths = map_loader::list_map_files(Path::new(MAPS_DIRECTORY)).expect("the maps directory");
+    assert!(!paths.is_empty());
+    for path in paths {
+        let diagnostics = map_loader::check(&path).unwrap_or_else(|error| panic!("{}", error));
+        assert!(diagnostics.is_empty(), "{}", diagnostics[0]);
+    }
+}
+
+#[test]
+fn unknown_glyphs_are_errors() {
+    assert_eq!(check_fixture("glyphs"), [(2, 3, Severity::Error)]);
+}
+
+#[test]
+fn short_rows_are_padded() {
+    // the blank line above the map still counts towards the line numbers
+    assert_eq!(check_fixture("ragged"), [(3, 5, Severity::Warning)]);
+}
+
+#[test]
+fn empty_maps_are_errors() {
+    assert_eq!(check_fixture("empty"), [(1, 1, Severity::Error)]);
+}
+
+#[test]
+fn a_door_without_a_key_cuts_off_the_ladder() {
+    assert_eq!(
+        check_fixture("locked"),
+        [(3, 4, Severity::Warning), (2, 5, Severity::Warning)]
+    );
+    assert_eq!(check_fixture("keyed"), []);
+}
+
+#[test]
+fn a_relocking_door_needs_a_way_back_out() {
+    assert_eq!(check_fixture("relocked"), [(2, 5, Severity::Warning)]);
+    assert_eq!(check_fixture("relocked_plate"), []);
+}
+
+#[test]
+fn missing_maps_are_io_errors() {
+    let path = Path::new(FIXTURE_DIRECTORY).join("missing.txt");
+    assert!(matches!(map_loader::check(&path), Err(MapError::Io(..))));
+}
+
+#[test]
+fn mistakes_in_the_metadata_are_errors() {
+    assert_eq!(
+        check_fixture("typos"),
+        [
+            (2, 11, Severity::Error),
+            (3, 11, Severity::Error),
+            (4, 10, Severity::Error),
+            (5, 20, Severity::Error),
+            (6, 1, Severity::Error),
+            (7, 6, Severity::Error),
+            (8, 10, Severity::Error),
+        ]
+    );
+    let path = Path::new(FIXTURE_DIRECTORY).join("typos.txt");
+    let diagnostics = map_loader::check(&path).expect("the fixture");
+    assert!(diagnostics.iter().all(|diagnostic| diagnostic.path.ends_with("typos.meta")));
+    assert!(diagnostics[0].message.contains("healing_potoin"));
+}
@@ -0,0 +1,130 @@
+// a recorded run has to play back to exactly the same state, turn for turn
+use project_aether::action::Action;
+use project_aether::game::{Game, GameConfig, GameEvent};
+use project_aether::replay::{Replay, ReplayError, ReplayWriter, VerifyError};
+use project_aether::scheduler::TimingMode;
+use project_aether::vec2::Vec2;
+use std::fs;
+use std::path::PathBuf;
+
+// a few moves around the starting map, with a command and some waiting thrown in
+fn sample_events() -> Vec<GameEvent> {
+    let mut events = Vec::new();
+    for action in [
+        Action::MoveRight,
+        Action::MoveRight,
+        Action::MoveDown,
+        Action::Search,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::Wait,
+        Action::Drink,
+    ] {
+        events.push(GameEvent::Input(action));
+    }
+    events.push(GameEvent::Command("give potion 2".to_string()));
+    for _ in 0..20 {
+        events.push(GameEvent::Input(Action::Wait));
+    }
+    events.push(GameEvent::Input(Action::Drink));
+    events
+}
+
+// plays the events the way the frontend does and returns what ended up in the file
+fn record(name: &str, config: &GameConfig, events: &[GameEvent]) -> (String, Game) {
+    let path: PathBuf = std::env::temp_dir().join(format!("project_aether_{}.replay", name));
+    let mut game = Game::new(config.clone()).expect("a new game");
+    let mut writer = ReplayWriter::create(&path, config).expect("replay file");
+    for event in events {
+        if game.step(event.clone()) {
+            writer.record(&game, event).expect("replay line");
+        }
+    }
+    (fs::read_to_string(&path).expect("recorded replay"), game)
+}
+
+#[test]
+fn a_recorded_run_verifies() {
+    let (contents, game) = record("turn_based", &GameConfig::default(), &sample_events());
+    let replay = Replay::parse(&contents).expect("a valid replay");
+    assert_eq!(replay.entries.len(), sample_events().len());
+
+    let replayed = replay.verify().unwrap_or_else(|error| panic!("{}", error));
+    assert_eq!(replayed.state_hash(), game.state_hash());
+    assert_eq!(replayed.turn(), game.turn());
+}
+
+#[test]
+fn real_time_ticks_play_back() {
+    let config = GameConfig {
+        seed_phrase: "ticking".to_string(),
+        timing: TimingMode::RealTime,
+        ..GameConfig::default()
+    };
+    let mut events = Vec::new();
+    for action in [Action::MoveRight, Action::MoveDown, Action::Wait] {
+        events.extend([GameEvent::Tick, GameEvent::Tick, GameEvent::Tick]);
+        events.push(GameEvent::Input(action));
+    }
+
+    let (contents, game) = record("real_time", &config, &events);
+    let replay = Replay::parse(&contents).expect("a valid replay");
+    assert_eq!(replay.timing, TimingMode::RealTime);
+    assert_eq!(replay.seed_phrase, "ticking");
+
+    let replayed = replay.verify().unwrap_or_else(|error| panic!("{}", error));
+    assert_eq!(replayed.state_hash(), game.state_hash());
+}
+
+#[test]
+fn the_spawn_and_fog_are_recorded() {
+    let config = GameConfig {
+        start_map: "test_map".to_string(),
+        spawn: Some(Vec2::new(12, 11)),
+        fog_of_war: true,
+        ..GameConfig::default()
+    };
+    let (contents, game) = record("spawn", &config, &sample_events());
+    let replay = Replay::parse(&contents).expect("a valid replay");
+    assert_eq!(replay.spawn, Some(Vec2::new(12, 11)));
+    assert!(replay.fog_of_war);
+
+    let replayed = replay.verify().unwrap_or_else(|error| panic!("{}", error));
+    assert_eq!(replayed.state_hash(), game.state_hash());
+}
+
+#[test]
+fn the_first_divergence_is_reported() {
+    let (contents, _) = record("diverging", &GameConfig::default(), &sample_events());
+
+    // swapping one move for another leaves the player somewhere else from that event on
+    let edited = contents.replacen("input move_down", "input move_up", 1);
+    let replay = Replay::parse(&edited).expect("a valid replay");
+    let divergence = match replay.verify() {
+        Err(VerifyError::Diverged(divergence)) => divergence,
+        Err(error) => panic!("unexpected error: {}", error),
+        Ok(_) => panic!("the edited run played out the same"),
+    };
+
+    assert_eq!(divergence.index, 2);
+    assert_eq!(divergence.event, GameEvent::Input(Action::MoveUp));
+    assert_ne!(divergence.expected_hash, divergence.found_hash);
+}
+
+#[test]
+fn broken_lines_name_their_line() {
+    let error_line = |contents: &str| match Replay::parse(contents) {
+        Err(ReplayError::Parse { line, .. }) => line,
+        Err(error) => panic!("unexpected error: {}", error),
+        Ok(_) => panic!("a broken replay parsed"),
+    };
+
+    assert_eq!(error_line("version = 1\n1 00ff input fly"), 2);
+    assert_eq!(error_line("# no version\n1 00ff tick"), 2);
+    assert_eq!(error_line("version = 1\ntiming = sometimes"), 2);
+    assert_eq!(error_line("version = 1\n\n1 zz tick"), 3);
+    assert!(matches!(
+        Replay::parse("version = 9"),
+        Err(ReplayError::Incompatible(9))
+    ));
+}
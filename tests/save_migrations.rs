@@ -0,0 +1,137 @@
+// every save format the game ever wrote has a fixture in tests/fixtures/saves, named after its
+// version, and each of them has to keep loading through the migration chain
+use project_aether::game::{Game, GameConfig};
+use project_aether::save::{SaveError, SAVE_VERSION};
+use project_aether::vec2::Vec2;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const FIXTURE_DIRECTORY: &str = "tests/fixtures/saves";
+
+fn fixture_path(version: u64) -> PathBuf {
+    Path::new(FIXTURE_DIRECTORY).join(format!("v{}.json", version))
+}
+
+fn load_fixture(version: u64) -> Game {
+    Game::load_file(&fixture_path(version), GameConfig::default())
+        .unwrap_or_else(|error| panic!("fixture v{} failed to load: {}", version, error))
+}
+
+// broken saves go to the temporary directory so the fixtures stay untouched
+fn write_temporary_save(name: &str, text: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("project_aether_{}.json", name));
+    fs::write(&path, text).expect("temporary save");
+    path
+}
+
+fn explored_positions(game: &Game) -> Vec<Vec2> {
+    let size = game.map().get_size();
+    (0..size.y)
+        .flat_map(|pos_y| (0..size.x).map(move |pos_x| Vec2::new(pos_x, pos_y)))
+        .filter(|position| game.map().is_explored(*position))
+        .collect()
+}
+
+#[test]
+fn there_is_a_fixture_for_every_version() {
+    for version in 1..=SAVE_VERSION {
+        assert!(
+            fixture_path(version).exists(),
+            "add a fixture saved by format {}",
+            version
+        );
+    }
+}
+
+#[test]
+fn every_fixture_loads() {
+    for version in 1..=SAVE_VERSION {
+        load_fixture(version);
+    }
+}
+
+// the fixtures were saved from the same few turns of the default seed, so once upgraded they
+// have to come out as the same game
+#[test]
+fn upgraded_fixtures_match_the_current_format() {
+    let current = load_fixture(SAVE_VERSION);
+    for version in 1..SAVE_VERSION {
+        let upgraded = load_fixture(version);
+        assert_eq!(upgraded.turn(), current.turn(), "turn of v{}", version);
+        assert_eq!(
+            upgraded.player().position,
+            current.player().position,
+            "player of v{}",
+            version
+        );
+        assert_eq!(
+            explored_positions(&upgraded),
+            explored_positions(&current),
+            "explored tiles of v{}",
+            version
+        );
+
+        let monster_positions = |game: &Game| -> Vec<(i32, Vec2)> {
+            game.monsters()
+                .map(|monster| (monster.id, monster.position))
+                .collect()
+        };
+        assert_eq!(
+            monster_positions(&upgraded),
+            monster_positions(&current),
+            "monsters of v{}",
+            version
+        );
+    }
+}
+
+#[test]
+fn format_one_keeps_its_explored_tiles() {
+    let text = fs::read_to_string(fixture_path(1)).expect("fixture v1");
+    let value: Value = serde_json::from_str(&text).expect("fixture v1 is json");
+    let map_index = value["map_manager"]["current_map_index"]
+        .as_u64()
+        .expect("current map index");
+    let explored = value["map_manager"]["maps"][map_index.to_string()]["explored"]
+        .as_array()
+        .expect("explored tiles");
+    assert!(!explored.is_empty());
+
+    let game = load_fixture(1);
+    for position in explored {
+        let position: Vec2 = serde_json::from_value(position.clone()).expect("a position");
+        assert!(
+            game.map().is_explored(position),
+            "{},{} lost its explored mark",
+            position.x,
+            position.y
+        );
+    }
+}
+
+#[test]
+fn newer_saves_are_refused() {
+    let path = write_temporary_save("newer", &format!("{{\"version\": {}}}", SAVE_VERSION + 1));
+    let result = Game::load_file(&path, GameConfig::default());
+    assert!(
+        matches!(result, Err(SaveError::Incompatible(_, version)) if version == SAVE_VERSION + 1)
+    );
+}
+
+#[test]
+fn saves_without_a_version_are_corrupted() {
+    let path = write_temporary_save("unversioned", "{\"turn\": 3}");
+    let result = Game::load_file(&path, GameConfig::default());
+    assert!(matches!(result, Err(SaveError::Corrupted(..))));
+}
+
+#[test]
+fn broken_migrations_are_reported_as_corrupted() {
+    let path = write_temporary_save("broken", "{\"version\": 1, \"map_manager\": {}}");
+    match Game::load_file(&path, GameConfig::default()) {
+        Err(SaveError::Corrupted(_, reason)) => assert!(reason.contains("format 1"), "{}", reason),
+        Err(error) => panic!("unexpected error: {}", error),
+        Ok(_) => panic!("a save without maps loaded"),
+    }
+}
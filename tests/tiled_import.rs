@@ -0,0 +1,105 @@
+// maps drawn in Tiled come out the same as if they had been typed in, whichever format they are
+// saved in, and whatever can't be matched to our tiles is reported where it is
+use project_aether::map_data::MapData;
+use project_aether::map_loader::{self, Severity};
+use project_aether::vec2::Vec2;
+use std::path::Path;
+
+const FIXTURE_DIRECTORY: &str = "tests/fixtures/tiled";
+
+fn import(name: &str) -> MapData {
+    let path = Path::new(FIXTURE_DIRECTORY).join(name);
+    map_loader::read_map(&path).unwrap_or_else(|error| panic!("{}", error))
+}
+
+fn get_rows(map_data: &MapData) -> Vec<String> {
+    map_data
+        .map
+        .iter()
+        .map(|row| row.iter().map(|space| space.tile).collect())
+        .collect()
+}
+
+fn check_fixture(name: &str) -> Vec<(usize, usize, Severity, String)> {
+    let path = Path::new(FIXTURE_DIRECTORY).join(name);
+    map_loader::check(&path)
+        .unwrap_or_else(|error| panic!("{}", error))
+        .into_iter()
+        .map(|diagnostic| {
+            (
+                diagnostic.line,
+                diagnostic.column,
+                diagnostic.severity,
+                diagnostic.message,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn both_formats_import_the_same_room() {
+    for name in ["room.tmx", "room.tmj"] {
+        let map_data = import(name);
+        assert_eq!(
+            get_rows(&map_data),
+            ["#######", "#.....#", "#..|..#", "#|-|.k#", "#######"],
+            "{}",
+            name
+        );
+        assert_eq!((map_data.width, map_data.height), (7, 5));
+        assert_eq!(map_data.spawn, Some(Vec2::new(1, 1)));
+        assert_eq!(map_data.monster_spawns, [(Vec2::new(5, 1), 'g')]);
+        let pile = &map_data.item_piles[&Vec2::new(4, 1)];
+        assert_eq!(pile[0].name, "Healing Potion");
+    }
+}
+
+#[test]
+fn imported_maps_are_checked_like_any_other() {
+    assert_eq!(check_fixture("room.tmx"), []);
+    assert_eq!(check_fixture("room.tmj"), []);
+}
+
+#[test]
+fn unknown_tiles_and_objects_are_errors() {
+    let diagnostics = check_fixture("broken.tmj");
+    let positions: Vec<(usize, usize, Severity)> = diagnostics
+        .iter()
+        .map(|(line, column, severity, _)| (*line, *column, *severity))
+        .collect();
+    assert_eq!(
+        positions,
+        [
+            (2, 2, Severity::Error),
+            (2, 3, Severity::Error),
+            (3, 4, Severity::Error),
+            (1, 1, Severity::Warning),
+        ]
+    );
+    // a tile painted twice is reported once
+    assert!(
+        diagnostics[0].3.contains("has no class"),
+        "{}",
+        diagnostics[0].3
+    );
+    assert!(
+        diagnostics[0].3.ends_with("(on 2 tiles)"),
+        "{}",
+        diagnostics[0].3
+    );
+    assert!(diagnostics[1].3.contains("'lava'"), "{}", diagnostics[1].3);
+    assert!(
+        diagnostics[2].3.contains("'dragon' is not a monster"),
+        "{}",
+        diagnostics[2].3
+    );
+}
+
+#[test]
+fn broken_xml_points_at_its_line() {
+    let diagnostics = check_fixture("unclosed.tmx");
+    assert_eq!(diagnostics.len(), 1);
+    let (line, _, severity, message) = &diagnostics[0];
+    assert_eq!((*line, *severity), (4, Severity::Error));
+    assert!(message.contains("</layer> closes <data>"), "{}", message);
+}